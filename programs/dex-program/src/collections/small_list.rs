@@ -161,6 +161,24 @@ impl<'a, T> SmallList<'a, T> {
         Ok(())
     }
 
+    // Used after the data region has been extended in place. Existing slots keep
+    // their indices, the added ones are handed out as raw slots.
+    pub fn grow(&self, slot_count: u8) -> DexResult {
+        let header = self.header()?;
+        require!(
+            slot_count >= header.total_raw && slot_count < NIL8,
+            DexError::InvalidSlotCount
+        );
+        require!(
+            Self::required_data_len(slot_count) <= self.data_len,
+            DexError::InvalidSlotCount
+        );
+
+        header.total_raw = slot_count;
+
+        Ok(())
+    }
+
     pub fn new_slot(&self) -> DexResult<&'a mut SmallListSlot<T>> {
         let header = self.header()?;
 
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::state::*,
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAuthority>) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+    require_keys_eq!(
+        dex.pending_authority,
+        ctx.accounts.new_authority.key(),
+        DexError::InvalidPendingAuthority
+    );
+
+    dex.authority = dex.pending_authority;
+    dex.pending_authority = Pubkey::default();
+
+    Ok(())
+}
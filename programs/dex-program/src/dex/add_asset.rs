@@ -6,6 +6,7 @@ use anchor_spl::token::TokenAccount;
 use crate::{
     dex::{state::*, OracleSource},
     errors::{DexError, DexResult},
+    utils::FLASH_LOAN_FEE_RATE,
 };
 
 #[derive(Accounts)]
@@ -106,7 +107,13 @@ pub fn handler(
         decimals,
         nonce,
         oracle_source,
-        padding: [0; 250],
+        flash_loan_fee_rate: FLASH_LOAN_FEE_RATE,
+        flash_loan_amount: 0,
+        protocol_fee_amount: 0,
+        trading_state: TradingState::Active as u8,
+        _align: [0; 7],
+        cached_price: 0,
+        padding: [0; 216],
     };
 
     dex.assets[asset_index] = asset;
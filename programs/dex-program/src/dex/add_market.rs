@@ -4,7 +4,7 @@ use anchor_lang::prelude::*;
 
 use crate::{
     collections::{MountMode, OrderBook, PagedList},
-    dex::{MarketInfo, OracleSource, Position},
+    dex::{MarketInfo, OracleSource, Position, TradingState},
     errors::{DexError, DexResult},
     order::Order,
     utils::{LEVERAGE_POW_DECIMALS, ORDER_POOL_MAGIC_BYTE},
@@ -68,7 +68,10 @@ pub fn handler(
     let markets = &dex.markets;
 
     market_symbol[..given_name.len()].copy_from_slice(given_name);
-    if markets.iter().any(|market| market.symbol == market_symbol) {
+    if markets
+        .iter()
+        .any(|market| market.valid && market.symbol == market_symbol)
+    {
         return Err(error!(DexError::DuplicateMarketName));
     }
 
@@ -81,10 +84,19 @@ pub fn handler(
     )
     .map_err(|_| DexError::FailedInitOrderPool)?;
 
-    let market_index = dex.markets_number as usize;
-    if market_index == dex.markets.len() {
-        return Err(error!(DexError::InsufficientMarketSlots));
-    }
+    // Reuse the slot of a removed market if there is one
+    let market_index = match dex.markets[..dex.markets_number as usize]
+        .iter()
+        .position(|market| !market.valid)
+    {
+        Some(index) => index,
+        None => {
+            if dex.markets_number as usize == dex.markets.len() {
+                return Err(error!(DexError::InsufficientMarketSlots));
+            }
+            dex.markets_number as usize
+        }
+    };
 
     let market = MarketInfo {
         symbol: market_symbol,
@@ -108,11 +120,17 @@ pub fn handler(
         asset_index,
         significant_decimals,
         order_pool_remaining_pages_number: 0,
-        padding: [0; 254],
+        trading_state: TradingState::Active as u8,
+        _align: [0; 5],
+        settle_price: 0,
+        reduce_only_since: 0,
+        padding: [0; 232],
     };
 
     dex.markets[market_index] = market;
-    dex.markets_number += 1;
+    if market_index == dex.markets_number as usize {
+        dex.markets_number += 1;
+    }
 
     Ok(())
 }
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::PagedList,
+    dex::Dex,
+    errors::{DexError, DexResult},
+    order::Order,
+    utils::ORDER_POOL_MAGIC_BYTE,
+};
+
+#[derive(Accounts)]
+pub struct AppendOrderPoolPage<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= order_pool_entry_page.owner == program_id)]
+    pub order_pool_entry_page: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= new_page.owner == program_id)]
+    pub new_page: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Layout of remaining accounts:
+/// 1. Order pool remaining pages
+pub fn handler(ctx: Context<AppendOrderPoolPage>, market: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    let mi = &mut dex.markets[market as usize];
+    require!(
+        mi.valid && mi.order_pool_entry_page == ctx.accounts.order_pool_entry_page.key(),
+        DexError::InvalidMarketIndex
+    );
+
+    let pages_number = mi.order_pool_remaining_pages_number as usize;
+    require!(
+        pages_number < mi.order_pool_remaining_pages.len(),
+        DexError::InsufficientOrderPoolPageSlots
+    );
+
+    require_eq!(
+        pages_number,
+        ctx.remaining_accounts.len(),
+        DexError::InvalidRemainingAccounts
+    );
+
+    for i in 0..pages_number {
+        require_eq!(
+            mi.order_pool_remaining_pages[i],
+            ctx.remaining_accounts[i].key(),
+            DexError::InvalidRemainingAccounts
+        );
+    }
+
+    PagedList::<Order>::append_pages(
+        &ctx.accounts.order_pool_entry_page,
+        ctx.remaining_accounts,
+        &[ctx.accounts.new_page.to_account_info()],
+        ORDER_POOL_MAGIC_BYTE,
+    )
+    .map_err(|_| DexError::FailedAppendOrderPoolPage)?;
+
+    mi.order_pool_remaining_pages[pages_number] = ctx.accounts.new_page.key();
+    mi.order_pool_remaining_pages_number += 1;
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, Timelock, TimelockStatus},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct CancelTimelock<'info> {
+    #[account(owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    #[account(mut)]
+    pub timelock: AccountLoader<'info, Timelock>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelTimelock>, id: u64) -> DexResult {
+    let dex = &ctx.accounts.dex.load()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require_keys_eq!(
+        dex.timelock,
+        ctx.accounts.timelock.key(),
+        DexError::InvalidTimelock
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let action = ctx.accounts.timelock.load_mut()?.cancel(id)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.update_timelock(
+        action.id,
+        action.eta,
+        action.kind,
+        action.index,
+        TimelockStatus::Cancelled as u8,
+    )
+}
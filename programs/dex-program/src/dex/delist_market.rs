@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, get_oracle_price, state::*, timelock_of},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct DelistMarket<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub oracle: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// The market must have been reduce only for at least the timelock delay.
+//
+// Layout of remaining accounts (required once a timelock is attached):
+//  offset 0: timelock
+pub fn handler(ctx: Context<DelistMarket>, market: u8, settle_price: u64) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let mi = dex.market_as_mut(market)?;
+    require_keys_eq!(
+        mi.oracle,
+        ctx.accounts.oracle.key(),
+        DexError::InvalidOracle
+    );
+    let oracle_price = get_oracle_price(mi.oracle_source, &ctx.accounts.oracle)?;
+
+    let delay = match timelock_of(dex, ctx.remaining_accounts)? {
+        Some(timelock) => timelock.load()?.delay,
+        None => 0,
+    };
+
+    dex.delist_market(market, settle_price, oracle_price, delay)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.delist(TradingScope::Market as u8, market, settle_price, false)
+}
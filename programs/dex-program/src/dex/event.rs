@@ -1,6 +1,7 @@
 use crate::{
     collections::{EventQueue, PackedEvent},
     errors::DexResult,
+    utils::FEE_TIER_COUNT,
 };
 use anchor_lang::prelude::*;
 #[cfg(feature = "client-support")]
@@ -41,6 +42,8 @@ pub struct PositionFilled {
     pub borrow_fee: u64,
     // Only for closing position
     pub pnl: i64,
+    // Liquidation price of the remaining position, zero once fully closed
+    pub liquidation_price: u64,
 }
 
 impl PackedEvent for PositionFilled {
@@ -119,6 +122,271 @@ impl PackedEvent for DIOptionRemoved {
     const DISCRIMINATOR: u8 = 104;
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct RewardsClaimed {
+    pub user_state: [u8; 32],
+
+    pub asset: u8,
+    pub vlp_amount: u64,
+    pub asset_amount: u64,
+    pub fee: u64,
+}
+
+impl PackedEvent for RewardsClaimed {
+    const DISCRIMINATOR: u8 = 105;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct EmissionUpdated {
+    pub es_vdx_per_second: u64,
+    pub vdx_total_supply: u64,
+    pub emission_start_time: i64,
+    pub emission_halving_interval: i64,
+    pub es_vdx_percentage_for_vdx_pool: u16,
+    pub reward_percentage_for_vdx_pool: u16,
+}
+
+impl PackedEvent for EmissionUpdated {
+    const DISCRIMINATOR: u8 = 106;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct EmissionParams {
+    pub es_vdx_per_second: u64,
+    pub vdx_total_supply: u64,
+    pub emission_halving_interval: i64,
+    pub es_vdx_percentage_for_vdx_pool: u16,
+    pub reward_percentage_for_vdx_pool: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct MarketParams {
+    pub oracle: [u8; 32],
+    pub minimum_collateral: u64,
+    pub charge_borrow_fee_interval: u64,
+    pub open_fee_rate: u16,
+    pub close_fee_rate: u16,
+    pub liquidate_fee_rate: u16,
+    pub liquidate_threshold: u16,
+    pub max_leverage: u32,
+    pub oracle_source: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct MarketUpdated {
+    pub market: u8,
+    pub old: MarketParams,
+    pub new: MarketParams,
+}
+
+impl PackedEvent for MarketUpdated {
+    const DISCRIMINATOR: u8 = 107;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct AssetParams {
+    pub oracle: [u8; 32],
+    pub swap_fee_rate: u16,
+    pub borrow_fee_rate: u16,
+    pub add_liquidity_fee_rate: u16,
+    pub remove_liquidity_fee_rate: u16,
+    pub flash_loan_fee_rate: u16,
+    pub target_weight: u16,
+    pub oracle_source: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct AssetUpdated {
+    pub asset: u8,
+    pub old: AssetParams,
+    pub new: AssetParams,
+}
+
+impl PackedEvent for AssetUpdated {
+    const DISCRIMINATOR: u8 = 108;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct TradingStateChanged {
+    pub scope: u8,
+    pub index: u8,
+    pub old_state: u8,
+    pub new_state: u8,
+}
+
+impl PackedEvent for TradingStateChanged {
+    const DISCRIMINATOR: u8 = 109;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct Delisted {
+    pub scope: u8,
+    pub index: u8,
+    pub settle_price: u64,
+    pub removed: bool,
+}
+
+impl PackedEvent for Delisted {
+    const DISCRIMINATOR: u8 = 110;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct TimelockUpdated {
+    pub id: u64,
+    pub eta: i64,
+    pub kind: u8,
+    pub index: u8,
+    pub status: u8, // 0: queued, 1: executed, 2: cancelled
+}
+
+impl PackedEvent for TimelockUpdated {
+    const DISCRIMINATOR: u8 = 111;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct ProtocolFeesWithdrawn {
+    pub asset: u8,
+    pub amount: u64,
+    pub recipient: [u8; 32],
+}
+
+impl PackedEvent for ProtocolFeesWithdrawn {
+    const DISCRIMINATOR: u8 = 112;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct VdxLocked {
+    pub user_state: [u8; 32],
+
+    pub amount: u64,
+    pub lock_tier: u8,
+    // Whole lock after the top-up
+    pub locked: u64,
+    pub unlock_time: i64,
+}
+
+impl PackedEvent for VdxLocked {
+    const DISCRIMINATOR: u8 = 113;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct VestingExited {
+    pub user_state: [u8; 32],
+
+    pub amount: u64,
+    pub released: u64,
+    // Penalty redistributed to stakers as esVDX
+    pub forfeited: u64,
+    pub newest_first: bool,
+}
+
+impl PackedEvent for VestingExited {
+    const DISCRIMINATOR: u8 = 114;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct ProtocolFeePercentageUpdated {
+    pub old: u16,
+    pub new: u16,
+}
+
+impl PackedEvent for ProtocolFeePercentageUpdated {
+    const DISCRIMINATOR: u8 = 115;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct ReferralRatesUpdated {
+    pub old_discount: u16,
+    pub old_rebate: u16,
+    pub new_discount: u16,
+    pub new_rebate: u16,
+}
+
+impl PackedEvent for ReferralRatesUpdated {
+    const DISCRIMINATOR: u8 = 116;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct FeeTiersUpdated {
+    pub old_staked: [u64; FEE_TIER_COUNT],
+    pub old_discounts: [u16; FEE_TIER_COUNT],
+    pub new_staked: [u64; FEE_TIER_COUNT],
+    pub new_discounts: [u16; FEE_TIER_COUNT],
+}
+
+impl PackedEvent for FeeTiersUpdated {
+    const DISCRIMINATOR: u8 = 117;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct PositionLeverageSet {
+    pub user_state: [u8; 32],
+    pub market: u8,
+    pub long_or_short: u8,
+    pub leverage: u32,
+    pub price: u64,
+    pub added: u64,
+    pub refunded: u64,
+    pub liquidation_price: u64,
+}
+
+impl PackedEvent for PositionLeverageSet {
+    const DISCRIMINATOR: u8 = 118;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct BorrowFeeSettled {
+    pub user_state: [u8; 32],
+    pub market: u8,
+    pub long_or_short: u8,
+    pub asset: u8,
+    pub fee: u64,
+}
+
+impl PackedEvent for BorrowFeeSettled {
+    const DISCRIMINATOR: u8 = 119;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct LockReleased {
+    pub user_state: [u8; 32],
+    pub released: u64,
+}
+
+impl PackedEvent for LockReleased {
+    const DISCRIMINATOR: u8 = 120;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+#[cfg_attr(feature = "client-support", derive(Serialize))]
+pub struct DelegateUpdated {
+    pub old: [u8; 32],
+    pub new: [u8; 32],
+}
+
+impl PackedEvent for DelegateUpdated {
+    const DISCRIMINATOR: u8 = 121;
+}
+
 pub trait AppendEvent {
     #[allow(clippy::too_many_arguments)]
     fn fill_position(
@@ -135,6 +403,7 @@ pub trait AppendEvent {
         fee: u64,
         borrow_fee: u64,
         pnl: i64,
+        liquidation_price: u64,
     ) -> DexResult;
 
     #[allow(clippy::too_many_arguments)]
@@ -189,6 +458,105 @@ pub trait AppendEvent {
         volume: u64,
         is_call: bool,
     ) -> DexResult;
+
+    fn claim_rewards(
+        &mut self,
+        user_state: [u8; 32],
+        asset: u8,
+        vlp_amount: u64,
+        asset_amount: u64,
+        fee: u64,
+    ) -> DexResult;
+
+    fn update_emission(
+        &mut self,
+        es_vdx_per_second: u64,
+        vdx_total_supply: u64,
+        emission_start_time: i64,
+        emission_halving_interval: i64,
+        es_vdx_percentage_for_vdx_pool: u16,
+        reward_percentage_for_vdx_pool: u16,
+    ) -> DexResult;
+
+    fn update_market(&mut self, market: u8, old: MarketParams, new: MarketParams) -> DexResult;
+
+    fn update_asset(&mut self, asset: u8, old: AssetParams, new: AssetParams) -> DexResult;
+
+    fn change_trading_state(
+        &mut self,
+        scope: u8,
+        index: u8,
+        old_state: u8,
+        new_state: u8,
+    ) -> DexResult;
+
+    fn delist(&mut self, scope: u8, index: u8, settle_price: u64, removed: bool) -> DexResult;
+
+    fn update_timelock(&mut self, id: u64, eta: i64, kind: u8, index: u8, status: u8) -> DexResult;
+
+    fn update_protocol_fee_percentage(&mut self, old: u16, new: u16) -> DexResult;
+
+    fn update_delegate(&mut self, old: [u8; 32], new: [u8; 32]) -> DexResult;
+
+    fn update_referral_rates(
+        &mut self,
+        old_discount: u16,
+        old_rebate: u16,
+        new_discount: u16,
+        new_rebate: u16,
+    ) -> DexResult;
+
+    fn update_fee_tiers(
+        &mut self,
+        old_staked: [u64; FEE_TIER_COUNT],
+        old_discounts: [u16; FEE_TIER_COUNT],
+        new_staked: [u64; FEE_TIER_COUNT],
+        new_discounts: [u16; FEE_TIER_COUNT],
+    ) -> DexResult;
+
+    #[allow(clippy::too_many_arguments)]
+    fn set_position_leverage(
+        &mut self,
+        user_state: [u8; 32],
+        market: u8,
+        long: bool,
+        leverage: u32,
+        price: u64,
+        added: u64,
+        refunded: u64,
+        liquidation_price: u64,
+    ) -> DexResult;
+
+    fn settle_borrow_fee(
+        &mut self,
+        user_state: [u8; 32],
+        market: u8,
+        long: bool,
+        asset: u8,
+        fee: u64,
+    ) -> DexResult;
+
+    fn withdraw_protocol_fees(&mut self, asset: u8, amount: u64, recipient: [u8; 32]) -> DexResult;
+
+    fn lock_vdx(
+        &mut self,
+        user_state: [u8; 32],
+        amount: u64,
+        lock_tier: u8,
+        locked: u64,
+        unlock_time: i64,
+    ) -> DexResult;
+
+    fn exit_vesting(
+        &mut self,
+        user_state: [u8; 32],
+        amount: u64,
+        released: u64,
+        forfeited: u64,
+        newest_first: bool,
+    ) -> DexResult;
+
+    fn release_lock(&mut self, user_state: [u8; 32], released: u64) -> DexResult;
 }
 
 impl AppendEvent for EventQueue<'_> {
@@ -207,6 +575,7 @@ impl AppendEvent for EventQueue<'_> {
         fee: u64,
         borrow_fee: u64,
         pnl: i64,
+        liquidation_price: u64,
     ) -> DexResult {
         let (code, text) = action.decode();
 
@@ -223,12 +592,13 @@ impl AppendEvent for EventQueue<'_> {
             fee,
             borrow_fee,
             pnl,
+            liquidation_price,
         };
 
         let event_seq = self.append(event)?;
 
         msg!(
-            "Position {}: {:?} {} {} {} {} {} {} {} {} {} {}",
+            "Position {}: {:?} {} {} {} {} {} {} {} {} {} {} {}",
             text,
             user_state,
             market,
@@ -240,6 +610,7 @@ impl AppendEvent for EventQueue<'_> {
             fee,
             borrow_fee,
             pnl,
+            liquidation_price,
             event_seq
         );
 
@@ -408,4 +779,434 @@ impl AppendEvent for EventQueue<'_> {
 
         Ok(())
     }
+
+    fn claim_rewards(
+        &mut self,
+        user_state: [u8; 32],
+        asset: u8,
+        vlp_amount: u64,
+        asset_amount: u64,
+        fee: u64,
+    ) -> DexResult {
+        let event = RewardsClaimed {
+            user_state,
+            asset,
+            vlp_amount,
+            asset_amount,
+            fee,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Rewards claimed: {:?} {} {} {} {} {}",
+            user_state,
+            asset,
+            vlp_amount,
+            asset_amount,
+            fee,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_emission(
+        &mut self,
+        es_vdx_per_second: u64,
+        vdx_total_supply: u64,
+        emission_start_time: i64,
+        emission_halving_interval: i64,
+        es_vdx_percentage_for_vdx_pool: u16,
+        reward_percentage_for_vdx_pool: u16,
+    ) -> DexResult {
+        let event = EmissionUpdated {
+            es_vdx_per_second,
+            vdx_total_supply,
+            emission_start_time,
+            emission_halving_interval,
+            es_vdx_percentage_for_vdx_pool,
+            reward_percentage_for_vdx_pool,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Emission updated: {} {} {} {} {} {} {}",
+            es_vdx_per_second,
+            vdx_total_supply,
+            emission_start_time,
+            emission_halving_interval,
+            es_vdx_percentage_for_vdx_pool,
+            reward_percentage_for_vdx_pool,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_market(&mut self, market: u8, old: MarketParams, new: MarketParams) -> DexResult {
+        let event = MarketUpdated { market, old, new };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Market updated: {} {:?} {} {} {} {} {} {} {} {} -> {:?} {} {} {} {} {} {} {} {} {}",
+            market,
+            old.oracle,
+            old.minimum_collateral,
+            old.charge_borrow_fee_interval,
+            old.open_fee_rate,
+            old.close_fee_rate,
+            old.liquidate_fee_rate,
+            old.liquidate_threshold,
+            old.max_leverage,
+            old.oracle_source,
+            new.oracle,
+            new.minimum_collateral,
+            new.charge_borrow_fee_interval,
+            new.open_fee_rate,
+            new.close_fee_rate,
+            new.liquidate_fee_rate,
+            new.liquidate_threshold,
+            new.max_leverage,
+            new.oracle_source,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_asset(&mut self, asset: u8, old: AssetParams, new: AssetParams) -> DexResult {
+        let event = AssetUpdated { asset, old, new };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Asset updated: {} {:?} {} {} {} {} {} {} {} -> {:?} {} {} {} {} {} {} {} {}",
+            asset,
+            old.oracle,
+            old.swap_fee_rate,
+            old.borrow_fee_rate,
+            old.add_liquidity_fee_rate,
+            old.remove_liquidity_fee_rate,
+            old.flash_loan_fee_rate,
+            old.target_weight,
+            old.oracle_source,
+            new.oracle,
+            new.swap_fee_rate,
+            new.borrow_fee_rate,
+            new.add_liquidity_fee_rate,
+            new.remove_liquidity_fee_rate,
+            new.flash_loan_fee_rate,
+            new.target_weight,
+            new.oracle_source,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn change_trading_state(
+        &mut self,
+        scope: u8,
+        index: u8,
+        old_state: u8,
+        new_state: u8,
+    ) -> DexResult {
+        let event = TradingStateChanged {
+            scope,
+            index,
+            old_state,
+            new_state,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Trading state changed: {} {} {} {} {}",
+            scope,
+            index,
+            old_state,
+            new_state,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn delist(&mut self, scope: u8, index: u8, settle_price: u64, removed: bool) -> DexResult {
+        let event = Delisted {
+            scope,
+            index,
+            settle_price,
+            removed,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Delisted: {} {} {} {} {}",
+            scope,
+            index,
+            settle_price,
+            removed,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_timelock(&mut self, id: u64, eta: i64, kind: u8, index: u8, status: u8) -> DexResult {
+        let event = TimelockUpdated {
+            id,
+            eta,
+            kind,
+            index,
+            status,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Timelock updated: {} {} {} {} {} {}",
+            id,
+            eta,
+            kind,
+            index,
+            status,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_protocol_fee_percentage(&mut self, old: u16, new: u16) -> DexResult {
+        let event = ProtocolFeePercentageUpdated { old, new };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Protocol fee percentage updated: {} {} {}",
+            old,
+            new,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_delegate(&mut self, old: [u8; 32], new: [u8; 32]) -> DexResult {
+        let event = DelegateUpdated { old, new };
+
+        let event_seq = self.append(event)?;
+        msg!("Delegate updated: {:?} {:?} {}", old, new, event_seq);
+
+        Ok(())
+    }
+
+    fn update_referral_rates(
+        &mut self,
+        old_discount: u16,
+        old_rebate: u16,
+        new_discount: u16,
+        new_rebate: u16,
+    ) -> DexResult {
+        let event = ReferralRatesUpdated {
+            old_discount,
+            old_rebate,
+            new_discount,
+            new_rebate,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Referral rates updated: {} {} {} {} {}",
+            old_discount,
+            old_rebate,
+            new_discount,
+            new_rebate,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn update_fee_tiers(
+        &mut self,
+        old_staked: [u64; FEE_TIER_COUNT],
+        old_discounts: [u16; FEE_TIER_COUNT],
+        new_staked: [u64; FEE_TIER_COUNT],
+        new_discounts: [u16; FEE_TIER_COUNT],
+    ) -> DexResult {
+        let event = FeeTiersUpdated {
+            old_staked,
+            old_discounts,
+            new_staked,
+            new_discounts,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Fee tiers updated: {:?} {:?} {:?} {:?} {}",
+            old_staked,
+            old_discounts,
+            new_staked,
+            new_discounts,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn set_position_leverage(
+        &mut self,
+        user_state: [u8; 32],
+        market: u8,
+        long: bool,
+        leverage: u32,
+        price: u64,
+        added: u64,
+        refunded: u64,
+        liquidation_price: u64,
+    ) -> DexResult {
+        let event = PositionLeverageSet {
+            user_state,
+            market,
+            long_or_short: if long { 0 } else { 1 },
+            leverage,
+            price,
+            added,
+            refunded,
+            liquidation_price,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Position leverage set: {:?} {} {} {} {} {} {} {} {}",
+            user_state,
+            market,
+            long,
+            leverage,
+            price,
+            added,
+            refunded,
+            liquidation_price,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn settle_borrow_fee(
+        &mut self,
+        user_state: [u8; 32],
+        market: u8,
+        long: bool,
+        asset: u8,
+        fee: u64,
+    ) -> DexResult {
+        let event = BorrowFeeSettled {
+            user_state,
+            market,
+            long_or_short: if long { 0 } else { 1 },
+            asset,
+            fee,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Borrow fee settled: {:?} {} {} {} {} {}",
+            user_state,
+            market,
+            long,
+            asset,
+            fee,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn withdraw_protocol_fees(&mut self, asset: u8, amount: u64, recipient: [u8; 32]) -> DexResult {
+        let event = ProtocolFeesWithdrawn {
+            asset,
+            amount,
+            recipient,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Protocol fees withdrawn: {} {} {}",
+            asset,
+            amount,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn lock_vdx(
+        &mut self,
+        user_state: [u8; 32],
+        amount: u64,
+        lock_tier: u8,
+        locked: u64,
+        unlock_time: i64,
+    ) -> DexResult {
+        let event = VdxLocked {
+            user_state,
+            amount,
+            lock_tier,
+            locked,
+            unlock_time,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "VDX locked: {:?} {} {} {} {} {}",
+            user_state,
+            amount,
+            lock_tier,
+            locked,
+            unlock_time,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn exit_vesting(
+        &mut self,
+        user_state: [u8; 32],
+        amount: u64,
+        released: u64,
+        forfeited: u64,
+        newest_first: bool,
+    ) -> DexResult {
+        let event = VestingExited {
+            user_state,
+            amount,
+            released,
+            forfeited,
+            newest_first,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!(
+            "Vesting exited: {:?} {} {} {} {} {}",
+            user_state,
+            amount,
+            released,
+            forfeited,
+            newest_first,
+            event_seq
+        );
+
+        Ok(())
+    }
+
+    fn release_lock(&mut self, user_state: [u8; 32], released: u64) -> DexResult {
+        let event = LockReleased {
+            user_state,
+            released,
+        };
+
+        let event_seq = self.append(event)?;
+        msg!("Lock released: {:?} {} {}", user_state, released, event_seq);
+
+        Ok(())
+    }
 }
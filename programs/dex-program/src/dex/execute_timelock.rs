@@ -0,0 +1,136 @@
+use std::convert::TryFrom;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{
+        event::AppendEvent, get_oracle_price, state::*, AssetParams, EmissionParams, MarketParams,
+        Timelock, TimelockKind, TimelockStatus,
+    },
+    errors::{DexError, DexResult},
+    utils::FEE_TIER_COUNT,
+};
+
+#[derive(Accounts)]
+pub struct ExecuteTimelock<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    #[account(mut)]
+    pub timelock: AccountLoader<'info, Timelock>,
+
+    /// CHECK: New oracle of a queued market or asset update, ignored otherwise
+    pub oracle: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+}
+
+// Permissionless, anyone can execute a queued action once it is due
+pub fn handler(ctx: Context<ExecuteTimelock>, id: u64) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+    require_keys_eq!(
+        dex.timelock,
+        ctx.accounts.timelock.key(),
+        DexError::InvalidTimelock
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = &mut ctx.accounts.timelock.load_mut()?;
+    let action = timelock.take_ready(id)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    let mut data = &action.data[..];
+    match TimelockKind::try_from(action.kind).map_err(|_| DexError::InvalidTimelockAction)? {
+        TimelockKind::Market => {
+            let new = MarketParams::deserialize(&mut data)?;
+            require!(
+                new.oracle == ctx.accounts.oracle.key().to_bytes(),
+                DexError::InvalidOracle
+            );
+            get_oracle_price(new.oracle_source, &ctx.accounts.oracle)?;
+
+            let mi = dex.market_as_mut(action.index)?;
+            let old = mi.params();
+            mi.set_params(&new)?;
+
+            event_queue.update_market(action.index, old, new)?;
+        }
+        TimelockKind::Asset => {
+            let new = AssetParams::deserialize(&mut data)?;
+            require!(
+                new.oracle == ctx.accounts.oracle.key().to_bytes(),
+                DexError::InvalidOracle
+            );
+            get_oracle_price(new.oracle_source, &ctx.accounts.oracle)?;
+
+            let ai = dex.asset_as_mut(action.index)?;
+            let old = ai.params();
+            ai.set_params(&new)?;
+
+            event_queue.update_asset(action.index, old, new)?;
+        }
+        TimelockKind::Delay => {
+            timelock.set_delay(i64::deserialize(&mut data)?)?;
+        }
+        TimelockKind::ProtocolFeePercentage => {
+            let old = dex.protocol_fee_percentage;
+            let new = u16::deserialize(&mut data)?;
+            dex.set_protocol_fee_percentage(new)?;
+
+            event_queue.update_protocol_fee_percentage(old, new)?;
+        }
+        TimelockKind::ReferralRates => {
+            let (old_discount, old_rebate) = (
+                dex.referral_discount_percentage,
+                dex.referral_rebate_percentage,
+            );
+            let (discount, rebate) = <(u16, u16)>::deserialize(&mut data)?;
+            dex.set_referral_rates(discount, rebate)?;
+
+            event_queue.update_referral_rates(old_discount, old_rebate, discount, rebate)?;
+        }
+        TimelockKind::FeeTiers => {
+            let (old_staked, old_discounts) = (dex.fee_tier_staked, dex.fee_tier_discounts);
+            let (staked, discounts) =
+                <([u64; FEE_TIER_COUNT], [u16; FEE_TIER_COUNT])>::deserialize(&mut data)?;
+            dex.set_fee_tiers(staked, discounts)?;
+
+            event_queue.update_fee_tiers(old_staked, old_discounts, staked, discounts)?;
+        }
+        TimelockKind::Delegate => {
+            let old = dex.delegate;
+            dex.delegate = Pubkey::deserialize(&mut data)?;
+
+            event_queue.update_delegate(old.to_bytes(), dex.delegate.to_bytes())?;
+        }
+        TimelockKind::Emission => {
+            dex.set_emission(&EmissionParams::deserialize(&mut data)?)?;
+
+            event_queue.update_emission(
+                dex.es_vdx_per_second,
+                dex.vdx_total_supply,
+                dex.emission_start_time,
+                dex.emission_halving_interval,
+                dex.es_vdx_percentage_for_vdx_pool,
+                dex.reward_percentage_for_vdx_pool,
+            )?;
+        }
+    }
+
+    event_queue.update_timelock(
+        action.id,
+        action.eta,
+        action.kind,
+        action.index,
+        TimelockStatus::Executed as u8,
+    )
+}
@@ -8,7 +8,10 @@ use crate::{
     dual_invest::DI,
     errors::{DexError, DexResult},
     order::MatchEvent,
-    utils::{get_timestamp, DEX_MAGIC_NUMBER, PRICE_FEED_MAGIC_NUMBER, VLP_DECIMALS},
+    utils::{
+        get_timestamp, DEX_MAGIC_NUMBER, ES_VDX_PERCENTAGE_FOR_VDX_POOL, ES_VDX_PER_SECOND,
+        PRICE_FEED_MAGIC_NUMBER, REWARD_PERCENTAGE_FOR_VDX_POOL, VDX_TOTAL_SUPPLY, VLP_DECIMALS,
+    },
 };
 
 #[derive(Accounts)]
@@ -64,10 +67,19 @@ pub fn handler(ctx: Context<InitDex>, vdx_nonce: u8, di_fee_rate: u16) -> DexRes
     dex.di_option = ctx.accounts.di_option.key();
     dex.update_rewards_last_timestamp = get_timestamp()?;
     dex.vdx_supply = 0;
+    dex.cached_aum = 0;
+    dex.cached_aum_timestamp = get_timestamp()?;
+    dex.es_vdx_per_second = ES_VDX_PER_SECOND;
+    dex.vdx_total_supply = VDX_TOTAL_SUPPLY;
+    dex.emission_start_time = get_timestamp()?;
+    dex.emission_halving_interval = 0;
+    dex.es_vdx_percentage_for_vdx_pool = ES_VDX_PERCENTAGE_FOR_VDX_POOL as u16;
+    dex.reward_percentage_for_vdx_pool = REWARD_PERCENTAGE_FOR_VDX_POOL as u16;
     dex.user_list_remaining_pages_number = 0;
     dex.assets_number = 0;
     dex.markets_number = 0;
     dex.usdc_asset_index = 0xff;
+    dex.trading_state = TradingState::Active as u8;
     dex.vlp_pool.init(
         // Dummy VLP token, never mint
         Pubkey::default(),
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::{state::*, Timelock},
+    errors::{DexError, DexResult},
+    utils::TIMELOCK_MAGIC_NUMBER,
+};
+
+#[derive(Accounts)]
+pub struct InitTimelock<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(zero)]
+    pub timelock: AccountLoader<'info, Timelock>,
+
+    pub authority: Signer<'info>,
+}
+
+// A timelock can not be detached once attached
+pub fn handler(ctx: Context<InitTimelock>, delay: i64) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+    require_keys_eq!(dex.timelock, Pubkey::default(), DexError::InvalidTimelock);
+
+    let timelock = &mut ctx.accounts.timelock.load_init()?;
+    timelock.magic = TIMELOCK_MAGIC_NUMBER;
+    timelock.dex = ctx.accounts.dex.key();
+    timelock.next_id = 0;
+    timelock.set_delay(delay)?;
+
+    dex.timelock = ctx.accounts.timelock.key();
+
+    Ok(())
+}
@@ -1,23 +1,59 @@
+pub mod accept_authority;
 pub mod add_asset;
 pub mod add_market;
+pub mod append_order_pool_page;
+pub mod cancel_timelock;
+pub mod delist_market;
 pub mod event;
+pub mod execute_timelock;
 pub mod feed_mock_oracle_price;
 pub mod init_dex;
 pub mod init_mock_oracle;
+pub mod init_timelock;
 pub mod oracle;
+pub mod propose_authority;
+pub mod refresh_aum;
+pub mod remove_asset;
+pub mod remove_market;
 pub mod set_delegate;
+pub mod set_emission;
+pub mod set_fee_tiers;
+pub mod set_referral_rates;
+pub mod set_timelock_delay;
+pub mod set_trading_state;
 pub mod stake;
 pub mod state;
+pub mod timelock;
+pub mod update_asset;
+pub mod update_market;
 pub mod update_price;
 
+pub use accept_authority::*;
 pub use add_asset::*;
 pub use add_market::*;
+pub use append_order_pool_page::*;
+pub use cancel_timelock::*;
+pub use delist_market::*;
 pub use event::*;
+pub use execute_timelock::*;
 pub use feed_mock_oracle_price::*;
 pub use init_dex::*;
 pub use init_mock_oracle::*;
+pub use init_timelock::*;
 pub use oracle::*;
+pub use propose_authority::*;
+pub use refresh_aum::*;
+pub use remove_asset::*;
+pub use remove_market::*;
 pub use set_delegate::*;
+pub use set_emission::*;
+pub use set_fee_tiers::*;
+pub use set_referral_rates::*;
+pub use set_timelock_delay::*;
+pub use set_trading_state::*;
 pub use stake::*;
 pub use state::*;
+pub use timelock::*;
+pub use update_asset::*;
+pub use update_market::*;
 pub use update_price::*;
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{dex::state::*, errors::DexResult};
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub new_authority: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// The transfer completes when the new authority accepts it. Proposing the
+// current authority again withdraws a pending proposal.
+pub fn handler(ctx: Context<ProposeAuthority>) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+    dex.pending_authority = ctx.accounts.new_authority.key();
+
+    Ok(())
+}
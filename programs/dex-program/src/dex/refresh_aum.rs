@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::state::*,
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct RefreshAum<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(owner = *program_id)]
+    pub price_feed: AccountLoader<'info, PriceFeed>,
+}
+
+// Remaining accounts layout:
+// dex.assets.map({
+//   asset index price oracle account
+// })
+// dex.markets.map({
+//    market index price oracle account
+// })
+pub fn handler(ctx: Context<RefreshAum>) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.price_feed == ctx.accounts.price_feed.key(),
+        DexError::InvalidPriceFeed
+    );
+
+    let price_feed = &ctx.accounts.price_feed.load()?;
+
+    dex.refresh_aum(ctx.remaining_accounts, price_feed)
+}
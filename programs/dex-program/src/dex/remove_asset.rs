@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct RemoveAsset<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveAsset>, asset: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    dex.remove_asset(asset)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.delist(TradingScope::Asset as u8, asset, 0, true)
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::{EventQueue, OrderBook, OrderSide},
+    dex::{event::AppendEvent, state::*},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct RemoveMarket<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(constraint= order_book.owner == program_id)]
+    pub order_book: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveMarket>, market: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let mi = dex.market_as_mut(market)?;
+    require!(
+        mi.order_book == ctx.accounts.order_book.key(),
+        DexError::InvalidMarketIndex
+    );
+    let settle_price = mi.settle_price;
+
+    // All orders must have been drained by settle_delisted
+    let order_book = OrderBook::mount(&ctx.accounts.order_book, true)?;
+    require!(
+        order_book.get_best_price(OrderSide::BID).is_none()
+            && order_book.get_best_price(OrderSide::ASK).is_none(),
+        DexError::DelistNotCompleted
+    );
+
+    dex.remove_market(market)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.delist(TradingScope::Market as u8, market, settle_price, true)
+}
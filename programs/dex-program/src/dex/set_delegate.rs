@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
 
-use crate::{dex::state::*, errors::DexResult};
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, timelock_of, TimelockKind, TimelockStatus},
+    errors::{DexError, DexResult},
+};
 
 #[derive(Accounts)]
 pub struct SetDelegate<'info> {
@@ -13,12 +17,50 @@ pub struct SetDelegate<'info> {
     /// CHECK
     pub delegate: AccountInfo<'info>,
 
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
     pub authority: Signer<'info>,
 }
 
+// The delegate can halt trading, so a new one only takes over once the timelock delay passed
+//
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
 pub fn handler(ctx: Context<SetDelegate>) -> DexResult {
     let dex = &mut ctx.accounts.dex.load_mut()?;
-    dex.delegate = ctx.accounts.delegate.key();
 
-    Ok(())
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    let delegate = ctx.accounts.delegate.key();
+    match timelock {
+        Some(timelock) => {
+            let (id, eta) =
+                timelock
+                    .load_mut()?
+                    .queue(TimelockKind::Delegate, 0, &delegate.try_to_vec()?)?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::Delegate as u8,
+                0,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            let old = std::mem::replace(&mut dex.delegate, delegate);
+
+            event_queue.update_delegate(old.to_bytes(), delegate.to_bytes())
+        }
+    }
 }
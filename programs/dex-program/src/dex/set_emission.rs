@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{
+        event::AppendEvent, state::*, timelock_of, EmissionParams, TimelockKind, TimelockStatus,
+    },
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct SetEmission<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+pub fn handler(
+    ctx: Context<SetEmission>,
+    es_vdx_per_second: u64,
+    vdx_total_supply: u64,
+    emission_halving_interval: i64,
+    es_vdx_percentage_for_vdx_pool: u16,
+    reward_percentage_for_vdx_pool: u16,
+) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let params = EmissionParams {
+        es_vdx_per_second,
+        vdx_total_supply,
+        emission_halving_interval,
+        es_vdx_percentage_for_vdx_pool,
+        reward_percentage_for_vdx_pool,
+    };
+    dex.check_emission_params(&params)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    match timelock_of(dex, ctx.remaining_accounts)? {
+        Some(timelock) => {
+            let (id, eta) =
+                timelock
+                    .load_mut()?
+                    .queue(TimelockKind::Emission, 0, &params.try_to_vec()?)?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::Emission as u8,
+                0,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            dex.set_emission(&params)?;
+            event_queue.update_emission(
+                dex.es_vdx_per_second,
+                dex.vdx_total_supply,
+                dex.emission_start_time,
+                dex.emission_halving_interval,
+                dex.es_vdx_percentage_for_vdx_pool,
+                dex.reward_percentage_for_vdx_pool,
+            )
+        }
+    }
+}
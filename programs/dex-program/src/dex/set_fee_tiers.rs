@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, timelock_of, TimelockKind, TimelockStatus},
+    errors::{DexError, DexResult},
+    utils::FEE_TIER_COUNT,
+};
+
+#[derive(Accounts)]
+pub struct SetFeeTiers<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Tiers with a zero discount are unused, the others must ascend in both staked VDX and discount
+//
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+pub fn handler(
+    ctx: Context<SetFeeTiers>,
+    staked: [u64; FEE_TIER_COUNT],
+    discounts: [u16; FEE_TIER_COUNT],
+) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    match timelock {
+        Some(timelock) => {
+            dex.check_fee_tiers(staked, discounts)?;
+
+            let (id, eta) = timelock.load_mut()?.queue(
+                TimelockKind::FeeTiers,
+                0,
+                &(staked, discounts).try_to_vec()?,
+            )?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::FeeTiers as u8,
+                0,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            let (old_staked, old_discounts) = (dex.fee_tier_staked, dex.fee_tier_discounts);
+            dex.set_fee_tiers(staked, discounts)?;
+
+            event_queue.update_fee_tiers(old_staked, old_discounts, staked, discounts)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, timelock_of, TimelockKind, TimelockStatus},
+    errors::{DexError, DexResult},
+    utils::MAX_REFERRAL_PERCENTAGE,
+};
+
+#[derive(Accounts)]
+pub struct SetReferralRates<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+pub fn handler(ctx: Context<SetReferralRates>, discount: u16, rebate: u16) -> DexResult {
+    require!(
+        discount.saturating_add(rebate) <= MAX_REFERRAL_PERCENTAGE,
+        DexError::InvalidReferralRates
+    );
+
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    match timelock {
+        Some(timelock) => {
+            let (id, eta) = timelock.load_mut()?.queue(
+                TimelockKind::ReferralRates,
+                0,
+                &(discount, rebate).try_to_vec()?,
+            )?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::ReferralRates as u8,
+                0,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            let (old_discount, old_rebate) = (
+                dex.referral_discount_percentage,
+                dex.referral_rebate_percentage,
+            );
+            dex.set_referral_rates(discount, rebate)?;
+
+            event_queue.update_referral_rates(old_discount, old_rebate, discount, rebate)
+        }
+    }
+}
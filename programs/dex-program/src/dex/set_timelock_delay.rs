@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, Timelock, TimelockKind, TimelockStatus},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct SetTimelockDelay<'info> {
+    #[account(
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    #[account(mut)]
+    pub timelock: AccountLoader<'info, Timelock>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Raising the delay takes effect at once, lowering it is queued behind the current delay
+pub fn handler(ctx: Context<SetTimelockDelay>, delay: i64) -> DexResult {
+    let dex = &ctx.accounts.dex.load()?;
+    require_keys_eq!(
+        dex.timelock,
+        ctx.accounts.timelock.key(),
+        DexError::InvalidTimelock
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = &mut ctx.accounts.timelock.load_mut()?;
+    if delay >= timelock.delay {
+        return timelock.set_delay(delay);
+    }
+
+    let (id, eta) = timelock.queue(TimelockKind::Delay, 0, &delay.to_le_bytes())?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.update_timelock(
+        id,
+        eta,
+        TimelockKind::Delay as u8,
+        0,
+        TimelockStatus::Queued as u8,
+    )
+}
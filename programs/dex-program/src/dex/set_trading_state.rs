@@ -0,0 +1,53 @@
+use std::convert::TryFrom;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct SetTradingState<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// `index` is ignored for the global scope
+pub fn handler(ctx: Context<SetTradingState>, scope: u8, index: u8, state: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    TradingState::try_from(state).map_err(|_| DexError::InvalidTradingState)?;
+
+    let old_state =
+        match TradingScope::try_from(scope).map_err(|_| DexError::InvalidTradingState)? {
+            TradingScope::Global => std::mem::replace(&mut dex.trading_state, state),
+            TradingScope::Market => dex.set_market_trading_state(index, state)?,
+            TradingScope::Asset => {
+                std::mem::replace(&mut dex.asset_as_mut(index)?.trading_state, state)
+            }
+        };
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.change_trading_state(scope, index, old_state, state)
+}
@@ -2,7 +2,10 @@ use anchor_lang::prelude::*;
 
 use crate::{
     errors::{DexError, DexResult},
-    utils::{SafeMath, REWARD_SHARE_POW_DECIMALS},
+    utils::{
+        time::get_timestamp, ISafeAddSub, SafeMath, REWARD_SHARE_POW_DECIMALS,
+        VDX_LOCK_MULTIPLIERS, VDX_LOCK_PERIODS,
+    },
 };
 
 #[zero_copy]
@@ -106,7 +109,10 @@ pub struct UserStake {
     pub reward_accumulated: u64,
     pub es_vdx_debt: u64,
     pub es_vdx_accumulated: u64,
-    pub padding: [u8; 64],
+    pub locked: u64,
+    pub locked_weight: u64,
+    pub unlock_time: i64,
+    pub padding: [u8; 40],
 }
 
 impl UserStake {
@@ -116,6 +122,33 @@ impl UserStake {
         self.reward_accumulated = 0;
         self.es_vdx_debt = 0;
         self.es_vdx_accumulated = 0;
+        self.locked = 0;
+        self.locked_weight = 0;
+        self.unlock_time = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staked == 0
+            && self.locked == 0
+            && self.reward_accumulated == 0
+            && self.es_vdx_accumulated == 0
+    }
+
+    // Locked stakes are weighted by the lock multiplier, the unlocked ones count as is.
+    #[inline]
+    pub fn weight(&self) -> DexResult<u64> {
+        self.staked.safe_add(self.locked_weight)
+    }
+
+    // Fee tiers count only the VDX under a running lock, regardless of the lock multiplier.
+    // The unlocked stake is redeemable at once and would let a stake-trade-redeem sequence
+    // buy the discount inside one transaction.
+    pub fn fee_tier_amount(&self) -> DexResult<u64> {
+        if self.locked == 0 || get_timestamp()? >= self.unlock_time {
+            return Ok(0);
+        }
+
+        Ok(self.locked)
     }
 
     pub fn enter_staking(&mut self, pool: &mut StakingPool, amount: u64) -> DexResult {
@@ -123,9 +156,9 @@ impl UserStake {
             return Ok(());
         }
 
-        if self.staked > 0 {
+        if self.weight()? > 0 {
             let pending_reward = (self
-                .staked
+                .weight()?
                 .safe_mul(pool.accumulate_reward_per_share)?
                 .safe_div(REWARD_SHARE_POW_DECIMALS as u128)?
                 as u64)
@@ -135,7 +168,7 @@ impl UserStake {
             pool.withdraw_reward(pending_reward)?;
 
             let pending_es_vdx = (self
-                .staked
+                .weight()?
                 .safe_mul(pool.accumulate_es_vdx_per_share)?
                 .safe_div(REWARD_SHARE_POW_DECIMALS as u128)?
                 as u64)
@@ -149,12 +182,12 @@ impl UserStake {
         self.staked = self.staked.safe_add(amount)?;
 
         self.reward_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
         self.es_vdx_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
@@ -170,7 +203,7 @@ impl UserStake {
         require!(actual_amount > 0, DexError::InvalidAmount);
 
         let pending_reward = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.reward_debt)?;
@@ -181,7 +214,7 @@ impl UserStake {
         }
 
         let pending_es_vdx = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.es_vdx_debt)?;
@@ -195,21 +228,113 @@ impl UserStake {
         self.staked = self.staked.safe_sub(actual_amount)?;
 
         self.reward_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
         self.es_vdx_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
         Ok(actual_amount)
     }
 
+    // Lock `amount` for the period of `lock_tier`, adding to an existing lock keeps its expiry.
+    pub fn enter_locked_staking(
+        &mut self,
+        pool: &mut StakingPool,
+        amount: u64,
+        lock_tier: u8,
+    ) -> DexResult {
+        require!(
+            (lock_tier as usize) < VDX_LOCK_PERIODS.len(),
+            DexError::InvalidLockTier
+        );
+        require!(amount > 0, DexError::InvalidAmount);
+
+        // Top-ups join the current lock, they may not push its expiry out
+        let unlock_time = get_timestamp()?.i_safe_add(VDX_LOCK_PERIODS[lock_tier as usize])?;
+        require!(
+            self.locked == 0 || unlock_time <= self.unlock_time,
+            DexError::LockExpiryExtended
+        );
+
+        self.settle_pending(pool)?;
+
+        let weight = amount
+            .safe_mul(VDX_LOCK_MULTIPLIERS[lock_tier as usize])?
+            .safe_div(100)? as u64;
+
+        pool.increase_staking(weight)?;
+        self.locked = self.locked.safe_add(amount)?;
+        self.locked_weight = self.locked_weight.safe_add(weight)?;
+        if self.unlock_time == 0 {
+            self.unlock_time = unlock_time;
+        }
+
+        self.update_debt(pool)
+    }
+
+    // Move the matured lock back to the unlocked stake.
+    pub fn release_locked(&mut self, pool: &mut StakingPool) -> DexResult<u64> {
+        if self.locked == 0 || get_timestamp()? < self.unlock_time {
+            return Ok(0);
+        }
+
+        self.settle_pending(pool)?;
+
+        pool.decrease_staking(self.locked_weight)?;
+        pool.increase_staking(self.locked)?;
+
+        let released = self.locked;
+        self.staked = self.staked.safe_add(released)?;
+        self.locked = 0;
+        self.locked_weight = 0;
+        self.unlock_time = 0;
+
+        self.update_debt(pool)?;
+
+        Ok(released)
+    }
+
+    fn settle_pending(&mut self, pool: &mut StakingPool) -> DexResult {
+        let weight = self.weight()?;
+
+        let pending_reward = (weight
+            .safe_mul(pool.accumulate_reward_per_share)?
+            .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
+            .safe_sub(self.reward_debt)?;
+
+        self.reward_accumulated = self.reward_accumulated.safe_add(pending_reward)?;
+        pool.withdraw_reward(pending_reward)?;
+
+        let pending_es_vdx = (weight
+            .safe_mul(pool.accumulate_es_vdx_per_share)?
+            .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
+            .safe_sub(self.es_vdx_debt)?;
+
+        self.es_vdx_accumulated = self.es_vdx_accumulated.safe_add(pending_es_vdx)?;
+        pool.withdraw_es_vdx(pending_es_vdx)
+    }
+
+    fn update_debt(&mut self, pool: &StakingPool) -> DexResult {
+        let weight = self.weight()?;
+
+        self.reward_debt = weight
+            .safe_mul(pool.accumulate_reward_per_share)?
+            .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
+
+        self.es_vdx_debt = weight
+            .safe_mul(pool.accumulate_es_vdx_per_share)?
+            .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
+
+        Ok(())
+    }
+
     pub fn withdraw_reward(&mut self, pool: &mut StakingPool, amount: u64) -> DexResult<u64> {
         let pending = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.reward_debt)?;
@@ -221,7 +346,7 @@ impl UserStake {
 
         self.reward_accumulated = self.reward_accumulated.safe_sub(withdrawable)?;
         self.reward_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
@@ -230,7 +355,7 @@ impl UserStake {
 
     pub fn withdraw_es_vdx(&mut self, pool: &mut StakingPool) -> DexResult<u64> {
         let pending = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.es_vdx_debt)?;
@@ -240,7 +365,7 @@ impl UserStake {
 
         self.es_vdx_accumulated = 0;
         self.es_vdx_debt = self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64;
 
@@ -249,7 +374,7 @@ impl UserStake {
 
     pub fn pending_reward(&self, pool: &StakingPool) -> DexResult<u64> {
         let pending = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_reward_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.reward_debt)?;
@@ -259,7 +384,7 @@ impl UserStake {
 
     pub fn pending_es_vdx(&self, pool: &StakingPool) -> DexResult<u64> {
         let pending = (self
-            .staked
+            .weight()?
             .safe_mul(pool.accumulate_es_vdx_per_share)?
             .safe_div(REWARD_SHARE_POW_DECIMALS as u128)? as u64)
             .safe_sub(self.es_vdx_debt)?;
@@ -546,4 +671,83 @@ mod test {
         assert_eq!(alice.pending_es_vdx(&pool).assert_unwrap(), usdc(125.));
         assert_eq!(bob.pending_es_vdx(&pool).assert_unwrap(), usdc(125.));
     }
+
+    #[test]
+    //
+    // Alice stakes 1000 unlocked, Bob locks 1000 for 12 months with a 2x multiplier.
+    // Bob earns twice as much until his lock is released.
+    //
+    fn test_locked_staking() {
+        let mut pool = StakingPool::default();
+        let mut alice = UserStake::default();
+        let mut bob = UserStake::default();
+
+        alice.enter_staking(&mut pool, usdc(1000.)).assert_ok();
+        bob.enter_locked_staking(&mut pool, usdc(1000.), 4)
+            .assert_err();
+        bob.enter_locked_staking(&mut pool, usdc(1000.), 3)
+            .assert_ok();
+
+        assert_eq!(bob.staked, 0);
+        assert_eq!(bob.locked, usdc(1000.));
+        assert_eq!(bob.locked_weight, usdc(2000.));
+        assert_eq!(pool.staked_total, usdc(3000.));
+        assert_eq!(alice.fee_tier_amount().assert_unwrap(), 0);
+        assert_eq!(bob.fee_tier_amount().assert_unwrap(), usdc(1000.));
+
+        pool.add_rewards(eth(0.3)).assert_ok();
+        pool.add_es_vdx(usdc(300.)).assert_ok();
+        assert_eq!(alice.pending_reward(&pool).assert_unwrap(), eth(0.1));
+        assert_eq!(bob.pending_reward(&pool).assert_unwrap(), eth(0.2));
+        assert_eq!(bob.pending_es_vdx(&pool).assert_unwrap(), usdc(200.));
+
+        // Locked amount is not redeemable
+        bob.leave_staking(&mut pool, usdc(1000.)).assert_err();
+        assert_eq!(bob.release_locked(&mut pool).assert_unwrap(), 0);
+
+        // Lock matured
+        bob.unlock_time = 0;
+        assert_eq!(bob.fee_tier_amount().assert_unwrap(), 0);
+        assert_eq!(bob.release_locked(&mut pool).assert_unwrap(), usdc(1000.));
+        assert_eq!(bob.staked, usdc(1000.));
+        assert_eq!(bob.locked_weight, 0);
+        assert_eq!(bob.fee_tier_amount().assert_unwrap(), 0);
+        assert_eq!(pool.staked_total, usdc(2000.));
+        assert_eq!(bob.pending_reward(&pool).assert_unwrap(), eth(0.2));
+
+        pool.add_rewards(eth(0.2)).assert_ok();
+        assert_eq!(alice.pending_reward(&pool).assert_unwrap(), eth(0.2));
+        assert_eq!(bob.pending_reward(&pool).assert_unwrap(), eth(0.3));
+
+        assert_eq!(
+            bob.leave_staking(&mut pool, usdc(1000.)).assert_unwrap(),
+            usdc(1000.)
+        );
+    }
+
+    #[test]
+    fn test_locked_staking_top_up() {
+        let mut pool = StakingPool::default();
+        let mut bob = UserStake::default();
+
+        bob.enter_locked_staking(&mut pool, usdc(1000.), 3)
+            .assert_ok();
+        let unlock_time = bob.unlock_time;
+
+        // Top-ups join the current lock without moving its expiry
+        bob.enter_locked_staking(&mut pool, usdc(100.), 3)
+            .assert_ok();
+        bob.enter_locked_staking(&mut pool, usdc(100.), 0)
+            .assert_ok();
+        assert_eq!(bob.unlock_time, unlock_time);
+        assert_eq!(bob.locked, usdc(1200.));
+        assert_eq!(bob.locked_weight, usdc(2000. + 200. + 110.));
+        assert_eq!(pool.staked_total, usdc(2310.));
+
+        // A tier running past the expiry is rejected
+        bob.unlock_time -= 1;
+        bob.enter_locked_staking(&mut pool, usdc(100.), 3)
+            .assert_err();
+        assert_eq!(bob.locked, usdc(1200.));
+    }
 }
@@ -1,4 +1,7 @@
+use std::convert::TryFrom;
+
 use anchor_lang::prelude::*;
+use num_enum::TryFromPrimitive;
 
 use crate::{
     dex::get_price,
@@ -6,12 +9,50 @@ use crate::{
     utils::{
         swap, time::get_timestamp, value, ISafeAddSub, ISafeMath, SafeMath, BORROW_FEE_RATE_BASE,
         ES_VDX_PERCENTAGE_FOR_VDX_POOL, ES_VDX_PER_SECOND, FEE_RATE_BASE, FEE_RATE_DECIMALS,
-        LEVERAGE_POW_DECIMALS, MAX_ASSET_COUNT, MAX_MARKET_COUNT, MAX_PRICE_COUNT,
-        REWARD_PERCENTAGE_FOR_VDX_POOL, UPDATE_REWARDS_PERIOD, USD_POW_DECIMALS, VDX_TOTAL_SUPPLY,
+        FEE_TIER_COUNT, FLASH_LOAN_FEE_RATE, LEVERAGE_POW_DECIMALS, MAX_ASSET_COUNT,
+        MAX_ASSET_FEE_RATE, MAX_AUM_AGE, MAX_FEE_TIER_DISCOUNT, MAX_LEVERAGE,
+        MAX_LIQUIDATE_THRESHOLD, MAX_MARKET_COUNT, MAX_MARKET_FEE_RATE, MAX_PRICE_COUNT,
+        MAX_PROTOCOL_FEE_PERCENTAGE, MAX_REFERRAL_PERCENTAGE, MAX_SETTLE_PRICE_DEVIATION,
+        MAX_TARGET_WEIGHT, MIN_REDUCE_ONLY_DURATION, REWARD_PERCENTAGE_FOR_VDX_POOL,
+        UPDATE_REWARDS_PERIOD, USDC_POW_DECIMALS, USD_POW_DECIMALS, VDX_TOTAL_SUPPLY,
     },
 };
 
-use super::{get_oracle_price, StakingPool};
+use super::{
+    get_feed_price, get_oracle_price, AssetParams, EmissionParams, MarketParams, OracleSource,
+    StakingPool,
+};
+
+#[derive(Copy, Clone, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum TradingState {
+    Active = 0,
+    ReduceOnly = 1,
+    Paused = 2,
+}
+
+impl TradingState {
+    // Increasing actions open exposure or bring funds in (open, bid, swap in,
+    // deposit, DI buy), reducing ones get users out (close, ask, withdraw).
+    pub fn check(state: u8, increase: bool) -> DexResult {
+        match TradingState::try_from(state).map_err(|_| DexError::InvalidTradingState)? {
+            TradingState::Active => Ok(()),
+            TradingState::ReduceOnly => {
+                require!(!increase, DexError::ReduceOnly);
+                Ok(())
+            }
+            TradingState::Paused => Err(error!(DexError::TradingPaused)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum TradingScope {
+    Global = 0,
+    Market = 1,
+    Asset = 2,
+}
 
 #[account(zero_copy)]
 pub struct Dex {
@@ -33,7 +74,23 @@ pub struct Dex {
     pub assets_number: u8,
     pub markets_number: u8,
     pub usdc_asset_index: u8,
-    pub padding: [u8; 252],
+    pub es_vdx_percentage_for_vdx_pool: u16,
+    pub reward_percentage_for_vdx_pool: u16,
+    pub cached_aum: i64,
+    pub cached_aum_timestamp: i64,
+    pub es_vdx_per_second: u64,
+    pub vdx_total_supply: u64,
+    pub emission_start_time: i64,
+    pub emission_halving_interval: i64,
+    pub pending_authority: Pubkey,
+    pub timelock: Pubkey,
+    pub fee_tier_staked: [u64; FEE_TIER_COUNT],
+    pub fee_tier_discounts: [u16; FEE_TIER_COUNT],
+    pub protocol_fee_percentage: u16,
+    pub referral_discount_percentage: u16,
+    pub referral_rebate_percentage: u16,
+    pub trading_state: u8,
+    pub padding: [u8; 89],
 }
 
 impl Dex {
@@ -79,6 +136,141 @@ impl Dex {
         ))
     }
 
+    pub fn check_market_trading(&self, market: u8, increase: bool) -> DexResult {
+        require!(market < self.markets_number, DexError::InvalidMarketIndex);
+
+        self.check_no_flash_loan()?;
+        TradingState::check(self.trading_state, increase)?;
+        TradingState::check(self.markets[market as usize].trading_state, increase)
+    }
+
+    pub fn check_asset_trading(&self, asset: u8, increase: bool) -> DexResult {
+        require!(asset < self.assets_number, DexError::InvalidAssetIndex);
+
+        self.check_no_flash_loan()?;
+        TradingState::check(self.trading_state, increase)?;
+        TradingState::check(self.assets[asset as usize].trading_state, increase)
+    }
+
+    // Lent out funds are still counted in `liquidity_amount`, pool and trade
+    // instructions are rejected until the loan is repaid.
+    pub fn check_no_flash_loan(&self) -> DexResult {
+        require!(
+            self.assets[..self.assets_number as usize]
+                .iter()
+                .all(|a| a.flash_loan_amount == 0),
+            DexError::FlashLoanInProgress
+        );
+
+        Ok(())
+    }
+
+    pub fn market_as_mut(&mut self, market: u8) -> DexResult<&mut MarketInfo> {
+        require!(
+            market < self.markets_number && self.markets[market as usize].valid,
+            DexError::InvalidMarketIndex
+        );
+
+        Ok(&mut self.markets[market as usize])
+    }
+
+    // Step 1 of delisting is switching the market to reduce only, the time is
+    // recorded so users get a chance to close before the market is settled.
+    pub fn set_market_trading_state(&mut self, market: u8, state: u8) -> DexResult<u8> {
+        let now = get_timestamp()?;
+        let mi = self.market_as_mut(market)?;
+
+        let old_state = mi.trading_state;
+        if state != TradingState::ReduceOnly as u8 {
+            mi.reduce_only_since = 0;
+        } else if old_state != state {
+            mi.reduce_only_since = now;
+        }
+        mi.trading_state = state;
+
+        Ok(old_state)
+    }
+
+    // Step 2, once the market has been reduce only for the timelock delay (at least
+    // MIN_REDUCE_ONLY_DURATION). Remaining positions are then settled at
+    // `settle_price`, which must be close to the oracle price.
+    pub fn delist_market(
+        &mut self,
+        market: u8,
+        settle_price: u64,
+        oracle_price: u64,
+        delay: i64,
+    ) -> DexResult {
+        let now = get_timestamp()?;
+        let mi = self.market_as_mut(market)?;
+        require!(
+            mi.trading_state == TradingState::ReduceOnly as u8 && mi.settle_price == 0,
+            DexError::InvalidTradingState
+        );
+        require!(
+            now.i_safe_sub(mi.reduce_only_since)? >= delay.max(MIN_REDUCE_ONLY_DURATION),
+            DexError::ReduceOnlyTooShort
+        );
+
+        require!(
+            settle_price > 0
+                && settle_price.abs_diff(oracle_price) as u128
+                    <= oracle_price
+                        .safe_mul(MAX_SETTLE_PRICE_DEVIATION)?
+                        .safe_div(100)?,
+            DexError::InvalidSettlePrice
+        );
+
+        mi.settle_price = settle_price;
+        mi.trading_state = TradingState::Paused as u8;
+        mi.reduce_only_since = 0;
+
+        Ok(())
+    }
+
+    pub fn remove_market(&mut self, market: u8) -> DexResult {
+        let mi = self.market_as_mut(market)?;
+        require!(mi.settle_price > 0, DexError::NotDelisted);
+        require!(
+            mi.global_long.size == 0 && mi.global_short.size == 0,
+            DexError::DelistNotCompleted
+        );
+
+        mi.valid = false;
+
+        Ok(())
+    }
+
+    // Asset slots are never reused, the price feed is indexed by asset.
+    pub fn remove_asset(&mut self, asset: u8) -> DexResult {
+        require!(asset != self.usdc_asset_index, DexError::InvalidAssetIndex);
+        require!(
+            !self.markets[..self.markets_number as usize]
+                .iter()
+                .any(|m| m.valid && m.asset_index == asset),
+            DexError::DelistNotCompleted
+        );
+
+        let ai = self.asset_as_mut(asset)?;
+        require!(
+            ai.trading_state != TradingState::Active as u8,
+            DexError::InvalidTradingState
+        );
+        require!(
+            ai.liquidity_amount == 0
+                && ai.collateral_amount == 0
+                && ai.borrowed_amount == 0
+                && ai.fee_amount == 0
+                && ai.protocol_fee_amount == 0
+                && ai.flash_loan_amount == 0,
+            DexError::DelistNotCompleted
+        );
+
+        ai.valid = false;
+
+        Ok(())
+    }
+
     pub fn market_asset(&mut self, market: u8, long: bool) -> DexResult<&mut AssetInfo> {
         require!(market < self.markets_number, DexError::InvalidMarketIndex);
 
@@ -119,12 +311,91 @@ impl Dex {
         Ok((index, &self.assets[index as usize]))
     }
 
-    fn aum(&self, oracles: &[AccountInfo], price_feed: &PriceFeed) -> DexResult<i64> {
+    // Oracle accounts follow the refresh_aum layout, valid assets first and then valid
+    // markets. Prices are indexed by asset and market index, 0 for invalid entries.
+    pub fn oracle_prices(
+        &self,
+        oracles: &[AccountInfo],
+        price_feed: &PriceFeed,
+    ) -> DexResult<(Vec<u64>, Vec<u64>)> {
+        let mut asset_prices = vec![0u64; self.assets_number as usize];
+        let mut market_prices = vec![0u64; self.markets_number as usize];
+
+        let mut oracle_offset = 0;
+        for (i, price) in asset_prices.iter_mut().enumerate() {
+            let ai = &self.assets[i];
+            if !ai.valid {
+                continue;
+            }
+
+            require!(oracle_offset < oracles.len(), DexError::InvalidOracle);
+            require_eq!(
+                ai.oracle,
+                oracles[oracle_offset].key(),
+                DexError::InvalidOracle
+            );
+
+            *price = get_price(
+                i as u8,
+                ai.oracle_source,
+                &oracles[oracle_offset],
+                price_feed,
+            )?;
+            oracle_offset += 1;
+        }
+
+        for (i, price) in market_prices.iter_mut().enumerate() {
+            let mi = &self.markets[i];
+            if !mi.valid {
+                continue;
+            }
+
+            require!(oracle_offset < oracles.len(), DexError::InvalidOracle);
+            require_eq!(
+                mi.oracle,
+                oracles[oracle_offset].key(),
+                DexError::InvalidOracle
+            );
+
+            *price = get_price(
+                mi.asset_index,
+                mi.oracle_source,
+                &oracles[oracle_offset],
+                price_feed,
+            )?;
+            oracle_offset += 1;
+        }
+
+        Ok((asset_prices, market_prices))
+    }
+
+    // Asset prices known without oracle accounts: the last pushed feed price, or the
+    // fixed price of a stable coin. Zero where neither is available.
+    pub fn feed_prices(&self, price_feed: &PriceFeed) -> DexResult<Vec<u64>> {
+        let mut asset_prices = vec![0u64; self.assets_number as usize];
+
+        for (i, price) in asset_prices.iter_mut().enumerate() {
+            let ai = &self.assets[i];
+            if !ai.valid {
+                continue;
+            }
+
+            *price = get_feed_price(i as u8, price_feed)?;
+            if *price == 0 && ai.oracle_source == OracleSource::StableCoin as u8 {
+                *price = USDC_POW_DECIMALS;
+            }
+        }
+
+        Ok(asset_prices)
+    }
+
+    // Asset prices are kept to value the fees collected until the next refresh
+    fn aum(&mut self, oracles: &[AccountInfo], price_feed: &PriceFeed) -> DexResult<i64> {
         let mut aum = 0u64;
 
         let mut oracle_offset = 0;
         for i in 0..self.assets_number as usize {
-            let ai = &self.assets[i];
+            let ai = &mut self.assets[i];
             if !ai.valid {
                 continue;
             }
@@ -143,6 +414,7 @@ impl Dex {
                 &oracles[oracle_offset],
                 price_feed,
             )?;
+            ai.cached_price = price;
 
             let amount = ai
                 .liquidity_amount
@@ -187,17 +459,87 @@ impl Dex {
         (aum as i64).i_safe_sub(pnl)
     }
 
-    fn to_oracle_index(&self, index: u8) -> DexResult<usize> {
-        require!(index < self.assets_number, DexError::InvalidAssetIndex);
+    pub fn refresh_aum(&mut self, oracles: &[AccountInfo], price_feed: &PriceFeed) -> DexResult {
+        self.cached_aum = self.aum(oracles, price_feed)?;
+        self.cached_aum_timestamp = get_timestamp()?;
+
+        self.distribute_fees()
+    }
+
+    // Pool amounts and global positions valued at the prices of the last AUM refresh
+    fn cached_pool_value(&self) -> DexResult<i64> {
+        let mut amounts = 0u64;
+        for ai in self.assets[..self.assets_number as usize].iter() {
+            if !ai.valid {
+                continue;
+            }
+
+            let amount = ai
+                .liquidity_amount
+                .safe_add(ai.collateral_amount)?
+                .safe_add(ai.borrowed_amount)?;
+            amounts = amounts.safe_add(value(amount, ai.cached_price, ai.decimals)?)?;
+        }
 
-        let mut oracle_index = 0usize;
-        for i in 0..index as usize {
-            if self.assets[i].valid {
-                oracle_index += 1;
+        let mut pnl = 0i64;
+        for mi in self.markets[..self.markets_number as usize].iter() {
+            if !mi.valid {
+                continue;
             }
+
+            let price = self.assets[mi.asset_index as usize].cached_price;
+            pnl = pnl.i_safe_add(mi.un_pnl(price)?)?;
         }
 
-        Ok(oracle_index)
+        (amounts as i64).i_safe_sub(pnl)
+    }
+
+    // Open, close and liquidation move the pool amounts and PnL, the cached AUM
+    // follows them until the next refresh
+    fn apply_aum_delta(&mut self, before: i64) -> DexResult {
+        let after = self.cached_pool_value()?;
+        self.cached_aum = self.cached_aum.i_safe_add(after.i_safe_sub(before)?)?;
+
+        Ok(())
+    }
+
+    pub fn credit_liquidity(&mut self, index: u8, amount: u64) -> DexResult {
+        let before = self.cached_pool_value()?;
+
+        let ai = self.asset_as_mut(index)?;
+        ai.liquidity_amount = ai.liquidity_amount.safe_add(amount)?;
+
+        self.apply_aum_delta(before)
+    }
+
+    fn distribute_fees(&mut self) -> DexResult {
+        let vlp_rewards = self.collect_rewards_as_vlp()?;
+
+        let vdx_pool_percentage = if self.vdx_pool.staked_total == 0 {
+            0
+        } else {
+            self.get_reward_percentage_for_vdx_pool()
+        } as u64;
+
+        let vlp_rewards_for_vdx_pool =
+            vlp_rewards.safe_mul(vdx_pool_percentage)?.safe_div(100)? as u64;
+        let vlp_rewards_for_vlp_pool = vlp_rewards.safe_sub(vlp_rewards_for_vdx_pool)?;
+
+        self.vdx_pool.add_rewards(vlp_rewards_for_vdx_pool)?;
+        self.vlp_pool.add_rewards(vlp_rewards_for_vlp_pool)?;
+
+        Ok(())
+    }
+
+    fn fresh_aum(&self) -> DexResult<i64> {
+        let now = get_timestamp()?;
+        require!(
+            now - self.cached_aum_timestamp <= MAX_AUM_AGE,
+            DexError::StaleAUM
+        );
+        require!(self.cached_aum >= 0, DexError::AUMBelowZero);
+
+        Ok(self.cached_aum)
     }
 
     pub fn add_liquidity(
@@ -205,17 +547,14 @@ impl Dex {
         index: u8,
         amount: u64,
         charge_fee: bool,
-        oracles: &[AccountInfo],
+        oracle: &AccountInfo,
         price_feed: &PriceFeed,
     ) -> DexResult<(u64, u64)> {
         require!(amount > 0, DexError::InvalidAmount);
 
-        let aum = self.aum(oracles, price_feed)?;
-        require!(aum >= 0, DexError::AUMBelowZero);
+        let aum = self.fresh_aum()?;
         let (vlp_supply, vlp_decimals, _) = self.vlp_info()?;
 
-        let oracle_index = self.to_oracle_index(index)?;
-
         let ai = self.asset_as_mut(index)?;
 
         let fee = if charge_fee {
@@ -231,13 +570,10 @@ impl Dex {
         ai.liquidity_amount = ai.liquidity_amount.safe_add(added)?;
         ai.fee_amount = ai.fee_amount.safe_add(fee)?;
 
-        require!(
-            ai.oracle == oracles[oracle_index].key(),
-            DexError::InvalidOracle
-        );
+        require!(ai.oracle == oracle.key(), DexError::InvalidOracle);
 
         // vlp_amount = asset_value * vlp_supply / aum
-        let price = get_price(index, ai.oracle_source, &oracles[oracle_index], price_feed)?;
+        let price = get_price(index, ai.oracle_source, oracle, price_feed)?;
         let asset_value = value(added, price, ai.decimals)?;
 
         let vlp_amount = if aum == 0 {
@@ -248,6 +584,8 @@ impl Dex {
             asset_value.safe_mul(vlp_supply)?.safe_div(aum as u128)? as u64
         };
 
+        self.cached_aum = aum.i_safe_add(asset_value as i64)?;
+
         Ok((vlp_amount, fee))
     }
 
@@ -255,25 +593,20 @@ impl Dex {
         &mut self,
         index: u8,
         amount: u64,
-        oracles: &[AccountInfo],
+        oracle: &AccountInfo,
         price_feed: &PriceFeed,
     ) -> DexResult<(u64, u64)> {
         require!(amount > 0, DexError::InvalidAmount);
 
-        let aum = self.aum(oracles, price_feed)?;
-        require!(aum >= 0, DexError::AUMBelowZero);
+        let aum = self.fresh_aum()?;
 
         let (vlp_supply, vlp_decimals, _) = self.vlp_info()?;
         require!(vlp_supply > 0, DexError::VLPSupplyZero);
-        let oracle_index = self.to_oracle_index(index)?;
 
         let ai = self.asset_as_mut(index)?;
-        require!(
-            ai.oracle == oracles[oracle_index].key(),
-            DexError::InvalidOracle
-        );
+        require!(ai.oracle == oracle.key(), DexError::InvalidOracle);
 
-        let asset_price = get_price(index, ai.oracle_source, &oracles[oracle_index], price_feed)?;
+        let asset_price = get_price(index, ai.oracle_source, oracle, price_feed)?;
         // vlp_price= aum / vlp_supply
         let vlp_price = (aum as u64)
             .safe_mul(10u64.pow(vlp_decimals.into()))?
@@ -291,6 +624,9 @@ impl Dex {
             .map_err(|_| DexError::InsufficientLiquidity)?;
         ai.fee_amount = ai.fee_amount.safe_add(fee)?;
 
+        let out_value = value(out_amount, asset_price, ai.decimals)?;
+        self.cached_aum = aum.i_safe_sub(out_value as i64)?;
+
         Ok((out_amount.safe_sub(fee)?, fee))
     }
 
@@ -312,15 +648,18 @@ impl Dex {
         }
     }
 
+    // `base_collateral` is false for shorts and stable collateral longs, whose
+    // collateral and borrow are held in USDC
     pub fn borrow_fund(
         &mut self,
         market: u8,
-        long: bool,
+        base_collateral: bool,
         collateral: u64,
         borrow: u64,
         open_fee: u64,
     ) -> DexResult {
-        let ai = self.market_asset(market, long)?;
+        let before = self.cached_pool_value()?;
+        let ai = self.market_asset(market, base_collateral)?;
 
         ai.fee_amount = ai.fee_amount.safe_add(open_fee)?;
         ai.liquidity_amount = ai
@@ -330,20 +669,21 @@ impl Dex {
         ai.collateral_amount = ai.collateral_amount.safe_add(collateral)?;
         ai.borrowed_amount = ai.borrowed_amount.safe_add(borrow)?;
 
-        Ok(())
+        self.apply_aum_delta(before)
     }
 
     pub fn settle_pnl(
         &mut self,
         market: u8,
-        long: bool,
+        base_collateral: bool,
         collateral: u64,
         borrow: u64,
         pnl: i64,
         close_fee: u64,
         borrow_fee: u64,
     ) -> DexResult<u64> {
-        let ai = self.market_asset(market, long)?;
+        let before = self.cached_pool_value()?;
+        let ai = self.market_asset(market, base_collateral)?;
 
         ai.liquidity_amount = ai.liquidity_amount.safe_add(borrow)?;
         ai.collateral_amount = ai.collateral_amount.safe_sub(collateral)?;
@@ -377,6 +717,8 @@ impl Dex {
             }
         };
 
+        self.apply_aum_delta(before)?;
+
         Ok(user_withdrawable)
     }
 
@@ -388,6 +730,7 @@ impl Dex {
         size: u64,
         collateral: u64,
     ) -> DexResult {
+        let before = self.cached_pool_value()?;
         let pos = self.position_as_mut(market, long)?;
 
         let merged_size = pos.size.safe_add(size)?;
@@ -402,7 +745,7 @@ impl Dex {
         pos.collateral = pos.collateral.safe_add(collateral)?;
         pos.last_fill_time = get_timestamp()?;
 
-        Ok(())
+        self.apply_aum_delta(before)
     }
 
     pub fn decrease_global_position(
@@ -412,6 +755,7 @@ impl Dex {
         size: u64,
         collateral: u64,
     ) -> DexResult {
+        let before = self.cached_pool_value()?;
         let pos = self.position_as_mut(market, long)?;
 
         pos.collateral = pos.collateral.safe_sub(collateral)?;
@@ -422,6 +766,45 @@ impl Dex {
             pos.zero(long)?;
         }
 
+        self.apply_aum_delta(before)
+    }
+
+    pub fn adjust_collateral(
+        &mut self,
+        market: u8,
+        long: bool,
+        base_collateral: bool,
+        added: u64,
+        removed: u64,
+    ) -> DexResult {
+        let before = self.cached_pool_value()?;
+        let ai = self.market_asset(market, base_collateral)?;
+        ai.collateral_amount = ai.collateral_amount.safe_add(added)?.safe_sub(removed)?;
+        self.apply_aum_delta(before)?;
+
+        // Global long collateral is kept in the market asset
+        if long && !base_collateral {
+            return Ok(());
+        }
+
+        let pos = self.position_as_mut(market, long)?;
+        pos.collateral = pos.collateral.safe_add(added)?.safe_sub(removed)?;
+
+        Ok(())
+    }
+
+    pub fn collect_borrow_fee(
+        &mut self,
+        market: u8,
+        long: bool,
+        base_collateral: bool,
+        fee: u64,
+    ) -> DexResult {
+        self.adjust_collateral(market, long, base_collateral, 0, fee)?;
+
+        let ai = self.market_asset(market, base_collateral)?;
+        ai.fee_amount = ai.fee_amount.safe_add(fee)?;
+
         Ok(())
     }
 
@@ -475,45 +858,25 @@ impl Dex {
         Ok((out, fee))
     }
 
-    fn collect_rewards_as_vlp(
-        &mut self,
-        oracles: &[AccountInfo],
-        price_feed: &PriceFeed,
-    ) -> DexResult<u64> {
+    fn collect_rewards_as_vlp(&mut self) -> DexResult<u64> {
         let mut collected_vlp: u64 = 0;
-        let mut oracle_offset = 0usize;
 
-        let mut aum = self.aum(oracles, price_feed)?;
-        require!(aum >= 0, DexError::AUMBelowZero);
+        let mut aum = self.fresh_aum()?;
         let (mut vlp_supply, vlp_decimals, _) = self.vlp_info()?;
 
         for i in 0..self.assets_number as usize {
-            let ai = self.asset_as_ref(i as u8)?;
-            if !ai.valid {
+            let ai = &mut self.assets[i];
+            if !ai.valid || ai.fee_amount == 0 {
                 continue;
             }
 
-            if ai.fee_amount == 0 {
-                oracle_offset += 1;
-                continue;
-            }
-
-            require!(oracle_offset < oracles.len(), DexError::InvalidOracle);
-
-            require_eq!(
-                ai.oracle,
-                oracles[oracle_offset].key(),
-                DexError::InvalidOracle
-            );
-
-            let price = get_price(
-                i as u8,
-                ai.oracle_source,
-                &oracles[oracle_offset],
-                price_feed,
-            )?;
-            let fee = ai.fee_amount;
-            let fee_value = value(fee, price, ai.decimals)?;
+            // The protocol share stays in the vault until withdrawn by the authority
+            let protocol_fee = ai
+                .fee_amount
+                .safe_mul(self.protocol_fee_percentage as u64)?
+                .safe_div(100)? as u64;
+            let fee = ai.fee_amount.safe_sub(protocol_fee)?;
+            let fee_value = value(fee, ai.cached_price, ai.decimals)?;
 
             let vlp_minted = if aum == 0 {
                 fee_value
@@ -526,17 +889,120 @@ impl Dex {
             aum += fee_value as i64;
             vlp_supply += vlp_minted;
 
-            let mai = self.asset_as_mut(i as u8)?;
-            mai.liquidity_amount = mai.liquidity_amount.safe_add(fee)?;
-            mai.fee_amount = 0;
+            ai.liquidity_amount = ai.liquidity_amount.safe_add(fee)?;
+            ai.protocol_fee_amount = ai.protocol_fee_amount.safe_add(protocol_fee)?;
+            ai.fee_amount = 0;
 
             collected_vlp = collected_vlp.safe_add(vlp_minted)?;
-            oracle_offset += 1;
         }
 
+        self.cached_aum = aum;
+
         Ok(collected_vlp)
     }
 
+    // Applies to fees collected on the next AUM refresh
+    pub fn set_protocol_fee_percentage(&mut self, percentage: u16) -> DexResult {
+        require!(
+            percentage <= MAX_PROTOCOL_FEE_PERCENTAGE,
+            DexError::InvalidProtocolFeePercentage
+        );
+        self.protocol_fee_percentage = percentage;
+
+        Ok(())
+    }
+
+    pub fn withdraw_protocol_fees(&mut self, index: u8) -> DexResult<u64> {
+        let ai = self.asset_as_mut(index)?;
+
+        let amount = ai.protocol_fee_amount;
+        require!(amount > 0, DexError::InsufficientProtocolFee);
+        ai.protocol_fee_amount = 0;
+
+        Ok(amount)
+    }
+
+    // Discount percentage on open and close fees of the highest tier reached by the locked VDX
+    pub fn fee_discount(&self, vdx_locked: u64) -> u16 {
+        self.fee_tier_staked
+            .iter()
+            .zip(self.fee_tier_discounts.iter())
+            .filter(|(staked, discount)| **discount > 0 && vdx_locked >= **staked)
+            .map(|(_, discount)| *discount)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn check_fee_tiers(
+        &self,
+        staked: [u64; FEE_TIER_COUNT],
+        discounts: [u16; FEE_TIER_COUNT],
+    ) -> DexResult {
+        for i in 0..FEE_TIER_COUNT {
+            require!(
+                discounts[i] <= MAX_FEE_TIER_DISCOUNT,
+                DexError::InvalidFeeTiers
+            );
+
+            if i > 0 && discounts[i] > 0 {
+                require!(
+                    staked[i] > staked[i - 1] && discounts[i] > discounts[i - 1],
+                    DexError::InvalidFeeTiers
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_fee_tiers(
+        &mut self,
+        staked: [u64; FEE_TIER_COUNT],
+        discounts: [u16; FEE_TIER_COUNT],
+    ) -> DexResult {
+        self.check_fee_tiers(staked, discounts)?;
+
+        self.fee_tier_staked = staked;
+        self.fee_tier_discounts = discounts;
+
+        Ok(())
+    }
+
+    // Both are percentages of the open and close fees of referred users
+    pub fn set_referral_rates(&mut self, discount: u16, rebate: u16) -> DexResult {
+        require!(
+            discount.saturating_add(rebate) <= MAX_REFERRAL_PERCENTAGE,
+            DexError::InvalidReferralRates
+        );
+        self.referral_discount_percentage = discount;
+        self.referral_rebate_percentage = rebate;
+
+        Ok(())
+    }
+
+    // Carve the trader discount and the referrer rebate out of a collected fee
+    pub fn take_referral_fee(
+        &mut self,
+        index: u8,
+        fee: u64,
+        with_rebate: bool,
+    ) -> DexResult<(u64, u64)> {
+        let discount = fee
+            .safe_mul(self.referral_discount_percentage as u64)?
+            .safe_div(100)? as u64;
+        let rebate = if with_rebate {
+            fee.safe_mul(self.referral_rebate_percentage as u64)?
+                .safe_div(100)? as u64
+        } else {
+            0
+        };
+
+        let ai = self.asset_as_mut(index)?;
+        ai.fee_amount = ai.fee_amount.safe_sub(discount.safe_add(rebate)?)?;
+
+        Ok((discount, rebate))
+    }
+
     pub fn swap_in(&mut self, index: u8, amount: u64, fee: u64) -> DexResult {
         let ai = self.asset_as_mut(index)?;
 
@@ -554,6 +1020,37 @@ impl Dex {
         Ok(())
     }
 
+    pub fn flash_loan_begin(&mut self, index: u8, amount: u64) -> DexResult {
+        let ai = self.asset_as_mut(index)?;
+
+        require!(ai.flash_loan_amount == 0, DexError::FlashLoanInProgress);
+        require!(amount > 0, DexError::InvalidAmount);
+        require!(
+            ai.liquidity_amount >= amount,
+            DexError::InsufficientLiquidity
+        );
+
+        ai.flash_loan_amount = amount;
+
+        Ok(())
+    }
+
+    pub fn flash_loan_end(&mut self, index: u8) -> DexResult<(u64, u64)> {
+        let ai = self.asset_as_mut(index)?;
+
+        let amount = ai.flash_loan_amount;
+        require!(amount > 0, DexError::NoFlashLoanInProgress);
+
+        let fee = amount
+            .safe_mul(ai.get_flash_loan_fee_rate() as u64)?
+            .safe_div(FEE_RATE_BASE)? as u64;
+
+        ai.fee_amount = ai.fee_amount.safe_add(fee)?;
+        ai.flash_loan_amount = 0;
+
+        Ok((amount, fee))
+    }
+
     pub fn di_option_borrow(&mut self, asset_index: u8, borrow: u64) -> DexResult {
         let ai = self.asset_as_mut(asset_index)?;
 
@@ -604,25 +1101,136 @@ impl Dex {
         Ok(())
     }
 
-    fn mint_es_vdx(&mut self, seconds: u64) -> DexResult {
-        if seconds == 0 {
+    // Dex accounts created before the emission schedule moved on chain read a
+    // zero start time, they keep the original constants until set_emission runs.
+    fn emission_configured(&self) -> bool {
+        self.emission_start_time != 0
+    }
+
+    pub fn get_es_vdx_per_second(&self) -> u64 {
+        if self.emission_configured() {
+            self.es_vdx_per_second
+        } else {
+            ES_VDX_PER_SECOND
+        }
+    }
+
+    pub fn get_vdx_total_supply(&self) -> u64 {
+        if self.emission_configured() {
+            self.vdx_total_supply
+        } else {
+            VDX_TOTAL_SUPPLY
+        }
+    }
+
+    pub fn get_es_vdx_percentage_for_vdx_pool(&self) -> u16 {
+        if self.emission_configured() {
+            self.es_vdx_percentage_for_vdx_pool
+        } else {
+            ES_VDX_PERCENTAGE_FOR_VDX_POOL as u16
+        }
+    }
+
+    pub fn get_reward_percentage_for_vdx_pool(&self) -> u16 {
+        if self.emission_configured() {
+            self.reward_percentage_for_vdx_pool
+        } else {
+            REWARD_PERCENTAGE_FOR_VDX_POOL as u16
+        }
+    }
+
+    pub fn check_emission_params(&self, params: &EmissionParams) -> DexResult {
+        require!(
+            params.emission_halving_interval >= 0
+                && params.es_vdx_percentage_for_vdx_pool <= 100
+                && params.reward_percentage_for_vdx_pool <= 100
+                && params.vdx_total_supply >= self.vdx_supply,
+            DexError::InvalidEmissionParams
+        );
+
+        Ok(())
+    }
+
+    // The new schedule starts now, emission accrued under the old one is settled first
+    pub fn set_emission(&mut self, params: &EmissionParams) -> DexResult {
+        self.check_emission_params(params)?;
+        self.update_staking_pool(true)?;
+
+        self.es_vdx_per_second = params.es_vdx_per_second;
+        self.vdx_total_supply = params.vdx_total_supply;
+        self.emission_start_time = get_timestamp()?;
+        self.emission_halving_interval = params.emission_halving_interval;
+        self.es_vdx_percentage_for_vdx_pool = params.es_vdx_percentage_for_vdx_pool;
+        self.reward_percentage_for_vdx_pool = params.reward_percentage_for_vdx_pool;
+
+        Ok(())
+    }
+
+    pub fn es_vdx_emission_rate(&self, at: i64) -> u64 {
+        if self.emission_halving_interval <= 0 || at <= self.emission_start_time {
+            return self.get_es_vdx_per_second();
+        }
+
+        let halvings = (at - self.emission_start_time) / self.emission_halving_interval;
+        if halvings >= u64::BITS as i64 {
+            0
+        } else {
+            self.get_es_vdx_per_second() >> halvings
+        }
+    }
+
+    // Emission amount between `from` and `to`, split at every halving on the way.
+    pub fn es_vdx_emission(&self, from: i64, to: i64) -> DexResult<u64> {
+        let mut amount = 0u64;
+        let mut cursor = from;
+
+        while cursor < to {
+            let rate = self.es_vdx_emission_rate(cursor);
+            if rate == 0 {
+                break;
+            }
+
+            let end = if self.emission_halving_interval <= 0 {
+                to
+            } else if cursor < self.emission_start_time {
+                self.emission_start_time.min(to)
+            } else {
+                let epoch = (cursor - self.emission_start_time) / self.emission_halving_interval;
+                (self.emission_start_time + (epoch + 1) * self.emission_halving_interval).min(to)
+            };
+
+            amount = amount.safe_add(rate.safe_mul((end - cursor) as u64)? as u64)?;
+            cursor = end;
+        }
+
+        Ok(amount)
+    }
+
+    fn mint_es_vdx(&mut self, from: i64, to: i64) -> DexResult {
+        if to <= from {
             return Ok(());
         }
 
-        let mut mint_amount = ES_VDX_PER_SECOND.safe_mul(seconds)? as u64;
-        if mint_amount.safe_add(self.vdx_supply)? > VDX_TOTAL_SUPPLY {
-            mint_amount = VDX_TOTAL_SUPPLY.safe_sub(self.vdx_supply)?;
+        let mut mint_amount = self.es_vdx_emission(from, to)?;
+        let vdx_total_supply = self.get_vdx_total_supply();
+        if mint_amount.safe_add(self.vdx_supply)? > vdx_total_supply {
+            mint_amount = vdx_total_supply.saturating_sub(self.vdx_supply);
         }
 
         self.vdx_supply += mint_amount;
-        if mint_amount == 0 {
+
+        self.distribute_es_vdx(mint_amount)
+    }
+
+    pub fn distribute_es_vdx(&mut self, amount: u64) -> DexResult {
+        if amount == 0 {
             return Ok(());
         }
 
-        let vdx_pool_amount = mint_amount
-            .safe_mul(ES_VDX_PERCENTAGE_FOR_VDX_POOL as u64)?
+        let vdx_pool_amount = amount
+            .safe_mul(self.get_es_vdx_percentage_for_vdx_pool() as u64)?
             .safe_div(100)? as u64;
-        let vlp_pool_amount = mint_amount.safe_sub(vdx_pool_amount)?;
+        let vlp_pool_amount = amount.safe_sub(vdx_pool_amount)?;
 
         self.vdx_pool.add_es_vdx(vdx_pool_amount)?;
         self.vlp_pool.add_es_vdx(vlp_pool_amount)?;
@@ -630,34 +1238,24 @@ impl Dex {
         Ok(())
     }
 
-    pub fn update_staking_pool(
-        &mut self,
-        oracles: &[AccountInfo],
-        price_feed: &PriceFeed,
-        force: bool,
-    ) -> DexResult {
+    pub fn update_staking_pool(&mut self, force: bool) -> DexResult {
         let now = get_timestamp()?;
         let elapsed = now - self.update_rewards_last_timestamp;
         if elapsed < UPDATE_REWARDS_PERIOD && !force {
             return Ok(());
         }
 
-        let vlp_rewards = self.collect_rewards_as_vlp(oracles, price_feed)?;
-
-        let vdx_pool_percentage = if self.vdx_pool.staked_total == 0 {
-            0
-        } else {
-            REWARD_PERCENTAGE_FOR_VDX_POOL
-        } as u64;
-
-        let vlp_rewards_for_vdx_pool =
-            vlp_rewards.safe_mul(vdx_pool_percentage)?.safe_div(100)? as u64;
-        let vlp_rewards_for_vlp_pool = vlp_rewards.safe_sub(vlp_rewards_for_vdx_pool)?;
-
-        self.vdx_pool.add_rewards(vlp_rewards_for_vdx_pool)?;
-        self.vlp_pool.add_rewards(vlp_rewards_for_vlp_pool)?;
+        // Fees are valued at the prices of the last AUM refresh, once the cache
+        // is stale they wait for the next refresh
+        if self.fresh_aum().is_ok()
+            && self.assets[..self.assets_number as usize]
+                .iter()
+                .any(|a| a.valid && a.fee_amount > 0)
+        {
+            self.distribute_fees()?;
+        }
 
-        self.mint_es_vdx(elapsed as u64)?;
+        self.mint_es_vdx(self.update_rewards_last_timestamp, now)?;
 
         self.update_rewards_last_timestamp = now;
 
@@ -685,7 +1283,62 @@ pub struct AssetInfo {
     pub decimals: u8,
     pub nonce: u8,
     pub oracle_source: u8,
-    pub padding: [u8; 250],
+    pub flash_loan_fee_rate: u16,
+    pub flash_loan_amount: u64,
+    pub protocol_fee_amount: u64,
+    pub trading_state: u8,
+    pub _align: [u8; 7],
+    pub cached_price: u64,
+    pub padding: [u8; 216],
+}
+
+impl AssetInfo {
+    // Assets listed before flash loans were added read a zero rate
+    pub fn get_flash_loan_fee_rate(&self) -> u16 {
+        if self.flash_loan_fee_rate == 0 {
+            FLASH_LOAN_FEE_RATE
+        } else {
+            self.flash_loan_fee_rate
+        }
+    }
+
+    pub fn params(&self) -> AssetParams {
+        AssetParams {
+            oracle: self.oracle.to_bytes(),
+            swap_fee_rate: self.swap_fee_rate,
+            borrow_fee_rate: self.borrow_fee_rate,
+            add_liquidity_fee_rate: self.add_liquidity_fee_rate,
+            remove_liquidity_fee_rate: self.remove_liquidity_fee_rate,
+            flash_loan_fee_rate: self.flash_loan_fee_rate,
+            target_weight: self.target_weight,
+            oracle_source: self.oracle_source,
+        }
+    }
+
+    pub fn set_params(&mut self, params: &AssetParams) -> DexResult {
+        OracleSource::try_from(params.oracle_source).map_err(|_| DexError::InvalidOracleSource)?;
+
+        require!(
+            params.swap_fee_rate <= MAX_ASSET_FEE_RATE
+                && params.borrow_fee_rate <= MAX_ASSET_FEE_RATE
+                && params.add_liquidity_fee_rate <= MAX_ASSET_FEE_RATE
+                && params.remove_liquidity_fee_rate <= MAX_ASSET_FEE_RATE
+                && params.flash_loan_fee_rate <= MAX_ASSET_FEE_RATE
+                && params.target_weight <= MAX_TARGET_WEIGHT,
+            DexError::InvalidAssetParams
+        );
+
+        self.oracle = Pubkey::new_from_array(params.oracle);
+        self.swap_fee_rate = params.swap_fee_rate;
+        self.borrow_fee_rate = params.borrow_fee_rate;
+        self.add_liquidity_fee_rate = params.add_liquidity_fee_rate;
+        self.remove_liquidity_fee_rate = params.remove_liquidity_fee_rate;
+        self.flash_loan_fee_rate = params.flash_loan_fee_rate;
+        self.target_weight = params.target_weight;
+        self.oracle_source = params.oracle_source;
+
+        Ok(())
+    }
 }
 
 #[zero_copy]
@@ -715,7 +1368,11 @@ pub struct MarketInfo {
     pub asset_index: u8,
     pub significant_decimals: u8,
     pub order_pool_remaining_pages_number: u8,
-    pub padding: [u8; 254],
+    pub trading_state: u8,
+    pub _align: [u8; 5],
+    pub settle_price: u64, // Final price of a delisted market
+    pub reduce_only_since: i64,
+    pub padding: [u8; 232],
 }
 
 pub struct MarketFeeRates {
@@ -730,19 +1387,64 @@ pub struct MarketFeeRates {
 }
 
 impl MarketInfo {
-    pub fn get_fee_rates(&self, borrow_fee_rate: u16) -> MarketFeeRates {
+    pub fn get_fee_rates(&self, borrow_fee_rate: u16, fee_discount: u16) -> MarketFeeRates {
+        let discounted =
+            |rate: u16| (rate as u32 * 100u32.saturating_sub(fee_discount as u32) / 100) as u16;
+
         MarketFeeRates {
             charge_borrow_fee_interval: self.charge_borrow_fee_interval,
             minimum_collateral: self.minimum_collateral,
             borrow_fee_rate,
+            open_fee_rate: discounted(self.open_fee_rate),
+            close_fee_rate: discounted(self.close_fee_rate),
+            liquidate_fee_rate: self.liquidate_fee_rate,
+            liquidate_threshold: self.liquidate_threshold,
+            base_decimals: self.decimals,
+        }
+    }
+
+    pub fn params(&self) -> MarketParams {
+        MarketParams {
+            oracle: self.oracle.to_bytes(),
+            minimum_collateral: self.minimum_collateral,
+            charge_borrow_fee_interval: self.charge_borrow_fee_interval,
             open_fee_rate: self.open_fee_rate,
             close_fee_rate: self.close_fee_rate,
             liquidate_fee_rate: self.liquidate_fee_rate,
             liquidate_threshold: self.liquidate_threshold,
-            base_decimals: self.decimals,
+            max_leverage: self.max_leverage,
+            oracle_source: self.oracle_source,
         }
     }
 
+    pub fn set_params(&mut self, params: &MarketParams) -> DexResult {
+        OracleSource::try_from(params.oracle_source).map_err(|_| DexError::InvalidOracleSource)?;
+
+        require!(
+            params.charge_borrow_fee_interval > 0
+                && params.open_fee_rate <= MAX_MARKET_FEE_RATE
+                && params.close_fee_rate <= MAX_MARKET_FEE_RATE
+                && params.liquidate_fee_rate <= MAX_MARKET_FEE_RATE
+                && params.liquidate_threshold > 0
+                && params.liquidate_threshold <= MAX_LIQUIDATE_THRESHOLD
+                && params.max_leverage >= LEVERAGE_POW_DECIMALS
+                && params.max_leverage <= MAX_LEVERAGE * LEVERAGE_POW_DECIMALS,
+            DexError::InvalidMarketParams
+        );
+
+        self.oracle = Pubkey::new_from_array(params.oracle);
+        self.minimum_collateral = params.minimum_collateral;
+        self.charge_borrow_fee_interval = params.charge_borrow_fee_interval;
+        self.open_fee_rate = params.open_fee_rate;
+        self.close_fee_rate = params.close_fee_rate;
+        self.liquidate_fee_rate = params.liquidate_fee_rate;
+        self.liquidate_threshold = params.liquidate_threshold;
+        self.max_leverage = params.max_leverage;
+        self.oracle_source = params.oracle_source;
+
+        Ok(())
+    }
+
     pub fn un_pnl(&self, price: u64) -> DexResult<i64> {
         let short_pnl = (self.global_short.average_price as i128 - price as i128)
             .i_safe_mul(self.global_short.size as i128)?
@@ -769,7 +1471,8 @@ pub struct Position {
     pub loss_stop_price: u64,
     pub profit_stop_price: u64,
     pub long: bool,
-    pub _padding: [u8; 7],
+    pub stable_collateral: bool,
+    pub _padding: [u8; 6],
 }
 
 impl Position {
@@ -791,6 +1494,30 @@ impl Position {
         self.loss_stop_price = 0;
         self.profit_stop_price = 0;
         self.long = long;
+        self.stable_collateral = false;
+
+        Ok(())
+    }
+
+    // Collateral, borrow and PnL are in the market asset, otherwise in USDC
+    #[inline]
+    pub fn base_collateral(&self) -> bool {
+        self.long && !self.stable_collateral
+    }
+
+    // A long can keep USDC collateral, the choice is fixed until the position is closed
+    pub fn set_stable_collateral(&mut self, stable_collateral: bool) -> DexResult {
+        require!(
+            self.long || !stable_collateral,
+            DexError::CollateralAssetMismatch
+        );
+        if self.size > 0 {
+            require!(
+                self.stable_collateral == stable_collateral,
+                DexError::CollateralAssetMismatch
+            );
+        }
+        self.stable_collateral = stable_collateral;
 
         Ok(())
     }
@@ -838,7 +1565,7 @@ impl Position {
         let (collateral, open_fee) =
             Position::calc_collateral_and_fee(amount, leverage, mfr.open_fee_rate)?;
 
-        let size = if self.long {
+        let size = if self.base_collateral() {
             collateral
                 .safe_mul(leverage as u64)?
                 .safe_div(LEVERAGE_POW_DECIMALS.into())
@@ -852,21 +1579,10 @@ impl Position {
 
         // Update cumulative fund fee
         let now = get_timestamp()?;
-        let cumulative_fund_fee = if self.borrowed_amount > 0 {
-            require!(self.last_fill_time <= now, DexError::InvalidPositionTime);
-
-            self.borrowed_amount
-                .safe_mul(mfr.borrow_fee_rate as u64)?
-                .safe_mul((now - self.last_fill_time) as u128)?
-                .safe_div(BORROW_FEE_RATE_BASE)?
-                .safe_div(mfr.charge_borrow_fee_interval as u128)? as u64
-                + self.cumulative_fund_fee
-        } else {
-            0
-        };
+        let cumulative_fund_fee = self.borrow_fee(now, mfr)?;
 
         // Update borrowed amount
-        let borrow = if self.long {
+        let borrow = if self.base_collateral() {
             Ok(size as u128)
         } else {
             collateral
@@ -923,34 +1639,15 @@ impl Position {
 
         // Update cumulative fund fee
         let now = get_timestamp()?;
-        let borrow_fee = if self.borrowed_amount > 0 {
-            require!(self.last_fill_time <= now, DexError::InvalidPositionTime);
-
-            self.borrowed_amount
-                .safe_mul(mfr.borrow_fee_rate as u64)?
-                .safe_mul((now - self.last_fill_time) as u128)?
-                .safe_div(BORROW_FEE_RATE_BASE)?
-                .safe_div(mfr.charge_borrow_fee_interval as u128)? as u64
-                + self.cumulative_fund_fee
-        } else {
-            0
-        };
+        let borrow_fee = self.borrow_fee(now, mfr)?;
 
         // Calculate close position fee
         let rate = if liquidate {
             mfr.liquidate_fee_rate
         } else {
             mfr.close_fee_rate
-        } as u64;
-        let close_fee = if self.long {
-            closing_size.safe_mul(rate)?.safe_div(FEE_RATE_BASE)? as u64
-        } else {
-            closing_size
-                .safe_mul(price)?
-                .safe_mul(rate as u128)?
-                .safe_div(10u64.pow(mfr.base_decimals as u32 + FEE_RATE_DECIMALS) as u128)?
-                as u64
         };
+        let close_fee = self.close_fee(closing_size, price, rate, mfr.base_decimals)?;
 
         let total_fee = borrow_fee.safe_add(close_fee)?;
         let pnl = self.pnl(closing_size, price, self.average_price, mfr.base_decimals)?;
@@ -995,6 +1692,110 @@ impl Position {
         ))
     }
 
+    // Rebalance the collateral against the borrowed amount, size and borrow stay untouched.
+    // Returns the collateral pulled from and refunded to the user.
+    pub fn set_leverage(
+        &mut self,
+        leverage: u32,
+        price: u64,
+        mfr: &MarketFeeRates,
+    ) -> DexResult<(u64, u64)> {
+        require!(
+            self.size > 0 && self.borrowed_amount > 0,
+            DexError::PositionNotExist
+        );
+
+        let collateral = self
+            .borrowed_amount
+            .safe_mul(LEVERAGE_POW_DECIMALS as u64)?
+            .safe_div(leverage as u128)? as u64;
+        require!(collateral > 0, DexError::InvalidLeverage);
+
+        if collateral >= self.collateral {
+            let added = collateral.safe_sub(self.collateral)?;
+            self.collateral = collateral;
+
+            return Ok((added, 0));
+        }
+
+        // The remaining collateral has to keep the position clear of liquidation
+        let borrow_fee = self.borrow_fee(get_timestamp()?, mfr)?;
+        let close_fee =
+            self.close_fee(self.size, price, mfr.liquidate_fee_rate, mfr.base_decimals)?;
+        let pnl = self.pnl(self.size, price, self.average_price, mfr.base_decimals)?;
+
+        let remaining = (collateral as i64)
+            .i_safe_add(pnl)?
+            .i_safe_sub(borrow_fee.safe_add(close_fee)? as i64)?;
+        let threshold = collateral
+            .safe_mul(mfr.liquidate_threshold as u64)?
+            .safe_div(100u128)? as i64;
+        require!(remaining > threshold, DexError::InvalidLeverage);
+
+        let refunded = self.collateral.safe_sub(collateral)?;
+        self.collateral = collateral;
+
+        Ok((0, refunded))
+    }
+
+    // Charge the accrued borrow fee from the collateral. Whatever the collateral can't cover
+    // stays accrued until the position is closed or liquidated.
+    pub fn settle_borrow_fee(&mut self, mfr: &MarketFeeRates) -> DexResult<u64> {
+        require!(self.size > 0, DexError::PositionNotExist);
+
+        let now = get_timestamp()?;
+        let borrow_fee = self.borrow_fee(now, mfr)?;
+        let charged = borrow_fee.min(self.collateral);
+
+        // Only the time paid by the accrued fee is settled, rounded up so that no second is
+        // charged twice. The rounded off part keeps accruing until the next settlement.
+        let accrued = borrow_fee.safe_sub(self.cumulative_fund_fee)?;
+        let rate = self.borrowed_amount.safe_mul(mfr.borrow_fee_rate as u64)?;
+        let settled_time = if rate == 0 {
+            now.i_safe_sub(self.last_fill_time)?
+        } else {
+            (accrued as u128)
+                .safe_mul(BORROW_FEE_RATE_BASE)?
+                .safe_mul(mfr.charge_borrow_fee_interval as u128)?
+                .safe_add(rate - 1)?
+                .safe_div(rate)? as i64
+        };
+
+        self.collateral = self.collateral.safe_sub(charged)?;
+        self.cumulative_fund_fee = borrow_fee.safe_sub(charged)?;
+        self.last_fill_time = self.last_fill_time.i_safe_add(settled_time)?;
+
+        Ok(charged)
+    }
+
+    pub fn borrow_fee(&self, now: i64, mfr: &MarketFeeRates) -> DexResult<u64> {
+        if self.borrowed_amount == 0 {
+            return Ok(0);
+        }
+        require!(self.last_fill_time <= now, DexError::InvalidPositionTime);
+
+        Ok(self
+            .borrowed_amount
+            .safe_mul(mfr.borrow_fee_rate as u64)?
+            .safe_mul((now - self.last_fill_time) as u128)?
+            .safe_div(BORROW_FEE_RATE_BASE)?
+            .safe_div(mfr.charge_borrow_fee_interval as u128)? as u64
+            + self.cumulative_fund_fee)
+    }
+
+    pub fn close_fee(&self, size: u64, price: u64, rate: u16, base_decimals: u8) -> DexResult<u64> {
+        let fee = if self.base_collateral() {
+            size.safe_mul(rate as u64)?.safe_div(FEE_RATE_BASE)? as u64
+        } else {
+            size.safe_mul(price)?
+                .safe_mul(rate as u128)?
+                .safe_div(10u64.pow(base_decimals as u32 + FEE_RATE_DECIMALS) as u128)?
+                as u64
+        };
+
+        Ok(fee)
+    }
+
     pub fn sub_closing(&mut self, closing_size: u64) -> DexResult {
         self.closing_size = self.closing_size.safe_sub(closing_size)?;
         Ok(())
@@ -1033,10 +1834,14 @@ impl Position {
         open_price: u64,
         base_decimals: u8,
     ) -> DexResult<i64> {
-        let pnl = if self.long {
+        let pnl = if self.base_collateral() {
             (close_price as i128 - open_price as i128)
                 .i_safe_mul(size as i128)?
                 .i_safe_div(open_price as i128)? as i64
+        } else if self.long {
+            (close_price as i128 - open_price as i128)
+                .i_safe_mul(size as i128)?
+                .i_safe_div(10i128.pow(base_decimals as u32))? as i64
         } else {
             (open_price as i128 - close_price as i128)
                 .i_safe_mul(size as i128)?
@@ -1045,6 +1850,46 @@ impl Position {
 
         Ok(pnl)
     }
+
+    // The market price at which the position gets liquidated: collateral + pnl - borrow fee
+    // - liquidate fee falls to the liquidate threshold of the collateral. Longs are liquidated
+    // at or below it, shorts at or above it. Zero for an empty position.
+    pub fn liquidation_price(&self, now: i64, mfr: &MarketFeeRates) -> DexResult<u64> {
+        if self.size == 0 {
+            return Ok(0);
+        }
+
+        let size = self.size as i128;
+        let average_price = self.average_price as i128;
+        let rate = mfr.liquidate_fee_rate as i128;
+        let fee_base = FEE_RATE_BASE as i128;
+        let base = 10i128.pow(mfr.base_decimals as u32);
+
+        let threshold = self
+            .collateral
+            .safe_mul(mfr.liquidate_threshold as u64)?
+            .safe_div(100u128)? as i128;
+        // Loss the collateral can absorb before hitting the threshold
+        let margin = (self.collateral as i128) - (self.borrow_fee(now, mfr)? as i128) - threshold;
+
+        let price = if self.base_collateral() {
+            // Liquidate fee is charged in the market asset, independent of the price
+            let fee = self.close_fee(self.size, 0, mfr.liquidate_fee_rate, mfr.base_decimals)?;
+            (size - margin + fee as i128)
+                .i_safe_mul(average_price)?
+                .i_safe_div(size)?
+        } else if self.long {
+            (average_price.i_safe_mul(size)? - margin.i_safe_mul(base)?)
+                .i_safe_mul(fee_base)?
+                .i_safe_div(size.i_safe_mul(fee_base - rate)?)?
+        } else {
+            (average_price.i_safe_mul(size)? + margin.i_safe_mul(base)?)
+                .i_safe_mul(fee_base)?
+                .i_safe_div(size.i_safe_mul(fee_base + rate)?)?
+        };
+
+        Ok(price.max(0) as u64)
+    }
 }
 
 #[account]
@@ -1120,7 +1965,10 @@ mod test {
     use super::*;
     use crate::{
         dex::{set_mock_price, OracleSource},
-        utils::test::*,
+        utils::{
+            test::*, ES_VDX_PERCENTAGE_FOR_VDX_POOL, ES_VDX_PER_SECOND,
+            REWARD_PERCENTAGE_FOR_VDX_POOL, VDX_TOTAL_SUPPLY,
+        },
     };
     use bumpalo::Bump;
 
@@ -1214,6 +2062,11 @@ mod test {
             };
             self.markets_number += 1;
             self.usdc_asset_index = 1;
+
+            self.es_vdx_per_second = ES_VDX_PER_SECOND;
+            self.vdx_total_supply = VDX_TOTAL_SUPPLY;
+            self.es_vdx_percentage_for_vdx_pool = ES_VDX_PERCENTAGE_FOR_VDX_POOL as u16;
+            self.reward_percentage_for_vdx_pool = REWARD_PERCENTAGE_FOR_VDX_POOL as u16;
         }
 
         pub fn add_asset(&mut self, decimals: u8, oracle: Pubkey) {
@@ -1343,6 +2196,86 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_dex_layout() {
+        assert_eq!(std::mem::size_of::<Dex>(), 25304);
+
+        // New fields are carved from the tail padding
+        let dex = Dex::default();
+        assert_eq!(
+            field_offset!(dex, update_rewards_last_timestamp),
+            field_offset!(dex, price_feed) + 32
+        );
+
+        let tail = field_offset!(dex, usdc_asset_index) + 1;
+        assert_eq!(field_offset!(dex, cached_aum), tail + 4);
+        assert_eq!(field_offset!(dex, cached_aum_timestamp), tail + 12);
+        assert_eq!(field_offset!(dex, es_vdx_per_second), tail + 20);
+        assert_eq!(field_offset!(dex, emission_halving_interval), tail + 44);
+        assert_eq!(field_offset!(dex, pending_authority), tail + 52);
+        assert_eq!(field_offset!(dex, timelock), tail + 84);
+        assert_eq!(field_offset!(dex, fee_tier_staked), tail + 116);
+        assert_eq!(
+            field_offset!(dex, padding) + dex.padding.len(),
+            std::mem::size_of::<Dex>()
+        );
+    }
+
+    #[test]
+    fn test_market_info_layout() {
+        assert_eq!(std::mem::size_of::<MarketInfo>(), 1080);
+
+        let mi = MarketInfo::default();
+        let tail = field_offset!(mi, order_pool_remaining_pages_number) + 1;
+        assert_eq!(field_offset!(mi, trading_state), tail);
+        assert_eq!(field_offset!(mi, settle_price), tail + 6);
+        assert_eq!(field_offset!(mi, reduce_only_since), tail + 14);
+        assert_eq!(
+            field_offset!(mi, padding) + mi.padding.len(),
+            std::mem::size_of::<MarketInfo>()
+        );
+    }
+
+    #[test]
+    fn test_flash_loan() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        dex.assets[0].liquidity_amount = btc(10.);
+        dex.assets[0].flash_loan_fee_rate = 9;
+
+        dex.flash_loan_end(0).assert_err();
+        dex.flash_loan_begin(0, btc(10.1)).assert_err();
+        dex.flash_loan_begin(0, 0).assert_err();
+
+        dex.flash_loan_begin(0, btc(10.)).assert_ok();
+        assert_eq!(dex.assets[0].flash_loan_amount, btc(10.));
+
+        // No nested loans on the same asset
+        dex.flash_loan_begin(0, btc(1.)).assert_err();
+
+        let (amount, fee) = dex.flash_loan_end(0).assert_unwrap();
+        assert_eq!(amount, btc(10.));
+        assert_eq!(fee, btc(0.009));
+        assert_eq!(dex.assets[0].fee_amount, btc(0.009));
+        assert_eq!(dex.assets[0].flash_loan_amount, 0);
+        assert_eq!(dex.assets[0].liquidity_amount, btc(10.));
+
+        dex.flash_loan_end(0).assert_err();
+
+        // Zero rate falls back to the default
+        dex.assets[0].flash_loan_fee_rate = 0;
+        dex.flash_loan_begin(0, btc(1.)).assert_ok();
+
+        // Pool and trade instructions wait for the repayment
+        dex.check_asset_trading(1, false).assert_err();
+        dex.check_market_trading(0, false).assert_err();
+
+        let (_, fee) = dex.flash_loan_end(0).assert_unwrap();
+        assert_eq!(fee, btc(0.0009));
+        dex.check_asset_trading(1, false).assert_ok();
+        dex.check_market_trading(0, false).assert_ok();
+    }
+
     #[test]
     fn test_global_pos_invalid_market() {
         let mut dex = Dex::default();
@@ -1528,7 +2461,7 @@ mod test {
     fn test_open_long_position() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
 
         let mut long = Position::new(true).assert_unwrap();
         let (size, collateral, borrow, open_fee) = long
@@ -1576,7 +2509,7 @@ mod test {
     fn test_close_long_position_with_profit() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
 
         let mut long = Position::new(true).assert_unwrap();
         let leverage = 20u64;
@@ -1607,98 +2540,331 @@ mod test {
     }
 
     #[test]
-    fn test_close_long_position_with_loss() {
+    fn test_close_long_position_with_loss() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
+
+        let mut long = Position::new(true).assert_unwrap();
+        let leverage = 5u64;
+        let (size, collateral, borrow, _) = long
+            .open(usdc(20000.), btc(1.0), leverage as u32 * 1000, &mfr)
+            .assert_unwrap();
+
+        const HOURS_2: u64 = 2;
+        long.mock_after_hours(HOURS_2);
+
+        let (returned, collateral_unlocked, pnl, _closed_size, close_fee, borrow_fee) = long
+            .close(size, usdc(18000.), &mfr, false, false)
+            .assert_unwrap();
+
+        let expected_borrow_fee = collateral * leverage * (mfr.borrow_fee_rate as u64) * HOURS_2
+            / BORROW_FEE_RATE_BASE as u64;
+
+        let expected_close_fee = size * (mfr.close_fee_rate as u64) / FEE_RATE_BASE as u64;
+
+        let expected_pnl = size * (usdc(20000.) - usdc(18000.)) / usdc(20000.);
+
+        assert_eq!(returned, borrow);
+        assert_eq!(collateral_unlocked, collateral);
+        assert_eq!(borrow_fee, expected_borrow_fee);
+        assert_eq!(close_fee, expected_close_fee);
+        assert_eq!(pnl, -(expected_pnl as i64));
+    }
+
+    #[test]
+    fn test_open_short_position() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
+
+        let mut short = Position::new(false).assert_unwrap();
+        let leverage = 10u64;
+        let (size, collateral, borrow, open_fee) = short
+            .open(usdc(20000.), usdc(2000.), leverage as u32 * 1000, &mfr)
+            .assert_unwrap();
+
+        let expected_open_fee = usdc(39.215686);
+        let expected_collateral = usdc(2000.0) - expected_open_fee;
+        let expected_size = ((expected_collateral as u128)
+            * (leverage as u128)
+            * 10u128.pow(mfr.base_decimals.into())
+            / usdc(20000.) as u128) as u64;
+
+        assert_eq!(open_fee, expected_open_fee);
+        assert_eq!(collateral, expected_collateral);
+        assert_eq!(size, expected_size);
+        assert_eq!(borrow, expected_collateral * leverage);
+
+        assert_eq!(short.size, expected_size);
+        assert_eq!(short.average_price, usdc(20000.));
+        assert_eq!(short.collateral, expected_collateral);
+        assert_eq!(short.borrowed_amount, expected_collateral * leverage);
+        assert_eq!(short.closing_size, 0);
+        assert_eq!(short.cumulative_fund_fee, 0);
+
+        const HOURS_2: u64 = 2;
+        short.mock_after_hours(HOURS_2);
+
+        // Short more
+        short
+            .open(usdc(20000.), usdc(2000.0), leverage as u32 * 1000, &mfr)
+            .assert_unwrap();
+        assert_eq!(open_fee, expected_open_fee);
+        assert_eq!(collateral, expected_collateral);
+        assert_eq!(size, expected_size);
+        assert_eq!(borrow, expected_collateral * leverage);
+
+        assert_eq!(short.size, expected_size * 2);
+        assert_eq!(short.average_price, usdc(20000.));
+        assert_eq!(short.collateral, expected_collateral * 2);
+        assert_eq!(short.borrowed_amount, expected_collateral * leverage * 2);
+        assert_eq!(short.closing_size, 0);
+
+        let expected_fund_fee =
+            expected_collateral * leverage * (mfr.borrow_fee_rate as u64) * HOURS_2
+                / BORROW_FEE_RATE_BASE as u64;
+        assert_eq!(short.cumulative_fund_fee, expected_fund_fee);
+    }
+
+    #[test]
+    fn test_stable_collateral_long_position() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
+
+        // Shorts always keep USDC
+        let mut short = Position::new(false).assert_unwrap();
+        short.set_stable_collateral(true).assert_err();
+
+        let mut long = Position::new(true).assert_unwrap();
+        long.set_stable_collateral(true).assert_ok();
+        assert!(!long.base_collateral());
+
+        let leverage = 10u64;
+        let (size, collateral, borrow, open_fee) = long
+            .open(usdc(20000.), usdc(2000.), leverage as u32 * 1000, &mfr)
+            .assert_unwrap();
+
+        // Sized and borrowed like a short, in USDC
+        let expected_open_fee = usdc(39.215686);
+        let expected_collateral = usdc(2000.0) - expected_open_fee;
+        let expected_size = ((expected_collateral as u128)
+            * (leverage as u128)
+            * 10u128.pow(mfr.base_decimals.into())
+            / usdc(20000.) as u128) as u64;
+
+        assert_eq!(open_fee, expected_open_fee);
+        assert_eq!(collateral, expected_collateral);
+        assert_eq!(size, expected_size);
+        assert_eq!(borrow, expected_collateral * leverage);
+
+        // The collateral asset can't change while the position is open
+        long.set_stable_collateral(false).assert_err();
+
+        // PnL is settled in USDC
+        let (returned, collateral_unlocked, pnl, _, close_fee, _) = long
+            .close(size, usdc(22000.), &mfr, false, false)
+            .assert_unwrap();
+
+        let expected_pnl =
+            (size as u128 * usdc(2000.) as u128 / 10u128.pow(mfr.base_decimals.into())) as i64;
+        let expected_close_fee = (size as u128 * usdc(22000.) as u128 * mfr.close_fee_rate as u128
+            / 10u128.pow(mfr.base_decimals as u32 + FEE_RATE_DECIMALS))
+            as u64;
+
+        assert_eq!(returned, borrow);
+        assert_eq!(collateral_unlocked, collateral);
+        assert_eq!(pnl, expected_pnl);
+        assert_eq!(close_fee, expected_close_fee);
+
+        // Closed positions start over with the market asset
+        assert!(long.base_collateral());
+    }
+
+    #[test]
+    fn test_set_position_leverage() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
+
+        let mut short = Position::new(false).assert_unwrap();
+        short.set_leverage(5000, usdc(20000.), &mfr).assert_err();
+
+        let (size, collateral, borrow, _) = short
+            .open(usdc(20000.), usdc(2000.), 10 * 1000, &mfr)
+            .assert_unwrap();
+        dex.increase_global_position(0, false, usdc(20000.), size, collateral)
+            .assert_ok();
+        dex.borrow_fund(0, false, collateral, 0, 0).assert_ok();
+
+        // Halve the leverage, the user adds collateral
+        let (added, refunded) = short
+            .set_leverage(5 * 1000, usdc(20000.), &mfr)
+            .assert_unwrap();
+        assert_eq!(added, collateral);
+        assert_eq!(refunded, 0);
+        assert_eq!(short.collateral, collateral * 2);
+        assert_eq!(short.size, size);
+        assert_eq!(short.borrowed_amount, borrow);
+
+        dex.adjust_collateral(0, false, false, added, refunded)
+            .assert_ok();
+        dex.assert_usdc_collateral(collateral * 2);
+        assert_eq!(dex.markets[0].global_short.collateral, collateral * 2);
+
+        // Too close to liquidation once the price moves against the short
+        short
+            .set_leverage(20 * 1000, usdc(21000.), &mfr)
+            .assert_err();
+        assert_eq!(short.collateral, collateral * 2);
+
+        // Raise the leverage, the excess collateral is refunded
+        let (added, refunded) = short
+            .set_leverage(20 * 1000, usdc(20000.), &mfr)
+            .assert_unwrap();
+        assert_eq!(added, 0);
+        assert_eq!(refunded, collateral * 2 - borrow / 20);
+        assert_eq!(short.collateral, borrow / 20);
+
+        dex.adjust_collateral(0, false, false, added, refunded)
+            .assert_ok();
+        dex.assert_usdc_collateral(borrow / 20);
+        assert_eq!(dex.markets[0].global_short.collateral, borrow / 20);
+    }
+
+    #[test]
+    fn test_liquidation_price() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mut mfr = dex.markets[0].get_fee_rates(20, 0);
+        mfr.liquidate_threshold = 10;
+
+        let remaining = |p: &Position, price: u64| -> i64 {
+            let mut p = *p;
+            let (_, collateral, pnl, _, close_fee, borrow_fee) =
+                p.close(p.size, price, &mfr, true, false).assert_unwrap();
+            collateral as i64 + pnl - (close_fee + borrow_fee) as i64
+        };
+        let threshold = |p: &Position| (p.collateral / 10) as i64;
 
-        let mut long = Position::new(true).assert_unwrap();
-        let leverage = 5u64;
-        let (size, collateral, borrow, _) = long
-            .open(usdc(20000.), btc(1.0), leverage as u32 * 1000, &mfr)
-            .assert_unwrap();
+        assert_eq!(
+            Position::new(true)
+                .assert_unwrap()
+                .liquidation_price(get_timestamp().assert_unwrap(), &mfr)
+                .assert_unwrap(),
+            0
+        );
 
-        const HOURS_2: u64 = 2;
-        long.mock_after_hours(HOURS_2);
+        let mut long = Position::new(true).assert_unwrap();
+        long.open(usdc(20000.), btc(0.1), 10 * 1000, &mfr)
+            .assert_ok();
 
-        let (returned, collateral_unlocked, pnl, _closed_size, close_fee, borrow_fee) = long
-            .close(size, usdc(18000.), &mfr, false, false)
-            .assert_unwrap();
+        let mut stable_long = Position::new(true).assert_unwrap();
+        stable_long.set_stable_collateral(true).assert_ok();
+        stable_long
+            .open(usdc(20000.), usdc(2000.), 10 * 1000, &mfr)
+            .assert_ok();
 
-        let expected_borrow_fee = collateral * leverage * (mfr.borrow_fee_rate as u64) * HOURS_2
-            / BORROW_FEE_RATE_BASE as u64;
+        let mut short = Position::new(false).assert_unwrap();
+        short
+            .open(usdc(20000.), usdc(2000.), 10 * 1000, &mfr)
+            .assert_ok();
 
-        let expected_close_fee = size * (mfr.close_fee_rate as u64) / FEE_RATE_BASE as u64;
+        for mut p in [long, stable_long, short] {
+            p.mock_after_hours(2);
+            let price = p
+                .liquidation_price(get_timestamp().assert_unwrap(), &mfr)
+                .assert_unwrap();
 
-        let expected_pnl = size * (usdc(20000.) - usdc(18000.)) / usdc(20000.);
+            // Longs are liquidated below the open price, shorts above it
+            if p.long {
+                assert!(price < usdc(20000.) && price > usdc(18000.));
+            } else {
+                assert!(price > usdc(20000.) && price < usdc(22000.));
+            }
 
-        assert_eq!(returned, borrow);
-        assert_eq!(collateral_unlocked, collateral);
-        assert_eq!(borrow_fee, expected_borrow_fee);
-        assert_eq!(close_fee, expected_close_fee);
-        assert_eq!(pnl, -(expected_pnl as i64));
+            let (safe, unsafe_) = if p.long {
+                (price + usdc(1.), price - usdc(1.))
+            } else {
+                (price - usdc(1.), price + usdc(1.))
+            };
+            assert!(remaining(&p, safe) > threshold(&p));
+            assert!(remaining(&p, unsafe_) <= threshold(&p));
+        }
     }
 
     #[test]
-    fn test_open_short_position() {
+    fn test_settle_borrow_fee() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
 
         let mut short = Position::new(false).assert_unwrap();
-        let leverage = 10u64;
-        let (size, collateral, borrow, open_fee) = short
-            .open(usdc(20000.), usdc(2000.), leverage as u32 * 1000, &mfr)
-            .assert_unwrap();
-
-        let expected_open_fee = usdc(39.215686);
-        let expected_collateral = usdc(2000.0) - expected_open_fee;
-        let expected_size = ((expected_collateral as u128)
-            * (leverage as u128)
-            * 10u128.pow(mfr.base_decimals.into())
-            / usdc(20000.) as u128) as u64;
-
-        assert_eq!(open_fee, expected_open_fee);
-        assert_eq!(collateral, expected_collateral);
-        assert_eq!(size, expected_size);
-        assert_eq!(borrow, expected_collateral * leverage);
+        short.settle_borrow_fee(&mfr).assert_err();
 
-        assert_eq!(short.size, expected_size);
-        assert_eq!(short.average_price, usdc(20000.));
-        assert_eq!(short.collateral, expected_collateral);
-        assert_eq!(short.borrowed_amount, expected_collateral * leverage);
-        assert_eq!(short.closing_size, 0);
-        assert_eq!(short.cumulative_fund_fee, 0);
+        let (size, collateral, borrow, _) = short
+            .open(usdc(20000.), usdc(2000.), 10 * 1000, &mfr)
+            .assert_unwrap();
+        dex.increase_global_position(0, false, usdc(20000.), size, collateral)
+            .assert_ok();
+        dex.borrow_fund(0, false, collateral, 0, 0).assert_ok();
 
         const HOURS_2: u64 = 2;
         short.mock_after_hours(HOURS_2);
 
-        // Short more
-        short
-            .open(usdc(20000.), usdc(2000.0), leverage as u32 * 1000, &mfr)
-            .assert_unwrap();
-        assert_eq!(open_fee, expected_open_fee);
-        assert_eq!(collateral, expected_collateral);
-        assert_eq!(size, expected_size);
-        assert_eq!(borrow, expected_collateral * leverage);
+        let expected_fee =
+            borrow * (mfr.borrow_fee_rate as u64) * HOURS_2 / BORROW_FEE_RATE_BASE as u64;
+        let fee = short.settle_borrow_fee(&mfr).assert_unwrap();
+        assert_eq!(fee, expected_fee);
+        assert_eq!(short.collateral, collateral - expected_fee);
+        assert_eq!(short.cumulative_fund_fee, 0);
+        assert_eq!(short.size, size);
+        assert_eq!(short.borrowed_amount, borrow);
 
-        assert_eq!(short.size, expected_size * 2);
-        assert_eq!(short.average_price, usdc(20000.));
-        assert_eq!(short.collateral, expected_collateral * 2);
-        assert_eq!(short.borrowed_amount, expected_collateral * leverage * 2);
-        assert_eq!(short.closing_size, 0);
+        dex.collect_borrow_fee(0, false, false, fee).assert_ok();
+        dex.assert_usdc_collateral(collateral - expected_fee);
+        dex.assert_usdc_fee(expected_fee);
+        assert_eq!(
+            dex.markets[0].global_short.collateral,
+            collateral - expected_fee
+        );
 
-        let expected_fund_fee =
-            expected_collateral * leverage * (mfr.borrow_fee_rate as u64) * HOURS_2
-                / BORROW_FEE_RATE_BASE as u64;
-        assert_eq!(short.cumulative_fund_fee, expected_fund_fee);
+        // Nothing accrued since the last settlement
+        assert_eq!(short.settle_borrow_fee(&mfr).assert_unwrap(), 0);
+
+        // Frequent settlements of a small borrow, about 0.054 accrued per second, keep
+        // the rounded off fee accruing instead of dropping it
+        let last_fill_time = short.last_fill_time;
+        short.borrowed_amount = borrow / 2000;
+        let mut settled = 0;
+        for _ in 0..10 {
+            short.last_fill_time -= 55;
+            settled += short.settle_borrow_fee(&mfr).assert_unwrap();
+        }
+        let expected_settled = short.borrowed_amount * (mfr.borrow_fee_rate as u64) * 550
+            / BORROW_FEE_RATE_BASE as u64
+            / 3600;
+        assert!(settled <= expected_settled && settled + 1 >= expected_settled);
+        assert!(short.last_fill_time <= last_fill_time);
+        short.borrowed_amount = borrow;
+        short.collateral += settled;
+        short.last_fill_time = last_fill_time;
+
+        // The part the collateral can't cover stays accrued
+        short.collateral = expected_fee / 2;
+        short.mock_after_hours(HOURS_2);
+        let fee = short.settle_borrow_fee(&mfr).assert_unwrap();
+        assert_eq!(fee, expected_fee / 2);
+        assert_eq!(short.collateral, 0);
+        assert_eq!(short.cumulative_fund_fee, expected_fee - expected_fee / 2);
     }
 
     #[test]
     fn test_close_short_position_with_profit() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
 
         let mut short = Position::new(false).assert_unwrap();
         let leverage = 10u64;
@@ -1735,7 +2901,7 @@ mod test {
     fn test_close_short_position_with_loss() {
         let mut dex = Dex::default();
         dex.mock_dex();
-        let mfr = dex.markets[0].get_fee_rates(20);
+        let mfr = dex.markets[0].get_fee_rates(20, 0);
 
         let mut short = Position::new(false).assert_unwrap();
         let leverage = 10u64;
@@ -2166,9 +3332,12 @@ mod test {
         let oracles: Vec<AccountInfo> = vec![btc_oracle, usdc_oracle, sol_oracle, btc_oracle2];
         let price_feed = PriceFeed::default();
 
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        assert_eq!(dex.cached_aum, 0);
+
         // Add 1000 SOL, add liquidity fee rate = 0.1%
         let (vlp_amount, fee) = dex
-            .add_liquidity(3, sol(1000.), true, &oracles, &price_feed)
+            .add_liquidity(3, sol(1000.), true, &oracles[2], &price_feed)
             .assert_unwrap();
 
         // vlp_amount = 19980_00000000
@@ -2179,7 +3348,7 @@ mod test {
 
         // Add another 1000 SOL
         let (vlp_amount, fee) = dex
-            .add_liquidity(3, sol(1000.), true, &oracles, &price_feed)
+            .add_liquidity(3, sol(1000.), true, &oracles[2], &price_feed)
             .assert_unwrap();
 
         // vlp_amount = 19980_00000000
@@ -2193,7 +3362,7 @@ mod test {
 
         // Remove liquidity, remove liquidity fee rate = 0.1%
         let (withdraw, fee) = dex
-            .remove_liquidity(3, vlp(1000.), &oracles, &price_feed)
+            .remove_liquidity(3, vlp(1000.), &oracles[2], &price_feed)
             .assert_unwrap();
 
         assert_eq!(fee, sol(0.05));
@@ -2207,7 +3376,7 @@ mod test {
 
         // Add 1 BTC
         let (vlp_amount, fee) = dex
-            .add_liquidity(1, btc(1.), true, &oracles, &price_feed)
+            .add_liquidity(1, btc(1.), true, &oracles[0], &price_feed)
             .assert_unwrap();
         assert_eq!(vlp_amount, vlp((1.0 - 0.001) * 20000.0));
         assert_eq!(fee, btc(0.001));
@@ -2215,10 +3384,464 @@ mod test {
 
         // Add 10000 usdc
         let (vlp_amount, fee) = dex
-            .add_liquidity(2, usdc(10000.), true, &oracles, &price_feed)
+            .add_liquidity(2, usdc(10000.), true, &oracles[1], &price_feed)
             .assert_unwrap();
         assert_eq!(vlp_amount, vlp((10000.0 - 10.) * 1.0));
         assert_eq!(fee, usdc(10.));
         dex.vlp_pool.increase_staking(vlp_amount).assert_ok();
+
+        // Wrong oracle
+        dex.add_liquidity(2, usdc(10000.), true, &oracles[0], &price_feed)
+            .assert_err();
+    }
+
+    #[test]
+    fn test_cached_aum() {
+        let bump = Bump::new();
+        let btc_oracle = gen_account(1024, &bump);
+        let usdc_oracle = gen_account(1024, &bump);
+
+        let mut dex = Dex::default();
+        dex.add_asset(BTC_DECIMALS, btc_oracle.key());
+        dex.add_asset(USDC_DECIMALS, usdc_oracle.key());
+        dex.add_market(BTC_DECIMALS, 0, btc_oracle.key());
+        dex.mock_vlp_pool(VLP_DECIMALS, 1);
+
+        set_mock_price(&btc_oracle, usdc(20000.)).assert_ok();
+        set_mock_price(&usdc_oracle, usdc(1.)).assert_ok();
+
+        let oracles: Vec<AccountInfo> = vec![btc_oracle.clone(), usdc_oracle, btc_oracle];
+        let price_feed = PriceFeed::default();
+
+        // Never refreshed
+        dex.add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_err();
+
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+
+        // Add 10000 usdc, add liquidity fee rate = 0.1%
+        let (vlp_amount, _) = dex
+            .add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_unwrap();
+        dex.vlp_pool.increase_staking(vlp_amount).assert_ok();
+        assert_eq!(dex.cached_aum, usdc(9990.) as i64);
+
+        // Fee is collected and counted in AUM on refresh
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        assert_eq!(dex.cached_aum, usdc(10000.) as i64);
+        dex.assert_asset_fee(1, 0);
+
+        // Price moves are only picked up by refresh
+        dex.add_liquidity(0, btc(1.), true, &oracles[0], &price_feed)
+            .assert_ok();
+        assert_eq!(dex.cached_aum, usdc(10000. + 19980.) as i64);
+
+        set_mock_price(&oracles[0], usdc(22000.)).assert_ok();
+        assert_eq!(dex.cached_aum, usdc(10000. + 19980.) as i64);
+
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        assert_eq!(dex.cached_aum, usdc(10000. + 22000.) as i64);
+
+        // Stale
+        dex.cached_aum_timestamp -= MAX_AUM_AGE + 1;
+        dex.remove_liquidity(1, vlp(100.), &oracles[1], &price_feed)
+            .assert_err();
+
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        dex.remove_liquidity(1, vlp(100.), &oracles[1], &price_feed)
+            .assert_ok();
+
+        // Fees are collected by staking updates at the refreshed prices
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        let aum = dex.cached_aum;
+        dex.assets[0].fee_amount = btc(0.01);
+        dex.update_staking_pool(true).assert_ok();
+        dex.assert_asset_fee(0, 0);
+        assert_eq!(dex.cached_aum, aum + usdc(220.) as i64);
+
+        // Pool PnL moves are applied to the cache, which stays fresh
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        let aum = dex.cached_aum;
+        dex.increase_global_position(0, true, usdc(21000.), btc(1.), btc(0.1))
+            .assert_ok();
+        assert_eq!(dex.cached_aum, aum - usdc(1000.) as i64);
+        dex.add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_ok();
+
+        // Only the liquidity fee is added by the refresh
+        let aum = dex.cached_aum;
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        assert_eq!(dex.cached_aum, aum + usdc(10.) as i64);
+        let aum = dex.cached_aum;
+
+        dex.decrease_global_position(0, true, btc(1.), btc(0.1))
+            .assert_ok();
+        assert_eq!(dex.cached_aum, aum + usdc(1000.) as i64);
+
+        // Staking updates defer the fees once the cache is stale
+        dex.cached_aum_timestamp -= MAX_AUM_AGE + 1;
+        dex.assets[0].fee_amount = btc(0.01);
+        dex.update_staking_pool(true).assert_ok();
+        dex.assert_asset_fee(0, btc(0.01));
+        dex.add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_err();
+
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        dex.assert_asset_fee(0, 0);
+        dex.add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_ok();
+    }
+
+    #[test]
+    fn test_protocol_fee_share() {
+        let bump = Bump::new();
+        let btc_oracle = gen_account(1024, &bump);
+        let usdc_oracle = gen_account(1024, &bump);
+
+        let mut dex = Dex::default();
+        dex.add_asset(BTC_DECIMALS, btc_oracle.key());
+        dex.add_asset(USDC_DECIMALS, usdc_oracle.key());
+        dex.mock_vlp_pool(VLP_DECIMALS, 1);
+        dex.set_protocol_fee_percentage(MAX_PROTOCOL_FEE_PERCENTAGE + 1)
+            .assert_err();
+        dex.set_protocol_fee_percentage(20).assert_ok();
+
+        set_mock_price(&btc_oracle, usdc(20000.)).assert_ok();
+        set_mock_price(&usdc_oracle, usdc(1.)).assert_ok();
+
+        let oracles: Vec<AccountInfo> = vec![btc_oracle, usdc_oracle];
+        let price_feed = PriceFeed::default();
+
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        dex.withdraw_protocol_fees(1).assert_err();
+
+        // Add 10000 usdc, add liquidity fee rate = 0.1%
+        let (vlp_amount, fee) = dex
+            .add_liquidity(1, usdc(10000.), true, &oracles[1], &price_feed)
+            .assert_unwrap();
+        dex.vlp_pool.increase_staking(vlp_amount).assert_ok();
+        assert_eq!(fee, usdc(10.));
+
+        // 20% of the fee goes to the treasury, the rest is counted in AUM
+        dex.refresh_aum(&oracles, &price_feed).assert_ok();
+        assert_eq!(dex.cached_aum, usdc(9998.) as i64);
+        assert_eq!(dex.assets[1].protocol_fee_amount, usdc(2.));
+        dex.assert_usdc_liquidity(usdc(9998.));
+        dex.assert_asset_fee(1, 0);
+
+        // Asset can not be removed while the treasury holds a balance
+        dex.assets[1].liquidity_amount = 0;
+        dex.assets[1].trading_state = TradingState::Paused as u8;
+        dex.remove_asset(1).assert_err();
+
+        assert_eq!(dex.withdraw_protocol_fees(1).assert_unwrap(), usdc(2.));
+        assert_eq!(dex.assets[1].protocol_fee_amount, 0);
+        dex.withdraw_protocol_fees(1).assert_err();
+    }
+
+    #[test]
+    fn test_take_referral_fee() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        dex.set_referral_rates(50, 1).assert_err();
+        dex.set_referral_rates(10, 20).assert_ok();
+
+        dex.mock_usdc_liquidity(usdc(10000.));
+        dex.borrow_fund(0, false, usdc(100.), usdc(900.), usdc(2.))
+            .assert_ok();
+
+        let (discount, rebate) = dex.take_referral_fee(1, usdc(2.), true).assert_unwrap();
+        assert_eq!(discount, usdc(0.2));
+        assert_eq!(rebate, usdc(0.4));
+        dex.assert_usdc_fee(usdc(1.4));
+
+        // The rebate stays in the pool if the referrer is gone
+        let (discount, rebate) = dex.take_referral_fee(1, usdc(1.), false).assert_unwrap();
+        assert_eq!(discount, usdc(0.1));
+        assert_eq!(rebate, 0);
+        dex.assert_usdc_fee(usdc(1.3));
+
+        // Can not take more than collected
+        dex.take_referral_fee(1, usdc(5.), true).assert_err();
+        dex.assert_usdc_fee(usdc(1.3));
+    }
+
+    #[test]
+    fn test_fee_tiers() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+
+        // Staked amounts must ascend
+        dex.set_fee_tiers([1000, 500, 0, 0], [10, 20, 0, 0])
+            .assert_err();
+        // Discounts must ascend
+        dex.set_fee_tiers([1000, 5000, 0, 0], [20, 10, 0, 0])
+            .assert_err();
+        // Discount too large
+        dex.set_fee_tiers([1000, 5000, 0, 0], [10, 60, 0, 0])
+            .assert_err();
+
+        dex.set_fee_tiers([1000, 5000, 10000, 0], [10, 20, 30, 0])
+            .assert_ok();
+
+        assert_eq!(dex.fee_discount(999), 0);
+        assert_eq!(dex.fee_discount(1000), 10);
+        assert_eq!(dex.fee_discount(9999), 20);
+        assert_eq!(dex.fee_discount(u64::MAX), 30);
+
+        let mfr = dex.markets[0].get_fee_rates(10, dex.fee_discount(5000));
+        assert_eq!(mfr.open_fee_rate, 16);
+        assert_eq!(mfr.close_fee_rate, 16);
+        assert_eq!(mfr.liquidate_fee_rate, 50);
+        assert_eq!(mfr.borrow_fee_rate, 10);
+    }
+
+    #[test]
+    fn test_es_vdx_emission_halving() {
+        let mut dex = Dex::default();
+        dex.es_vdx_per_second = 1000;
+        dex.vdx_total_supply = u64::MAX;
+        dex.emission_start_time = 100;
+
+        // No halving
+        assert_eq!(dex.es_vdx_emission(100, 200).assert_unwrap(), 100_000);
+
+        dex.emission_halving_interval = 50;
+        assert_eq!(dex.es_vdx_emission_rate(149), 1000);
+        assert_eq!(dex.es_vdx_emission_rate(150), 500);
+        assert_eq!(dex.es_vdx_emission_rate(200), 250);
+
+        // Split at each halving boundary
+        assert_eq!(
+            dex.es_vdx_emission(120, 210).assert_unwrap(),
+            30 * 1000 + 50 * 500 + 10 * 250
+        );
+
+        // Rate reaches zero eventually
+        assert_eq!(dex.es_vdx_emission_rate(100 + 50 * 64), 0);
+        assert_eq!(
+            dex.es_vdx_emission(100 + 50 * 64, 100 + 50 * 65)
+                .assert_unwrap(),
+            0
+        );
+
+        // Capped by total supply
+        dex.vdx_total_supply = 10_000;
+        dex.mint_es_vdx(100, 150).assert_ok();
+        assert_eq!(dex.vdx_supply, 10_000);
+        dex.mint_es_vdx(150, 200).assert_ok();
+        assert_eq!(dex.vdx_supply, 10_000);
+    }
+
+    #[test]
+    fn test_emission_fallback() {
+        let mut dex = Dex::default();
+
+        // Never configured, the original constants apply
+        assert_eq!(dex.get_es_vdx_per_second(), ES_VDX_PER_SECOND);
+        assert_eq!(dex.get_vdx_total_supply(), VDX_TOTAL_SUPPLY);
+        assert_eq!(
+            dex.get_es_vdx_percentage_for_vdx_pool(),
+            ES_VDX_PERCENTAGE_FOR_VDX_POOL as u16
+        );
+        assert_eq!(
+            dex.get_reward_percentage_for_vdx_pool(),
+            REWARD_PERCENTAGE_FOR_VDX_POOL as u16
+        );
+        assert_eq!(
+            dex.es_vdx_emission(0, 10).assert_unwrap(),
+            ES_VDX_PER_SECOND * 10
+        );
+
+        let mut params = EmissionParams {
+            es_vdx_per_second: 0,
+            vdx_total_supply: 0,
+            emission_halving_interval: 0,
+            es_vdx_percentage_for_vdx_pool: 0,
+            reward_percentage_for_vdx_pool: 0,
+        };
+
+        params.reward_percentage_for_vdx_pool = 101;
+        dex.set_emission(&params).assert_err();
+        params.reward_percentage_for_vdx_pool = 0;
+
+        // Zero values set on purpose are kept
+        dex.set_emission(&params).assert_ok();
+        assert_eq!(dex.get_es_vdx_per_second(), 0);
+        assert_eq!(dex.get_vdx_total_supply(), 0);
+        assert_eq!(dex.get_es_vdx_percentage_for_vdx_pool(), 0);
+        assert_eq!(dex.get_reward_percentage_for_vdx_pool(), 0);
+
+        dex.vdx_supply = 100;
+        dex.set_emission(&params).assert_err();
+    }
+
+    #[test]
+    fn test_set_market_params() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+
+        let mi = dex.market_as_mut(0).assert_unwrap();
+        let old = mi.params();
+
+        let mut new = old;
+        new.max_leverage = 50 * LEVERAGE_POW_DECIMALS;
+        new.liquidate_threshold = 5;
+        mi.set_params(&new).assert_ok();
+        assert_eq!(mi.max_leverage, 50 * LEVERAGE_POW_DECIMALS);
+        assert_eq!(mi.liquidate_threshold, 5);
+        assert_eq!(mi.open_fee_rate, old.open_fee_rate);
+
+        let mut bad = new;
+        bad.max_leverage = (MAX_LEVERAGE + 1) * LEVERAGE_POW_DECIMALS;
+        mi.set_params(&bad).assert_err();
+
+        bad = new;
+        bad.liquidate_threshold = 0;
+        mi.set_params(&bad).assert_err();
+
+        bad = new;
+        bad.open_fee_rate = MAX_MARKET_FEE_RATE + 1;
+        mi.set_params(&bad).assert_err();
+
+        bad = new;
+        bad.oracle_source = 0xff;
+        mi.set_params(&bad).assert_err();
+
+        // Rejected updates leave the market untouched
+        assert_eq!(mi.max_leverage, 50 * LEVERAGE_POW_DECIMALS);
+
+        dex.market_as_mut(1).assert_err();
+    }
+
+    #[test]
+    fn test_set_asset_params() {
+        let bump = Bump::new();
+        let oracle = gen_account(1024, &bump);
+
+        let mut dex = Dex::default();
+        dex.add_asset(BTC_DECIMALS, oracle.key());
+
+        let ai = dex.asset_as_mut(0).assert_unwrap();
+        let old = ai.params();
+
+        // Migrate to another oracle source
+        let mut new = old;
+        new.oracle = Pubkey::new_unique().to_bytes();
+        new.oracle_source = OracleSource::Pyth as u8;
+        new.swap_fee_rate = 30;
+        ai.set_params(&new).assert_ok();
+        assert_eq!(ai.oracle, Pubkey::new_from_array(new.oracle));
+        assert_eq!(ai.oracle_source, OracleSource::Pyth as u8);
+        assert_eq!(ai.swap_fee_rate, 30);
+        assert_eq!(ai.borrow_fee_rate, old.borrow_fee_rate);
+
+        let mut bad = new;
+        bad.borrow_fee_rate = MAX_ASSET_FEE_RATE + 1;
+        ai.set_params(&bad).assert_err();
+
+        bad = new;
+        bad.target_weight = MAX_TARGET_WEIGHT + 1;
+        ai.set_params(&bad).assert_err();
+
+        bad = new;
+        bad.oracle_source = 0xff;
+        ai.set_params(&bad).assert_err();
+
+        assert_eq!(ai.params().swap_fee_rate, 30);
+    }
+
+    #[test]
+    fn test_trading_state() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+
+        dex.check_market_trading(0, true).assert_ok();
+        dex.check_asset_trading(0, true).assert_ok();
+
+        dex.markets[0].trading_state = TradingState::ReduceOnly as u8;
+        dex.check_market_trading(0, true).assert_err();
+        dex.check_market_trading(0, false).assert_ok();
+
+        dex.assets[1].trading_state = TradingState::Paused as u8;
+        dex.check_asset_trading(1, true).assert_err();
+        dex.check_asset_trading(1, false).assert_err();
+        dex.check_asset_trading(0, true).assert_ok();
+
+        // Global pause stops everything
+        dex.markets[0].trading_state = TradingState::Active as u8;
+        dex.trading_state = TradingState::Paused as u8;
+        dex.check_market_trading(0, false).assert_err();
+        dex.check_asset_trading(0, false).assert_err();
+
+        dex.trading_state = 0xff;
+        dex.check_market_trading(0, false).assert_err();
+    }
+
+    #[test]
+    fn test_delist_market_and_asset() {
+        let mut dex = Dex::default();
+        dex.mock_dex();
+
+        let price = usdc(20000.);
+
+        // Must be reduce only first
+        dex.delist_market(0, price, price, 0).assert_err();
+        dex.remove_market(0).assert_err();
+
+        let reduce_only = TradingState::ReduceOnly as u8;
+        assert!(dex.set_market_trading_state(0, reduce_only).assert_unwrap() == 0);
+        let since = dex.markets[0].reduce_only_since;
+        assert!(since > 0);
+
+        // Switching again keeps the time
+        dex.markets[0].reduce_only_since -= MIN_REDUCE_ONLY_DURATION;
+        dex.set_market_trading_state(0, reduce_only).assert_ok();
+        assert_eq!(
+            dex.markets[0].reduce_only_since,
+            since - MIN_REDUCE_ONLY_DURATION
+        );
+
+        // Not long enough for the timelock delay
+        dex.delist_market(0, price, price, 2 * MIN_REDUCE_ONLY_DURATION)
+            .assert_err();
+
+        // Settle price bounded by the oracle price
+        dex.delist_market(0, 0, price, 0).assert_err();
+        dex.delist_market(0, usdc(21001.), price, 0).assert_err();
+        dex.delist_market(0, usdc(18999.), price, 0).assert_err();
+
+        dex.delist_market(0, usdc(21000.), price, 0).assert_ok();
+        assert_eq!(dex.markets[0].settle_price, usdc(21000.));
+        assert!(dex.markets[0].trading_state == TradingState::Paused as u8);
+
+        // Delisted only once
+        dex.set_market_trading_state(0, reduce_only).assert_ok();
+        dex.markets[0].reduce_only_since -= MIN_REDUCE_ONLY_DURATION;
+        dex.delist_market(0, price, price, 0).assert_err();
+        dex.markets[0].trading_state = TradingState::Paused as u8;
+
+        // Open positions left
+        dex.markets[0].global_long.size = btc(1.);
+        dex.remove_market(0).assert_err();
+
+        dex.markets[0].global_long.size = 0;
+        dex.remove_market(0).assert_ok();
+        assert!(!dex.markets[0].valid);
+        dex.market_as_mut(0).assert_err();
+
+        // BTC is no longer used by any market
+        dex.remove_asset(0).assert_err();
+        dex.assets[0].trading_state = TradingState::ReduceOnly as u8;
+        dex.assets[0].liquidity_amount = btc(1.);
+        dex.remove_asset(0).assert_err();
+
+        dex.assets[0].liquidity_amount = 0;
+        dex.remove_asset(0).assert_ok();
+        assert!(!dex.assets[0].valid);
+
+        // USDC can never be removed
+        dex.assets[1].trading_state = TradingState::Paused as u8;
+        dex.remove_asset(1).assert_err();
     }
 }
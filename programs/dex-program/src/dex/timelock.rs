@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use num_enum::TryFromPrimitive;
+
+use crate::{
+    errors::{DexError, DexResult},
+    utils::{time::get_timestamp, MAX_TIMELOCK_ACTIONS, MAX_TIMELOCK_DELAY},
+};
+
+use super::Dex;
+
+#[derive(Copy, Clone, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum TimelockKind {
+    Market = 0,
+    Asset = 1,
+    Delay = 2,
+    Emission = 3,
+    ProtocolFeePercentage = 4,
+    ReferralRates = 5,
+    FeeTiers = 6,
+    Delegate = 7,
+}
+
+#[repr(u8)]
+pub enum TimelockStatus {
+    Queued = 0,
+    Executed = 1,
+    Cancelled = 2,
+}
+
+#[account(zero_copy)]
+pub struct Timelock {
+    pub magic: u64,
+    pub dex: Pubkey,
+    pub delay: i64,
+    pub next_id: u64,
+    pub actions: [TimelockAction; MAX_TIMELOCK_ACTIONS],
+}
+
+#[zero_copy]
+pub struct TimelockAction {
+    pub id: u64,
+    pub eta: i64,
+    pub data: [u8; 64], // Borsh encoded parameters
+    pub kind: u8,
+    pub index: u8,
+    pub valid: bool,
+    pub padding: [u8; 5],
+}
+
+impl Timelock {
+    pub fn set_delay(&mut self, delay: i64) -> DexResult {
+        require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&delay),
+            DexError::InvalidTimelockAction
+        );
+        self.delay = delay;
+
+        Ok(())
+    }
+
+    // Actions carry the whole parameter set of their target, so only one may be
+    // pending per target or executing them in turn would revert earlier changes.
+    pub fn queue(&mut self, kind: TimelockKind, index: u8, data: &[u8]) -> DexResult<(u64, i64)> {
+        require!(data.len() <= 64, DexError::InvalidTimelockAction);
+        require!(
+            !self
+                .actions
+                .iter()
+                .any(|a| a.valid && a.kind == kind as u8 && a.index == index),
+            DexError::TimelockActionPending
+        );
+
+        let eta = get_timestamp()? + self.delay;
+        let id = self.next_id;
+
+        let action = self
+            .actions
+            .iter_mut()
+            .find(|a| !a.valid)
+            .ok_or(DexError::TimelockQueueFull)?;
+
+        action.id = id;
+        action.eta = eta;
+        action.data = [0; 64];
+        action.data[..data.len()].copy_from_slice(data);
+        action.kind = kind as u8;
+        action.index = index;
+        action.valid = true;
+
+        self.next_id += 1;
+
+        Ok((id, eta))
+    }
+
+    fn remove(&mut self, id: u64, ready: bool) -> DexResult<TimelockAction> {
+        let now = get_timestamp()?;
+        let action = self
+            .actions
+            .iter_mut()
+            .find(|a| a.valid && a.id == id)
+            .ok_or(DexError::InvalidTimelockAction)?;
+
+        require!(!ready || action.eta <= now, DexError::TimelockNotReady);
+        action.valid = false;
+
+        Ok(*action)
+    }
+
+    pub fn take_ready(&mut self, id: u64) -> DexResult<TimelockAction> {
+        self.remove(id, true)
+    }
+
+    pub fn cancel(&mut self, id: u64) -> DexResult<TimelockAction> {
+        self.remove(id, false)
+    }
+}
+
+// Once a timelock is attached to the dex, parameter changes must be queued. The
+// timelock account is expected as the first remaining account.
+pub fn timelock_of<'info>(
+    dex: &Dex,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> DexResult<Option<AccountLoader<'info, Timelock>>> {
+    if dex.timelock == Pubkey::default() {
+        return Ok(None);
+    }
+
+    let account = remaining_accounts
+        .first()
+        .ok_or(DexError::TimelockRequired)?;
+    require_keys_eq!(account.key(), dex.timelock, DexError::InvalidTimelock);
+
+    Ok(Some(AccountLoader::try_from(account)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test::*;
+
+    impl Default for Timelock {
+        fn default() -> Timelock {
+            unsafe { std::mem::zeroed() }
+        }
+    }
+
+    #[test]
+    fn test_timelock_queue() {
+        let mut timelock = Timelock::default();
+        timelock.set_delay(MAX_TIMELOCK_DELAY + 1).assert_err();
+        timelock.set_delay(3600).assert_ok();
+
+        let now = get_timestamp().assert_unwrap();
+        let (id, eta) = timelock
+            .queue(TimelockKind::Delay, 0, &7200i64.to_le_bytes())
+            .assert_unwrap();
+        assert_eq!(id, 0);
+        assert!(eta >= now + 3600);
+
+        // Not ready yet
+        timelock.take_ready(id).assert_err();
+
+        timelock.actions[0].eta = now;
+        let action = timelock.take_ready(id).assert_unwrap();
+        assert_eq!(action.kind, TimelockKind::Delay as u8);
+        assert_eq!(action.data[..8], 7200i64.to_le_bytes());
+
+        // Executed only once
+        timelock.take_ready(id).assert_err();
+
+        for i in 0..MAX_TIMELOCK_ACTIONS {
+            timelock
+                .queue(TimelockKind::Market, i as u8, &[])
+                .assert_ok();
+        }
+        timelock.queue(TimelockKind::Asset, 0, &[]).assert_err();
+
+        timelock.cancel(MAX_TIMELOCK_ACTIONS as u64).assert_ok();
+        timelock.cancel(MAX_TIMELOCK_ACTIONS as u64).assert_err();
+        timelock.queue(TimelockKind::Asset, 2, &[]).assert_ok();
+    }
+
+    #[test]
+    fn test_timelock_one_pending_per_target() {
+        let mut timelock = Timelock::default();
+        timelock.set_delay(3600).assert_ok();
+
+        let (id, _) = timelock.queue(TimelockKind::Market, 1, &[]).assert_unwrap();
+        timelock.queue(TimelockKind::Market, 1, &[]).assert_err();
+
+        // Other targets are unaffected
+        timelock.queue(TimelockKind::Market, 2, &[]).assert_ok();
+        timelock.queue(TimelockKind::Asset, 1, &[]).assert_ok();
+
+        timelock.cancel(id).assert_ok();
+        timelock.queue(TimelockKind::Market, 1, &[]).assert_ok();
+    }
+}
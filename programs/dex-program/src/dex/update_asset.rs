@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{
+        event::AppendEvent, get_oracle_price, state::*, timelock_of, TimelockKind, TimelockStatus,
+    },
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct UpdateAsset<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub oracle: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Pass the MAX value of a field to leave it unchanged, the oracle is always
+// taken from the accounts (pass the current one to keep it).
+//
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<UpdateAsset>,
+    asset: u8,
+    swap_fee_rate: u16,
+    borrow_fee_rate: u16,
+    add_liquidity_fee_rate: u16,
+    remove_liquidity_fee_rate: u16,
+    flash_loan_fee_rate: u16,
+    target_weight: u16,
+    oracle_source: u8,
+) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let ai = dex.asset_as_mut(asset)?;
+    let old = ai.params();
+    let mut new = old;
+
+    new.oracle = ctx.accounts.oracle.key().to_bytes();
+    if swap_fee_rate != u16::MAX {
+        new.swap_fee_rate = swap_fee_rate;
+    }
+    if borrow_fee_rate != u16::MAX {
+        new.borrow_fee_rate = borrow_fee_rate;
+    }
+    if add_liquidity_fee_rate != u16::MAX {
+        new.add_liquidity_fee_rate = add_liquidity_fee_rate;
+    }
+    if remove_liquidity_fee_rate != u16::MAX {
+        new.remove_liquidity_fee_rate = remove_liquidity_fee_rate;
+    }
+    if flash_loan_fee_rate != u16::MAX {
+        new.flash_loan_fee_rate = flash_loan_fee_rate;
+    }
+    if target_weight != u16::MAX {
+        new.target_weight = target_weight;
+    }
+    if oracle_source != u8::MAX {
+        new.oracle_source = oracle_source;
+    }
+
+    // Make sure the asset can still be priced
+    get_oracle_price(new.oracle_source, &ctx.accounts.oracle)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    match timelock {
+        Some(timelock) => {
+            // Validate now, apply once the delay has passed
+            let mut pending = *ai;
+            pending.set_params(&new)?;
+
+            let (id, eta) =
+                timelock
+                    .load_mut()?
+                    .queue(TimelockKind::Asset, asset, &new.try_to_vec()?)?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::Asset as u8,
+                asset,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            ai.set_params(&new)?;
+            event_queue.update_asset(asset, old, new)
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{
+        event::AppendEvent, get_oracle_price, state::*, timelock_of, TimelockKind, TimelockStatus,
+    },
+    errors::{DexError, DexResult},
+    utils::LEVERAGE_POW_DECIMALS,
+};
+
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub oracle: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Pass the MAX value of a field to leave it unchanged, the oracle is always
+// taken from the accounts (pass the current one to keep it).
+//
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<UpdateMarket>,
+    market: u8,
+    minimum_collateral: u64,
+    charge_borrow_fee_interval: u64,
+    open_fee_rate: u16,
+    close_fee_rate: u16,
+    liquidate_fee_rate: u16,
+    liquidate_threshold: u16,
+    max_leverage: u32,
+    oracle_source: u8,
+) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.delegate == ctx.accounts.authority.key()
+            || dex.authority == ctx.accounts.authority.key(),
+        DexError::InvalidAdminOrDelegate
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let mi = dex.market_as_mut(market)?;
+    let old = mi.params();
+    let mut new = old;
+
+    new.oracle = ctx.accounts.oracle.key().to_bytes();
+    if minimum_collateral != u64::MAX {
+        new.minimum_collateral = minimum_collateral;
+    }
+    if charge_borrow_fee_interval != u64::MAX {
+        new.charge_borrow_fee_interval = charge_borrow_fee_interval;
+    }
+    if open_fee_rate != u16::MAX {
+        new.open_fee_rate = open_fee_rate;
+    }
+    if close_fee_rate != u16::MAX {
+        new.close_fee_rate = close_fee_rate;
+    }
+    if liquidate_fee_rate != u16::MAX {
+        new.liquidate_fee_rate = liquidate_fee_rate;
+    }
+    if liquidate_threshold != u16::MAX {
+        new.liquidate_threshold = liquidate_threshold;
+    }
+    if max_leverage != u32::MAX {
+        new.max_leverage = max_leverage
+            .checked_mul(LEVERAGE_POW_DECIMALS)
+            .ok_or(DexError::InvalidMarketParams)?;
+    }
+    if oracle_source != u8::MAX {
+        new.oracle_source = oracle_source;
+    }
+
+    // Make sure the market can still be priced
+    get_oracle_price(new.oracle_source, &ctx.accounts.oracle)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    match timelock {
+        Some(timelock) => {
+            // Validate now, apply once the delay has passed
+            let mut pending = *mi;
+            pending.set_params(&new)?;
+
+            let (id, eta) =
+                timelock
+                    .load_mut()?
+                    .queue(TimelockKind::Market, market, &new.try_to_vec()?)?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::Market as u8,
+                market,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            mi.set_params(&new)?;
+            event_queue.update_market(market, old, new)
+        }
+    }
+}
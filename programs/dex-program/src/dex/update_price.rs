@@ -8,7 +8,7 @@ use crate::{
 
 #[derive(Accounts)]
 pub struct UpdatePrice<'info> {
-    #[account(owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub dex: AccountLoader<'info, Dex>,
 
     /// CHECK
@@ -18,8 +18,15 @@ pub struct UpdatePrice<'info> {
     pub authority: Signer<'info>,
 }
 
+// Remaining accounts layout (optional, refreshes the cached AUM if present):
+// dex.assets.map({
+//   asset index price oracle account
+// })
+// dex.markets.map({
+//    market index price oracle account
+// })
 pub fn handler(ctx: Context<UpdatePrice>, prices: [u64; MAX_ASSET_COUNT]) -> DexResult {
-    let dex = &ctx.accounts.dex.load()?;
+    let dex = &mut ctx.accounts.dex.load_mut()?;
     let price_feed = &mut ctx.accounts.price_feed.load_mut()?;
 
     require!(
@@ -61,5 +68,9 @@ pub fn handler(ctx: Context<UpdatePrice>, prices: [u64; MAX_ASSET_COUNT]) -> Dex
 
     price_feed.last_update_time = now;
 
+    if !ctx.remaining_accounts.is_empty() {
+        dex.refresh_aum(ctx.remaining_accounts, price_feed)?;
+    }
+
     Ok(())
 }
@@ -32,7 +32,7 @@ pub struct DiBuy<'info> {
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -65,6 +65,8 @@ pub fn handler(ctx: Context<DiBuy>, id: u64, premium_rate: u16, size: u64) -> De
     let option = di.borrow().get_option(id)?;
     let base_ai = dex.asset_as_ref(option.base_asset_index)?;
     let quote_ai = dex.asset_as_ref(option.quote_asset_index)?;
+    dex.check_asset_trading(option.base_asset_index, true)?;
+    dex.check_asset_trading(option.quote_asset_index, true)?;
 
     // Check size
     require!(
@@ -177,6 +179,11 @@ pub fn handler(ctx: Context<DiBuy>, id: u64, premium_rate: u16, size: u64) -> De
 
     // Create the option
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
     us.borrow_mut()
         .di_new_option(&option, size, borrow_base_funds, borrow_quote_funds)?;
 
@@ -24,7 +24,7 @@ pub struct DiSettle<'info> {
     pub user: AccountInfo<'info>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), user.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -163,6 +163,11 @@ pub fn handler(ctx: Context<DiSettle>, created: u64, force: bool, settle_price:
         Account::<TokenAccount>::try_from_unchecked(&ctx.accounts.user_mint_acc).ok();
 
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
     let (option_slot, option) = us.borrow().di_get_option(created, false)?;
 
     // Get settle price
@@ -26,7 +26,7 @@ pub struct DiWithdrawSettled<'info> {
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -90,8 +90,15 @@ pub fn handler(ctx: Context<DiWithdrawSettled>, created: u64) -> DexResult {
     let dex = &ctx.accounts.dex.load()?;
 
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
     let (asset_index, withdrawable) = us.borrow_mut().di_withdraw_from_settled_option(created)?;
 
+    dex.check_asset_trading(asset_index, false)?;
+
     let ai = dex.asset_as_ref(asset_index)?;
     validate_accounts(&ctx, ai)?;
 
@@ -286,6 +286,132 @@ pub enum DexError {
 
     #[msg("Invalid vdx decimals")]
     InvalidVDXDecimals,
+
+    #[msg("Flash loan in progress")]
+    FlashLoanInProgress,
+
+    #[msg("No flash loan in progress")]
+    NoFlashLoanInProgress,
+
+    #[msg("Missing flash loan end instruction")]
+    MissingFlashLoanEnd,
+
+    #[msg("Flash loan via CPI not allowed")]
+    FlashLoanCpiNotAllowed,
+
+    #[msg("Stale AUM")]
+    StaleAUM,
+
+    #[msg("Invalid lock tier")]
+    InvalidLockTier,
+
+    #[msg("Invalid emission parameters")]
+    InvalidEmissionParams,
+
+    #[msg("Insufficient vesting amount")]
+    InsufficientVestingAmount,
+
+    #[msg("Invalid market parameters")]
+    InvalidMarketParams,
+
+    #[msg("Invalid asset parameters")]
+    InvalidAssetParams,
+
+    #[msg("Invalid trading state")]
+    InvalidTradingState,
+
+    #[msg("Trading paused")]
+    TradingPaused,
+
+    #[msg("Reduce only")]
+    ReduceOnly,
+
+    #[msg("Market not delisted")]
+    NotDelisted,
+
+    #[msg("Delisting not completed")]
+    DelistNotCompleted,
+
+    #[msg("Invalid timelock")]
+    InvalidTimelock,
+
+    #[msg("Timelock queue full")]
+    TimelockQueueFull,
+
+    #[msg("Timelock action not ready")]
+    TimelockNotReady,
+
+    #[msg("Invalid timelock action")]
+    InvalidTimelockAction,
+
+    #[msg("Timelock required")]
+    TimelockRequired,
+
+    #[msg("Invalid pending authority")]
+    InvalidPendingAuthority,
+
+    #[msg("Invalid protocol fee percentage")]
+    InvalidProtocolFeePercentage,
+
+    #[msg("Insufficient protocol fee")]
+    InsufficientProtocolFee,
+
+    #[msg("Invalid referral code")]
+    InvalidReferralCode,
+
+    #[msg("Referral already linked")]
+    ReferralAlreadyLinked,
+
+    #[msg("Invalid referral rates")]
+    InvalidReferralRates,
+
+    #[msg("Invalid fee tiers")]
+    InvalidFeeTiers,
+
+    #[msg("Invalid user delegate")]
+    InvalidUserDelegate,
+
+    #[msg("Failed append order pool page")]
+    FailedAppendOrderPoolPage,
+
+    #[msg("Insufficient order pool page slots")]
+    InsufficientOrderPoolPageSlots,
+
+    #[msg("Invalid slot count")]
+    InvalidSlotCount,
+
+    #[msg("User state not empty")]
+    UserStateNotEmpty,
+
+    #[msg("Invalid sub-account")]
+    InvalidSubAccount,
+
+    #[msg("Margin mode locked by open positions")]
+    MarginModeLocked,
+
+    #[msg("Cross-margin account has open positions")]
+    CrossMarginPositionOpen,
+
+    #[msg("Collateral asset mismatch")]
+    CollateralAssetMismatch,
+
+    #[msg("Insufficient swap output")]
+    InsufficientSwapOutput,
+
+    #[msg("Lock top-up would extend the expiry")]
+    LockExpiryExtended,
+
+    #[msg("Timelock action already pending")]
+    TimelockActionPending,
+
+    #[msg("Limit bids can't open stable collateral longs")]
+    StableCollateralBid,
+
+    #[msg("Settle price too far from the oracle price")]
+    InvalidSettlePrice,
+
+    #[msg("Market not reduce only for long enough")]
+    ReduceOnlyTooShort,
 }
 
 pub type DexResult<T = ()> = Result<T>;
@@ -124,8 +124,9 @@ pub mod dex_program {
         long: bool,
         amount: u64,
         leverage: u32,
+        stable_collateral: bool,
     ) -> DexResult {
-        position::open::handler(ctx, market, long, amount, leverage)
+        position::open::handler(ctx, market, long, amount, leverage, stable_collateral)
     }
 
     pub fn close_position(
@@ -133,8 +134,10 @@ pub mod dex_program {
         market: u8,
         long: bool,
         size: u64,
+        out_asset: u8,
+        minimum_out: u64,
     ) -> DexResult {
-        position::close::handler(ctx, market, long, size)
+        position::close::handler(ctx, market, long, size, out_asset, minimum_out)
     }
 
     pub fn liquidate_position(
@@ -166,8 +169,10 @@ pub mod dex_program {
         long: bool,
         price: u64,
         size: u64,
+        out_asset: u8,
+        minimum_out: u64,
     ) -> DexResult {
-        order::ask::handler(ctx, market, long, price, size)
+        order::ask::handler(ctx, market, long, price, size, out_asset, minimum_out)
     }
 
     pub fn cancel_order(ctx: Context<CancelOrder>, user_order_slot: u8) -> DexResult {
@@ -267,6 +272,28 @@ pub mod dex_program {
         dex::update_price::handler(ctx, prices)
     }
 
+    pub fn refresh_aum(ctx: Context<RefreshAum>) -> DexResult {
+        dex::refresh_aum::handler(ctx)
+    }
+
+    pub fn set_emission(
+        ctx: Context<SetEmission>,
+        es_vdx_per_second: u64,
+        vdx_total_supply: u64,
+        emission_halving_interval: i64,
+        es_vdx_percentage_for_vdx_pool: u16,
+        reward_percentage_for_vdx_pool: u16,
+    ) -> DexResult {
+        dex::set_emission::handler(
+            ctx,
+            es_vdx_per_second,
+            vdx_total_supply,
+            emission_halving_interval,
+            es_vdx_percentage_for_vdx_pool,
+            reward_percentage_for_vdx_pool,
+        )
+    }
+
     pub fn compound(ctx: Context<Compound>) -> DexResult {
         user::compound::handler(ctx)
     }
@@ -279,6 +306,14 @@ pub mod dex_program {
         user::redeem_vdx::handler(ctx, amount)
     }
 
+    pub fn lock_vdx(ctx: Context<StakeVdx>, amount: u64, lock_tier: u8) -> DexResult {
+        user::lock_vdx::handler(ctx, amount, lock_tier)
+    }
+
+    pub fn release_expired_lock(ctx: Context<ReleaseExpiredLock>) -> DexResult {
+        user::release_expired_lock::handler(ctx)
+    }
+
     pub fn set_liquidity_fee_rate(
         ctx: Context<SetLiquidityFeeRate>,
         index: u8,
@@ -291,6 +326,233 @@ pub mod dex_program {
     pub fn set_delegate(ctx: Context<SetDelegate>) -> DexResult {
         dex::set_delegate::handler(ctx)
     }
+
+    pub fn flash_loan_begin(ctx: Context<FlashLoanBegin>, amount: u64) -> DexResult {
+        pool::flash_loan_begin::handler(ctx, amount)
+    }
+
+    pub fn flash_loan_end(ctx: Context<FlashLoanEnd>) -> DexResult {
+        pool::flash_loan_end::handler(ctx)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, asset: u8) -> DexResult {
+        user::claim_rewards::handler(ctx, asset)
+    }
+
+    pub fn exit_vesting(ctx: Context<ExitVesting>, amount: u64, newest_first: bool) -> DexResult {
+        user::exit_vesting::handler(ctx, amount, newest_first)
+    }
+
+    pub fn update_market(
+        ctx: Context<UpdateMarket>,
+        market: u8,
+        minimum_collateral: u64,
+        charge_borrow_fee_interval: u64,
+        open_fee_rate: u16,
+        close_fee_rate: u16,
+        liquidate_fee_rate: u16,
+        liquidate_threshold: u16,
+        max_leverage: u32,
+        oracle_source: u8,
+    ) -> DexResult {
+        dex::update_market::handler(
+            ctx,
+            market,
+            minimum_collateral,
+            charge_borrow_fee_interval,
+            open_fee_rate,
+            close_fee_rate,
+            liquidate_fee_rate,
+            liquidate_threshold,
+            max_leverage,
+            oracle_source,
+        )
+    }
+
+    pub fn update_asset(
+        ctx: Context<UpdateAsset>,
+        asset: u8,
+        swap_fee_rate: u16,
+        borrow_fee_rate: u16,
+        add_liquidity_fee_rate: u16,
+        remove_liquidity_fee_rate: u16,
+        flash_loan_fee_rate: u16,
+        target_weight: u16,
+        oracle_source: u8,
+    ) -> DexResult {
+        dex::update_asset::handler(
+            ctx,
+            asset,
+            swap_fee_rate,
+            borrow_fee_rate,
+            add_liquidity_fee_rate,
+            remove_liquidity_fee_rate,
+            flash_loan_fee_rate,
+            target_weight,
+            oracle_source,
+        )
+    }
+
+    pub fn set_trading_state(
+        ctx: Context<SetTradingState>,
+        scope: u8,
+        index: u8,
+        state: u8,
+    ) -> DexResult {
+        dex::set_trading_state::handler(ctx, scope, index, state)
+    }
+
+    pub fn delist_market(ctx: Context<DelistMarket>, market: u8, settle_price: u64) -> DexResult {
+        dex::delist_market::handler(ctx, market, settle_price)
+    }
+
+    pub fn settle_delisted(ctx: Context<SettleDelisted>, market: u8) -> DexResult {
+        position::settle_delisted::handler(ctx, market)
+    }
+
+    pub fn remove_market(ctx: Context<RemoveMarket>, market: u8) -> DexResult {
+        dex::remove_market::handler(ctx, market)
+    }
+
+    pub fn remove_asset(ctx: Context<RemoveAsset>, asset: u8) -> DexResult {
+        dex::remove_asset::handler(ctx, asset)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>) -> DexResult {
+        dex::propose_authority::handler(ctx)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> DexResult {
+        dex::accept_authority::handler(ctx)
+    }
+
+    pub fn init_timelock(ctx: Context<InitTimelock>, delay: i64) -> DexResult {
+        dex::init_timelock::handler(ctx, delay)
+    }
+
+    pub fn set_timelock_delay(ctx: Context<SetTimelockDelay>, delay: i64) -> DexResult {
+        dex::set_timelock_delay::handler(ctx, delay)
+    }
+
+    pub fn execute_timelock(ctx: Context<ExecuteTimelock>, id: u64) -> DexResult {
+        dex::execute_timelock::handler(ctx, id)
+    }
+
+    pub fn cancel_timelock(ctx: Context<CancelTimelock>, id: u64) -> DexResult {
+        dex::cancel_timelock::handler(ctx, id)
+    }
+
+    pub fn set_protocol_fee_percentage(
+        ctx: Context<SetProtocolFeePercentage>,
+        percentage: u16,
+    ) -> DexResult {
+        pool::set_protocol_fee_percentage::handler(ctx, percentage)
+    }
+
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, asset: u8) -> DexResult {
+        pool::withdraw_protocol_fees::handler(ctx, asset)
+    }
+
+    pub fn set_referral_rates(
+        ctx: Context<SetReferralRates>,
+        discount: u16,
+        rebate: u16,
+    ) -> DexResult {
+        dex::set_referral_rates::handler(ctx, discount, rebate)
+    }
+
+    pub fn create_referral_code(ctx: Context<CreateReferralCode>, code: [u8; 16]) -> DexResult {
+        user::create_referral_code::handler(ctx, code)
+    }
+
+    pub fn set_referral(ctx: Context<SetReferral>) -> DexResult {
+        user::set_referral::handler(ctx)
+    }
+
+    pub fn set_fee_tiers(
+        ctx: Context<SetFeeTiers>,
+        staked: [u64; 4],
+        discounts: [u16; 4],
+    ) -> DexResult {
+        dex::set_fee_tiers::handler(ctx, staked, discounts)
+    }
+
+    pub fn set_user_delegate(ctx: Context<SetUserDelegate>, permissions: u8) -> DexResult {
+        user::set_user_delegate::handler(ctx, permissions)
+    }
+
+    pub fn clear_user_delegate(ctx: Context<ClearUserDelegate>) -> DexResult {
+        user::clear_user_delegate::handler(ctx)
+    }
+
+    pub fn append_order_pool_page(ctx: Context<AppendOrderPoolPage>, market: u8) -> DexResult {
+        dex::append_order_pool_page::handler(ctx, market)
+    }
+
+    pub fn resize_user_state(
+        ctx: Context<ResizeUserState>,
+        order_slot_count: u8,
+        position_slot_count: u8,
+        di_option_slot_count: u8,
+        asset_slot_count: u8,
+    ) -> DexResult {
+        user::resize::handler(
+            ctx,
+            order_slot_count,
+            position_slot_count,
+            di_option_slot_count,
+            asset_slot_count,
+        )
+    }
+
+    pub fn close_user_state(ctx: Context<CloseUserState>) -> DexResult {
+        user::close::handler(ctx)
+    }
+
+    pub fn create_sub_account(
+        ctx: Context<CreateSubAccount>,
+        sub_account: u8,
+        order_slot_count: u8,
+        position_slot_count: u8,
+        di_option_slot_count: u8,
+    ) -> DexResult {
+        user::create_sub_account::handler(
+            ctx,
+            sub_account,
+            order_slot_count,
+            position_slot_count,
+            di_option_slot_count,
+        )
+    }
+
+    pub fn transfer_user_asset(
+        ctx: Context<TransferUserAsset>,
+        asset: u8,
+        amount: u64,
+    ) -> DexResult {
+        user::transfer_asset::handler(ctx, asset, amount)
+    }
+
+    pub fn set_margin_mode(ctx: Context<SetMarginMode>, cross_margin: bool) -> DexResult {
+        user::set_margin_mode::handler(ctx, cross_margin)
+    }
+
+    pub fn deposit_asset(ctx: Context<DepositAsset>, asset: u8, amount: u64) -> DexResult {
+        order::deposit::handler(ctx, asset, amount)
+    }
+
+    pub fn set_position_leverage(
+        ctx: Context<SetPositionLeverage>,
+        market: u8,
+        long: bool,
+        leverage: u32,
+    ) -> DexResult {
+        position::set_leverage::handler(ctx, market, long, leverage)
+    }
+
+    pub fn settle_borrow_fee(ctx: Context<SettleBorrowFee>, market: u8, long: bool) -> DexResult {
+        position::settle_borrow_fee::handler(ctx, market, long)
+    }
 }
 
 #[derive(Accounts)]
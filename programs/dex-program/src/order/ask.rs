@@ -4,7 +4,7 @@ use crate::{
     errors::{DexError, DexResult},
     order::Order,
     user::state::*,
-    utils::{ORDER_POOL_MAGIC_BYTE, USDC_DECIMALS},
+    utils::{DELEGATE_ORDER, NIL8, ORDER_POOL_MAGIC_BYTE, USDC_DECIMALS},
 };
 use anchor_lang::prelude::*;
 
@@ -25,7 +25,7 @@ pub struct LimitAsk<'info> {
     pub order_pool_entry_page: UncheckedAccount<'info>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -38,9 +38,18 @@ pub struct LimitAsk<'info> {
 
 /// Layout of remaining accounts:
 /// 1. Order pool remaining pages
-pub fn handler(ctx: Context<LimitAsk>, market: u8, long: bool, price: u64, size: u64) -> DexResult {
+pub fn handler(
+    ctx: Context<LimitAsk>,
+    market: u8,
+    long: bool,
+    price: u64,
+    size: u64,
+    out_asset: u8,
+    minimum_out: u64,
+) -> DexResult {
     let dex = &ctx.accounts.dex.load()?;
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, false)?;
 
     require!(
         dex.price_feed == ctx.accounts.price_feed.key(),
@@ -82,6 +91,14 @@ pub fn handler(ctx: Context<LimitAsk>, market: u8, long: bool, price: u64, size:
     };
     require!(ai.valid, DexError::InvalidMarketIndex);
 
+    // Payout asset is swapped from the collateral asset when the order gets filled
+    if out_asset != NIL8 {
+        require!(
+            dex.asset_as_ref(out_asset).is_ok(),
+            DexError::InvalidAssetIndex
+        );
+    }
+
     let price_feed = &ctx.accounts.price_feed.load()?;
     // Check price
     let market_price = get_price(
@@ -100,6 +117,12 @@ pub fn handler(ctx: Context<LimitAsk>, market: u8, long: bool, price: u64, size:
 
     // Mount user state
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_ORDER,
+    )?;
 
     // Mount order book & order pool
     let order_book = OrderBook::mount(&ctx.accounts.order_book, true)?;
@@ -119,12 +142,17 @@ pub fn handler(ctx: Context<LimitAsk>, market: u8, long: bool, price: u64, size:
     let order = order_pool
         .new_slot()
         .map_err(|_| DexError::NoFreeSlotInOrderPool)?;
-    order
-        .data
-        .init(price, closing_size, ctx.accounts.authority.key().to_bytes());
+    order.data.init(
+        price,
+        closing_size,
+        us.borrow().owner().to_bytes(),
+        us.borrow().sub_account(),
+    );
 
     us.borrow_mut()
         .set_ask_order_slot(user_order_slot, order.index())?;
+    us.borrow_mut()
+        .set_ask_order_payout(user_order_slot, out_asset, minimum_out)?;
 
     // Link order to order book
     // let side = if long { OrderSide::ASK } else { OrderSide::BID };
@@ -4,7 +4,7 @@ use crate::{
     errors::{DexError, DexResult},
     order::Order,
     user::state::*,
-    utils::{value, LEVERAGE_POW_DECIMALS, ORDER_POOL_MAGIC_BYTE, USDC_DECIMALS},
+    utils::{value, DELEGATE_ORDER, LEVERAGE_POW_DECIMALS, ORDER_POOL_MAGIC_BYTE, USDC_DECIMALS},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, TokenAccount, Transfer};
@@ -48,7 +48,7 @@ pub struct LimitBid<'info> {
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -76,6 +76,7 @@ pub fn handler(
 ) -> DexResult {
     let dex = &ctx.accounts.dex.load()?;
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, true)?;
 
     require!(
         dex.price_feed == ctx.accounts.price_feed.key(),
@@ -142,7 +143,31 @@ pub fn handler(
         DexError::InvalidMarketIndex
     );
 
-    let mfr = mi.get_fee_rates(mai.borrow_fee_rate);
+    dex.check_asset_trading(asset, true)?;
+    dex.check_asset_trading(market_asset_index, true)?;
+
+    // Mount user state
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_ORDER,
+    )?;
+
+    // Limit bids open with market asset collateral and can't be merged into a stable collateral long
+    require!(
+        !long
+            || !us
+                .borrow()
+                .is_stable_collateral(market, true)
+                .unwrap_or(false),
+        DexError::StableCollateralBid
+    );
+    let mfr = mi.get_fee_rates(
+        mai.borrow_fee_rate,
+        dex.fee_discount(us.borrow().meta.vdx.fee_tier_amount()?),
+    );
     let price_feed = &ctx.accounts.price_feed.load()?;
 
     let actual_amount = if ai.mint == mai.mint {
@@ -210,9 +235,6 @@ pub fn handler(
         require!(market_price < price, DexError::PriceLTMarketPrice)
     }
 
-    // Mount user state
-    let us = UserState::mount(&ctx.accounts.user_state, true)?;
-
     // Mount order book & order pool
     let order_book = OrderBook::mount(&ctx.accounts.order_book, true)?;
     let order_pool = PagedList::<Order>::mount(
@@ -228,9 +250,12 @@ pub fn handler(
         .new_slot()
         .map_err(|_| DexError::NoFreeSlotInOrderPool)?;
 
-    order
-        .data
-        .init(price, amount, ctx.accounts.authority.key().to_bytes());
+    order.data.init(
+        price,
+        amount,
+        us.borrow().owner().to_bytes(),
+        us.borrow().sub_account(),
+    );
 
     // Save order in user state
     let user_order_slot = us.borrow_mut().new_bid_order(
@@ -4,7 +4,7 @@ use crate::{
     errors::{DexError, DexResult},
     order::{select_side, Order},
     user::state::*,
-    utils::ORDER_POOL_MAGIC_BYTE,
+    utils::{DELEGATE_ORDER, ORDER_POOL_MAGIC_BYTE},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, TokenAccount, Transfer};
@@ -29,14 +29,11 @@ pub struct CancelOrder<'info> {
     /// CHECK
     pub program_signer: AccountInfo<'info>,
 
-    #[account(
-            mut,
-            constraint = (user_mint_acc.owner == *authority.key)
-        )]
+    #[account(mut)]
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -52,6 +49,19 @@ pub struct CancelOrder<'info> {
 pub fn handler(ctx: Context<CancelOrder>, user_order_slot: u8) -> DexResult {
     // Mount user state
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_ORDER,
+    )?;
+
+    // Refunds only flow back to the owner
+    require_keys_eq!(
+        ctx.accounts.user_mint_acc.owner,
+        us.borrow().owner(),
+        DexError::InvalidUserMintAccount
+    );
     let order_slot = us
         .borrow_mut()
         .get_order_info(user_order_slot)
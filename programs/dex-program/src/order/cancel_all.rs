@@ -4,10 +4,10 @@ use crate::{
     errors::{DexError, DexResult},
     order::{select_side, Order},
     user::state::*,
-    utils::ORDER_POOL_MAGIC_BYTE,
+    utils::{DELEGATE_ORDER, ORDER_POOL_MAGIC_BYTE},
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer};
+use anchor_spl::token::{self, TokenAccount, Transfer};
 
 #[derive(Accounts)]
 pub struct CancelAllOrders<'info> {
@@ -15,7 +15,7 @@ pub struct CancelAllOrders<'info> {
     pub dex: AccountLoader<'info, Dex>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -42,6 +42,12 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelAllOrders<'info>>) -
 
     // Mount user state
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_ORDER,
+    )?;
     let mut offset = 0usize;
 
     let token_program = ctx.accounts.token_program.clone();
@@ -73,7 +79,7 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelAllOrders<'info>>) -
         for i in 0..mi.order_pool_remaining_pages_number as usize {
             require_eq!(
                 mi.order_pool_remaining_pages[i],
-                ctx.remaining_accounts[offset + 6 + i].key(),
+                ctx.remaining_accounts[offset + i].key(),
                 DexError::InvalidRemainingAccounts
             );
         }
@@ -107,6 +113,13 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelAllOrders<'info>>) -
             let program_signer = &ctx.remaining_accounts[offset + 2];
             let user_mint_acc = &ctx.remaining_accounts[offset + 3];
 
+            // Refunds only flow back to the owner
+            let token_account = Account::<TokenAccount>::try_from(user_mint_acc)?;
+            require!(
+                token_account.owner == us.borrow().owner() && token_account.mint == mint.key(),
+                DexError::InvalidUserMintAccount
+            );
+
             let ai = dex.asset_as_ref(asset)?;
             require!(
                 ai.valid
@@ -6,8 +6,8 @@ use crate::{
     },
     errors::{DexError, DexResult},
     order::MatchEvent,
-    user::state::*,
-    utils::SafeMath,
+    user::{pay_referral_fee, state::*},
+    utils::{SafeMath, NIL8},
 };
 
 use anchor_lang::{prelude::*, system_program};
@@ -23,7 +23,7 @@ pub struct Crank<'info> {
     pub user: AccountInfo<'info>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), user.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -180,6 +180,48 @@ fn withdraw_market_mint(
     Ok(())
 }
 
+fn swap_payout(
+    dex: &Dex,
+    ctx: &Context<Crank>,
+    asset: u8,
+    order: &UserOrder,
+    amount: u64,
+    price_feed: &PriceFeed,
+) -> DexResult<Option<(u64, u64)>> {
+    // The cranker must pass the payout asset accounts
+    let ai = dex.asset_as_ref(asset)?;
+    let aoi = dex.asset_as_ref(order.out_asset)?;
+    require!(
+        aoi.valid && aoi.mint == ctx.accounts.in_mint.key(),
+        DexError::InvalidMint
+    );
+    require!(
+        ai.oracle == ctx.accounts.market_mint_oracle.key()
+            && aoi.oracle == ctx.accounts.in_mint_oracle.key(),
+        DexError::InvalidOracle
+    );
+
+    if dex.check_asset_trading(order.out_asset, false).is_err() {
+        return Ok(None);
+    }
+
+    let oracles = &[
+        &ctx.accounts.market_mint_oracle,
+        &ctx.accounts.in_mint_oracle,
+    ];
+    let (out, fee) = dex.swap(asset, order.out_asset, amount, true, oracles, price_feed)?;
+
+    if out < order.minimum_out || out > aoi.liquidity_amount {
+        return Ok(None);
+    }
+
+    Ok(Some((out, fee)))
+}
+
+// Layout of remaining accounts:
+//  referred user: referral code, referrer user state
+//
+// A crank of a referred user fails without them, a keeper can't skip the discount and rebate.
 pub fn handler(ctx: Context<Crank>) -> DexResult {
     let dex = &mut ctx.accounts.dex.load_mut()?;
 
@@ -206,9 +248,15 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
     );
 
     require!(
-        data.user == ctx.accounts.user.key().to_bytes(),
+        data.user == ctx.accounts.user.key().to_bytes()
+            && data.sub_account == us.borrow().sub_account(),
         DexError::InvalidUser
     );
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
 
     require!(
         order.market < dex.markets_number,
@@ -223,7 +271,14 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
     let mi = &dex.markets[order.market as usize];
     require!(mi.valid, DexError::InvalidMarketIndex);
 
-    let (market_asset_index, mai) = if order.long {
+    // Limit bids open with market asset collateral, stable collateral longs close in USDC
+    let stable_position = order.long
+        && us
+            .borrow()
+            .is_stable_collateral(order.market, true)
+            .unwrap_or(false);
+    let base_collateral = order.long && (order.open || !stable_position);
+    let (market_asset_index, mai) = if base_collateral {
         (mi.asset_index, &dex.assets[mi.asset_index as usize])
     } else {
         (
@@ -240,7 +295,10 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
         DexError::InvalidMarketIndex
     );
 
-    let mfr = mi.get_fee_rates(mai.borrow_fee_rate);
+    let mfr = mi.get_fee_rates(
+        mai.borrow_fee_rate,
+        dex.fee_discount(us.borrow().meta.vdx.fee_tier_amount()?),
+    );
     let user_state_key = ctx.accounts.user_state.key().to_bytes();
 
     let user_mint_acc =
@@ -297,7 +355,18 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
             borrow + actual_amount
         };
 
-        if let Err(_) = dex.has_sufficient_liquidity(order.market, order.long, required_liquidity) {
+        // Opening orders are refunded once the market or its assets stop accepting new exposure,
+        // or when the long already keeps stable collateral
+        let can_open = !stable_position
+            && dex.check_market_trading(order.market, true).is_ok()
+            && dex.check_asset_trading(order.asset, true).is_ok()
+            && dex.check_asset_trading(market_asset_index, true).is_ok();
+
+        if !can_open
+            || dex
+                .has_sufficient_liquidity(order.market, order.long, required_liquidity)
+                .is_err()
+        {
             if let Some(acc) = user_mint_acc {
                 refund_in_mint(&ctx, &acc, ai, order.size)?;
             } else {
@@ -320,6 +389,13 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
             )?;
 
             dex.borrow_fund(order.market, order.long, collateral, borrow, open_fee)?;
+            pay_referral_fee(
+                dex,
+                &us,
+                ctx.remaining_accounts,
+                market_asset_index,
+                open_fee,
+            )?;
             dex.increase_global_position(order.market, order.long, order.price, size, collateral)?;
             dex.increase_volume(order.market, order.price, size)?;
 
@@ -337,6 +413,8 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
                 open_fee,
                 0,
                 0,
+                us.borrow()
+                    .liquidation_price(order.market, order.long, &mfr)?,
             )?;
         }
     } else {
@@ -353,25 +431,83 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
                 true,
             )?;
 
-        // Update market global position
-        dex.decrease_global_position(order.market, order.long, closed_size, collateral)?;
+        // Update market global position, its long collateral is kept in the market asset
+        let global_collateral = if stable_position { 0 } else { collateral };
+        dex.decrease_global_position(order.market, order.long, closed_size, global_collateral)?;
 
         let withdrawable = dex.settle_pnl(
             order.market,
-            order.long,
+            base_collateral,
             collateral,
             borrow,
             pnl,
             close_fee,
             borrow_fee,
         )?;
+        // The market asset is valued at the order price, the others at their feed prices
+        let mut asset_prices = dex.feed_prices(price_feed)?;
+        asset_prices[dex.markets[order.market as usize].asset_index as usize] = order.price;
+        us.borrow_mut().cover_shortfall(
+            dex,
+            order.market,
+            base_collateral,
+            collateral,
+            pnl,
+            close_fee.safe_add(borrow_fee)?,
+            &asset_prices,
+        )?;
+        pay_referral_fee(
+            dex,
+            &us,
+            ctx.remaining_accounts,
+            market_asset_index,
+            close_fee,
+        )?;
 
-        if withdrawable > 0 {
-            if let Some(acc) = user_mint_acc {
-                withdraw_market_mint(&ctx, &acc, market_asset_nonce, withdrawable)?;
-            } else {
-                us.borrow_mut()
-                    .deposit_asset(market_asset_index, withdrawable)?;
+        // Pay out in the asset asked by the order. Missing or mismatched accounts fail the crank,
+        // while slippage, short liquidity or a paused asset fall back to the collateral asset
+        // so that the match queue never gets blocked by market conditions
+        let swap_requested = order.out_asset != NIL8 && order.out_asset != market_asset_index;
+        let swapped = if swap_requested && withdrawable > 0 {
+            swap_payout(
+                dex,
+                &ctx,
+                market_asset_index,
+                &order,
+                withdrawable,
+                price_feed,
+            )?
+        } else {
+            None
+        };
+
+        if let Some((out, fee)) = swapped {
+            dex.swap_in(market_asset_index, withdrawable.safe_sub(fee)?, fee)?;
+            dex.swap_out(order.out_asset, out)?;
+
+            event_queue.swap_asset(
+                user_state_key,
+                ctx.accounts.market_mint.key().to_bytes(),
+                ctx.accounts.in_mint.key().to_bytes(),
+                withdrawable,
+                out,
+                fee,
+            )?;
+
+            match user_mint_acc {
+                Some(acc) if acc.mint == ctx.accounts.in_mint.key() => {
+                    refund_in_mint(&ctx, &acc, dex.asset_as_ref(order.out_asset)?, out)?
+                }
+                _ => us.borrow_mut().deposit_asset(order.out_asset, out)?,
+            }
+        } else if withdrawable > 0 {
+            match user_mint_acc {
+                Some(acc) if !swap_requested || acc.mint == ctx.accounts.market_mint.key() => {
+                    withdraw_market_mint(&ctx, &acc, market_asset_nonce, withdrawable)?
+                }
+                _ => us
+                    .borrow_mut()
+                    .deposit_asset(market_asset_index, withdrawable)?,
             }
         }
 
@@ -389,6 +525,8 @@ pub fn handler(ctx: Context<Crank>) -> DexResult {
             close_fee,
             borrow_fee,
             pnl,
+            us.borrow()
+                .liquidation_price(order.market, order.long, &mfr)?,
         )?;
     }
 
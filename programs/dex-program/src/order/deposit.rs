@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+    user::UserState,
+};
+
+#[derive(Accounts)]
+pub struct DepositAsset<'info> {
+    #[account(owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut)]
+    pub mint_vault: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = (user_mint_acc.owner == *authority.key)
+    )]
+    pub user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK
+    #[account(executable, constraint = (token_program.key == &token::ID))]
+    pub token_program: AccountInfo<'info>,
+}
+
+// Credits the user's asset pool, the balance backs cross-margin positions
+pub fn handler(ctx: Context<DepositAsset>, asset: u8, amount: u64) -> DexResult {
+    require!(amount > 0, DexError::InvalidAmount);
+
+    let dex = &ctx.accounts.dex.load()?;
+    dex.check_asset_trading(asset, true)?;
+
+    let ai = dex.asset_as_ref(asset)?;
+    require!(
+        ai.vault == ctx.accounts.mint_vault.key(),
+        DexError::InvalidVault
+    );
+    require!(
+        ai.mint == ctx.accounts.user_mint_acc.mint,
+        DexError::InvalidUserMintAccount
+    );
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    us.borrow_mut().deposit_asset(asset, amount)?;
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_mint_acc.to_account_info(),
+        to: ctx.accounts.mint_vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.clone(), cpi_accounts);
+    token::transfer(cpi_ctx, amount)
+}
@@ -40,6 +40,7 @@ pub struct FillOrder<'info> {
 pub fn handler(ctx: Context<FillOrder>, market: u8) -> DexResult {
     let dex = &ctx.accounts.dex.load()?;
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, false)?;
     require!(
         dex.match_queue == ctx.accounts.match_queue.key(),
         DexError::InvalidMatchQueue
@@ -98,26 +99,24 @@ pub fn handler(ctx: Context<FillOrder>, market: u8) -> DexResult {
 
     let mut filled_bid_orders = 0u32;
     loop {
-        let (user_order_slot, order_slot, user) = match order_book.get_next_match_order(
-            market_price,
-            OrderSide::ASK,
-            OrderType::LIMIT,
-            &order_pool,
-        ) {
+        let (user_order_slot, order_slot, user, sub_account) = match order_book
+            .get_next_match_order(market_price, OrderSide::ASK, OrderType::LIMIT, &order_pool)
+        {
             Some((order, _)) => {
                 let user_order_slot = order.data.user_order_slot;
                 let order_slot = order.index();
                 let user = order.data.user;
+                let sub_account = order.data.sub_account;
 
                 order_book.fill_order(u64::MAX, OrderSide::ASK, order, &order_pool)?;
 
-                (user_order_slot, order_slot, user)
+                (user_order_slot, order_slot, user, sub_account)
             }
             None => break,
         };
 
         match_queue
-            .append(user, order_slot, user_order_slot)
+            .append(user, order_slot, user_order_slot, sub_account)
             .map_err(|_| DexError::FailedAppendMatchEvent)?;
 
         filled_bid_orders += 1;
@@ -128,26 +127,24 @@ pub fn handler(ctx: Context<FillOrder>, market: u8) -> DexResult {
 
     let mut filled_ask_orders = 0u32;
     loop {
-        let (user_order_slot, order_slot, user) = match order_book.get_next_match_order(
-            market_price,
-            OrderSide::BID,
-            OrderType::LIMIT,
-            &order_pool,
-        ) {
+        let (user_order_slot, order_slot, user, sub_account) = match order_book
+            .get_next_match_order(market_price, OrderSide::BID, OrderType::LIMIT, &order_pool)
+        {
             Some((order, _)) => {
                 let user_order_slot = order.data.user_order_slot;
                 let order_slot = order.index();
                 let user = order.data.user;
+                let sub_account = order.data.sub_account;
 
                 order_book.fill_order(u64::MAX, OrderSide::BID, order, &order_pool)?;
 
-                (user_order_slot, order_slot, user)
+                (user_order_slot, order_slot, user, sub_account)
             }
             None => break,
         };
 
         match_queue
-            .append(user, order_slot, user_order_slot)
+            .append(user, order_slot, user_order_slot, sub_account)
             .map_err(|_| DexError::FailedAppendMatchEvent)?;
 
         filled_ask_orders += 1;
@@ -4,20 +4,34 @@ pub struct MatchEvent {
     pub user: [u8; 32],
     pub order_slot: u32,
     pub user_order_slot: u8,
-    pub _padding: [u8; 3],
+    pub sub_account: u8,
+    pub _padding: [u8; 2],
 }
 
 pub trait AppendSingleEvent {
-    fn append(&mut self, user: [u8; 32], order_slot: u32, user_order_slot: u8) -> DexResult;
+    fn append(
+        &mut self,
+        user: [u8; 32],
+        order_slot: u32,
+        user_order_slot: u8,
+        sub_account: u8,
+    ) -> DexResult;
 }
 
 impl AppendSingleEvent for SingleEventQueue<'_, MatchEvent> {
-    fn append(&mut self, user: [u8; 32], order_slot: u32, user_order_slot: u8) -> DexResult {
+    fn append(
+        &mut self,
+        user: [u8; 32],
+        order_slot: u32,
+        user_order_slot: u8,
+        sub_account: u8,
+    ) -> DexResult {
         let new_event = self.new_tail()?;
         new_event.data.user = user;
 
         new_event.data.order_slot = order_slot;
         new_event.data.user_order_slot = user_order_slot;
+        new_event.data.sub_account = sub_account;
 
         Ok(())
     }
@@ -46,7 +60,7 @@ mod test {
     }
 
     fn append_event(q: &mut SingleEventQueue<MatchEvent>, i: usize) {
-        q.append(random_pubkey(i).to_bytes(), i as u32, i as u8)
+        q.append(random_pubkey(i).to_bytes(), i as u32, i as u8, 0)
             .assert_ok();
     }
 
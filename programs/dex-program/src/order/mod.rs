@@ -3,6 +3,7 @@ pub mod bid;
 pub mod cancel;
 pub mod cancel_all;
 pub mod crank;
+pub mod deposit;
 pub mod fill;
 pub mod match_event;
 pub mod order;
@@ -13,6 +14,7 @@ pub use bid::*;
 pub use cancel::*;
 pub use cancel_all::*;
 pub use crank::*;
+pub use deposit::*;
 pub use fill::*;
 pub use match_event::*;
 pub use order::*;
@@ -12,16 +12,17 @@ pub struct Order {
     pub size: u64,
     pub price_node: u16,
     pub user_order_slot: u8,
-    padding: u8,
+    pub sub_account: u8,
     next: u32,
     prev: u32,
 }
 
 impl Order {
-    pub fn init(&mut self, price: u64, size: u64, user: [u8; 32]) {
+    pub fn init(&mut self, price: u64, size: u64, user: [u8; 32], sub_account: u8) {
         self.price = price;
         self.size = size;
         self.user = user;
+        self.sub_account = sub_account;
     }
 
     pub fn set_extra_slot(&mut self, price_node: u16, user_order_slot: u8) {
@@ -26,7 +26,7 @@ pub struct WithdrawAsset<'info> {
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -41,9 +41,20 @@ pub fn handler(ctx: Context<WithdrawAsset>, asset: u8) -> DexResult {
     let dex = &ctx.accounts.dex.load()?;
 
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    require!(
+        !us.borrow().is_cross_margin() || !us.borrow().has_position(),
+        DexError::CrossMarginPositionOpen
+    );
     let withdrawable = us.borrow_mut().withdraw_asset(asset)?;
     require!(withdrawable > 0, DexError::AssetNotExist);
 
+    dex.check_asset_trading(asset, false)?;
+
     let ai = dex.asset_as_ref(asset)?;
     require!(
         ai.vault == ctx.accounts.mint_vault.key(),
@@ -17,6 +17,9 @@ pub struct AddLiquidity<'info> {
     /// CHECK
     mint: AccountInfo<'info>,
 
+    /// CHECK
+    oracle: AccountInfo<'info>,
+
     /// CHECK
     #[account(mut)]
     vault: AccountInfo<'info>,
@@ -28,7 +31,7 @@ pub struct AddLiquidity<'info> {
     user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -45,25 +48,9 @@ pub struct AddLiquidity<'info> {
     pub price_feed: AccountLoader<'info, PriceFeed>,
 }
 
-// Remaining accounts layout:
-// dex.assets.map({
-//   asset index price oracle account
-// })
-// dex.markets.map({
-//    market index price oracle account
-// })
 pub fn handler(ctx: Context<AddLiquidity>, amount: u64) -> DexResult {
     let mut dex = &mut ctx.accounts.dex.load_mut()?;
 
-    let assets_oracles_len = dex.assets.iter().filter(|a| a.valid).count();
-    let expected_oracles_len = assets_oracles_len + dex.markets.iter().filter(|m| m.valid).count();
-
-    require_eq!(
-        expected_oracles_len,
-        ctx.remaining_accounts.len(),
-        DexError::InvalidRemainingAccounts
-    );
-
     require!(
         dex.event_queue == ctx.accounts.event_queue.key(),
         DexError::InvalidEventQueue
@@ -75,6 +62,7 @@ pub fn handler(ctx: Context<AddLiquidity>, amount: u64) -> DexResult {
     );
 
     let (index, ai) = dex.find_asset_by_mint(ctx.accounts.mint.key())?;
+    dex.check_asset_trading(index, true)?;
     require_eq!(ai.vault, ctx.accounts.vault.key(), DexError::InvalidVault);
 
     //Transfer assets
@@ -89,12 +77,17 @@ pub fn handler(ctx: Context<AddLiquidity>, amount: u64) -> DexResult {
     let price_feed = &ctx.accounts.price_feed.load()?;
 
     // Update rewards
-    dex.update_staking_pool(&ctx.remaining_accounts, price_feed, true)?;
+    dex.update_staking_pool(true)?;
 
     let (vlp_amount, fee) =
-        dex.add_liquidity(index, amount, true, &ctx.remaining_accounts, price_feed)?;
+        dex.add_liquidity(index, amount, true, &ctx.accounts.oracle, price_feed)?;
 
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
     us.borrow_mut().enter_staking_vlp(&mut dex, vlp_amount)?;
 
     // Save to event queue
@@ -0,0 +1,104 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    },
+    Discriminator,
+};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct FlashLoanBegin<'info> {
+    #[account(mut)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub mint: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// CHECK
+    pub program_signer: AccountInfo<'info>,
+
+    #[account(mut, constraint = user_mint_acc.mint == *mint.key)]
+    pub user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<FlashLoanBegin>, amount: u64) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    let (index, ai) = dex.find_asset_by_mint(ctx.accounts.mint.key())?;
+    require!(
+        ai.vault == ctx.accounts.vault.key()
+            && ai.program_signer == ctx.accounts.program_signer.key(),
+        DexError::InvalidMint
+    );
+    dex.check_asset_trading(index, true)?;
+    let nonce = ai.nonce;
+
+    // The loan must be taken by a top-level instruction and be followed by
+    // a matching end instruction in the same transaction.
+    let ixs = &ctx.accounts.instructions;
+    let current = load_current_index_checked(ixs)? as usize;
+    let current_ix = load_instruction_at_checked(current, ixs)?;
+    require_keys_eq!(
+        current_ix.program_id,
+        crate::ID,
+        DexError::FlashLoanCpiNotAllowed
+    );
+
+    let mut next = current + 1;
+    loop {
+        let ix = load_instruction_at_checked(next, ixs)
+            .map_err(|_| error!(DexError::MissingFlashLoanEnd))?;
+
+        if ix.program_id == crate::ID
+            && ix.data.len() >= 8
+            && ix.data[..8] == crate::instruction::FlashLoanEnd::DISCRIMINATOR
+            && ix.accounts.len() >= 2
+            && ix.accounts[0].pubkey == ctx.accounts.dex.key()
+            && ix.accounts[1].pubkey == ctx.accounts.mint.key()
+        {
+            break;
+        }
+
+        next += 1;
+    }
+
+    dex.flash_loan_begin(index, amount)?;
+
+    let seeds = &[
+        ctx.accounts.mint.key.as_ref(),
+        ctx.accounts.dex.to_account_info().key.as_ref(),
+        &[nonce],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.vault.to_account_info(),
+        to: ctx.accounts.user_mint_acc.to_account_info(),
+        authority: ctx.accounts.program_signer.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    );
+    token::transfer(cpi_ctx, amount)
+}
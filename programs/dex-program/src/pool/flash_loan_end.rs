@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+    utils::SafeMath,
+};
+
+#[derive(Accounts)]
+pub struct FlashLoanEnd<'info> {
+    #[account(mut)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub mint: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    #[account(
+         mut,
+         constraint = (user_mint_acc.owner == *authority.key && user_mint_acc.mint == *mint.key)
+     )]
+    pub user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<FlashLoanEnd>) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    let (index, ai) = dex.find_asset_by_mint(ctx.accounts.mint.key())?;
+    require!(ai.vault == ctx.accounts.vault.key(), DexError::InvalidMint);
+
+    let (amount, fee) = dex.flash_loan_end(index)?;
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.user_mint_acc.to_account_info(),
+        to: ctx.accounts.vault.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, amount.safe_add(fee)?)
+}
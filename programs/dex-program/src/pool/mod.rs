@@ -1,9 +1,17 @@
 pub mod add;
+pub mod flash_loan_begin;
+pub mod flash_loan_end;
 pub mod remove;
 pub mod set_fee_rate;
+pub mod set_protocol_fee_percentage;
 pub mod swap;
+pub mod withdraw_protocol_fees;
 
 pub use add::*;
+pub use flash_loan_begin::*;
+pub use flash_loan_end::*;
 pub use remove::*;
 pub use set_fee_rate::*;
+pub use set_protocol_fee_percentage::*;
 pub use swap::*;
+pub use withdraw_protocol_fees::*;
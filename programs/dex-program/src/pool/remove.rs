@@ -16,6 +16,9 @@ pub struct RemoveLiquidity<'info> {
     /// CHECK
     mint: AccountInfo<'info>,
 
+    /// CHECK
+    oracle: AccountInfo<'info>,
+
     /// CHECK
     #[account(mut)]
     vault: AccountInfo<'info>,
@@ -30,7 +33,7 @@ pub struct RemoveLiquidity<'info> {
     user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -49,25 +52,9 @@ pub struct RemoveLiquidity<'info> {
     pub price_feed: AccountLoader<'info, PriceFeed>,
 }
 
-// Remaining accounts layout:
-// dex.assets.map({
-//   asset index price oracle account
-// })
-// dex.markets.map({
-//    market index price oracle account
-// })
 pub fn handler(ctx: Context<RemoveLiquidity>, vlp_amount: u64) -> DexResult {
     let mut dex = &mut ctx.accounts.dex.load_mut()?;
 
-    let assets_oracles_len = dex.assets.iter().filter(|a| a.valid).count();
-    let expected_oracles_len = assets_oracles_len + dex.markets.iter().filter(|m| m.valid).count();
-
-    require_eq!(
-        expected_oracles_len,
-        ctx.remaining_accounts.len(),
-        DexError::InvalidRemainingAccounts
-    );
-
     require!(
         dex.event_queue == ctx.accounts.event_queue.key(),
         DexError::InvalidEventQueue
@@ -79,6 +66,7 @@ pub fn handler(ctx: Context<RemoveLiquidity>, vlp_amount: u64) -> DexResult {
     );
 
     let (index, ai) = dex.find_asset_by_mint(ctx.accounts.mint.key())?;
+    dex.check_asset_trading(index, false)?;
     require!(
         ai.vault == ctx.accounts.vault.key()
             && ai.program_signer == ctx.accounts.program_signer.key(),
@@ -91,19 +79,20 @@ pub fn handler(ctx: Context<RemoveLiquidity>, vlp_amount: u64) -> DexResult {
     ];
     let price_feed = &ctx.accounts.price_feed.load()?;
 
-    dex.update_staking_pool(&ctx.remaining_accounts, price_feed, true)?;
+    dex.update_staking_pool(true)?;
 
     let signer = &[&seeds[..]];
 
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
     let actual_vlp_amount = us.borrow().withdrawable_vlp_amount(&mut dex, vlp_amount)?;
 
-    let (withdraw, fee) = dex.remove_liquidity(
-        index,
-        actual_vlp_amount,
-        &ctx.remaining_accounts,
-        price_feed,
-    )?;
+    let (withdraw, fee) =
+        dex.remove_liquidity(index, actual_vlp_amount, &ctx.accounts.oracle, price_feed)?;
 
     if withdraw > 0 {
         // Withdraw assets
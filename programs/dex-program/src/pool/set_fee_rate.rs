@@ -27,6 +27,12 @@ pub fn handler(
         DexError::InvalidAdminOrDelegate
     );
 
+    // Fee changes go through update_asset once a timelock is attached
+    require!(
+        dex.timelock == Pubkey::default(),
+        DexError::TimelockRequired
+    );
+
     let ai = dex.asset_as_mut(index)?;
     if add_fee_rate != u16::MAX {
         ai.add_liquidity_fee_rate = add_fee_rate;
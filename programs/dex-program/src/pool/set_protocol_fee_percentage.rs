@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, state::*, timelock_of, TimelockKind, TimelockStatus},
+    errors::{DexError, DexResult},
+    utils::MAX_PROTOCOL_FEE_PERCENTAGE,
+};
+
+#[derive(Accounts)]
+pub struct SetProtocolFeePercentage<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Layout of remaining accounts (once a timelock is attached, the change is queued):
+//  offset 0: timelock
+pub fn handler(ctx: Context<SetProtocolFeePercentage>, percentage: u16) -> DexResult {
+    require!(
+        percentage <= MAX_PROTOCOL_FEE_PERCENTAGE,
+        DexError::InvalidProtocolFeePercentage
+    );
+
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let timelock = timelock_of(dex, ctx.remaining_accounts)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    let old = dex.protocol_fee_percentage;
+    match timelock {
+        Some(timelock) => {
+            let (id, eta) = timelock.load_mut()?.queue(
+                TimelockKind::ProtocolFeePercentage,
+                0,
+                &percentage.to_le_bytes(),
+            )?;
+
+            event_queue.update_timelock(
+                id,
+                eta,
+                TimelockKind::ProtocolFeePercentage as u8,
+                0,
+                TimelockStatus::Queued as u8,
+            )
+        }
+        None => {
+            dex.set_protocol_fee_percentage(percentage)?;
+            event_queue.update_protocol_fee_percentage(old, percentage)
+        }
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     dex::{event::AppendEvent, Dex, PriceFeed},
     errors::DexError,
     errors::DexResult,
+    user::UserState,
     utils::SafeMath,
 };
 
@@ -54,7 +55,7 @@ pub struct Swap<'info> {
     pub event_queue: UncheckedAccount<'info>,
 
     /// CHECK
-    #[account(seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -75,6 +76,13 @@ pub fn handler(ctx: Context<Swap>, amount: u64) -> DexResult {
         DexError::InvalidPriceFeed
     );
 
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
     let (ain, aii) = dex.find_asset_by_mint(ctx.accounts.in_mint.key())?;
     require!(
         aii.mint == ctx.accounts.in_mint.key()
@@ -94,6 +102,9 @@ pub fn handler(ctx: Context<Swap>, amount: u64) -> DexResult {
 
     require!(aii.mint != aoi.mint, DexError::InvalidMint);
 
+    dex.check_asset_trading(ain, true)?;
+    dex.check_asset_trading(aout, false)?;
+
     let seeds = &[
         ctx.accounts.out_mint.key.as_ref(),
         ctx.accounts.dex.to_account_info().key.as_ref(),
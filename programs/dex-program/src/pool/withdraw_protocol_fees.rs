@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, Dex},
+    errors::{DexError, DexResult},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(
+        mut,
+        has_one = authority, owner = *program_id
+    )]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(mut)]
+    pub mint_vault: AccountInfo<'info>,
+
+    /// CHECK
+    pub asset_program_signer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub recipient_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK
+    #[account(executable, constraint = (token_program.key == &token::ID))]
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<WithdrawProtocolFees>, asset: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let ai = dex.asset_as_ref(asset)?;
+    require!(
+        ai.vault == ctx.accounts.mint_vault.key(),
+        DexError::InvalidVault
+    );
+    require!(
+        ai.program_signer == ctx.accounts.asset_program_signer.key(),
+        DexError::InvalidProgramSigner
+    );
+    require!(
+        ai.mint == ctx.accounts.recipient_mint_acc.mint,
+        DexError::InvalidUserMintAccount
+    );
+
+    let mint = ai.mint;
+    let nonce = ai.nonce;
+
+    let amount = dex.withdraw_protocol_fees(asset)?;
+
+    let seeds = &[
+        mint.as_ref(),
+        ctx.accounts.dex.to_account_info().key.as_ref(),
+        &[nonce],
+    ];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.mint_vault.to_account_info(),
+        to: ctx.accounts.recipient_mint_acc.to_account_info(),
+        authority: ctx.accounts.asset_program_signer.to_account_info(),
+    };
+
+    let signer_seeds = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.clone(),
+        cpi_accounts,
+        signer_seeds,
+    );
+
+    token::transfer(cpi_ctx, amount)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.withdraw_protocol_fees(
+        asset,
+        amount,
+        ctx.accounts.recipient_mint_acc.key().to_bytes(),
+    )
+}
@@ -5,7 +5,8 @@ use crate::{
         get_price, Dex, PriceFeed,
     },
     errors::{DexError, DexResult},
-    user::state::*,
+    user::{pay_referral_fee, state::*},
+    utils::{SafeMath, DELEGATE_CLOSE, NIL8},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, TokenAccount, Transfer};
@@ -25,14 +26,11 @@ pub struct ClosePosition<'info> {
     /// CHECK
     pub program_signer: AccountInfo<'info>,
 
-    #[account(
-        mut,
-        constraint = (user_mint_acc.owner == *authority.key)
-    )]
+    #[account(mut)]
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -51,10 +49,21 @@ pub struct ClosePosition<'info> {
     pub price_feed: AccountLoader<'info, PriceFeed>,
 }
 
-pub fn handler(ctx: Context<ClosePosition>, market: u8, long: bool, size: u64) -> DexResult {
+// Layout of remaining accounts:
+//  paying out another asset: collateral asset oracle, out asset oracle
+//  referred user: referral code, referrer user state
+pub fn handler(
+    ctx: Context<ClosePosition>,
+    market: u8,
+    long: bool,
+    size: u64,
+    out_asset: u8,
+    minimum_out: u64,
+) -> DexResult {
     let dex = &mut ctx.accounts.dex.load_mut()?;
 
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, false)?;
 
     require!(
         dex.event_queue == ctx.accounts.event_queue.key(),
@@ -72,30 +81,60 @@ pub fn handler(ctx: Context<ClosePosition>, market: u8, long: bool, size: u64) -
         DexError::InvalidMarketIndex
     );
 
-    let ai = if long {
-        &dex.assets[mi.asset_index as usize]
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_CLOSE,
+    )?;
+
+    // Stable collateral longs are settled in USDC
+    let base_collateral = long && !us.borrow().is_stable_collateral(market, long)?;
+    let asset = if base_collateral {
+        mi.asset_index
     } else {
-        &dex.assets[dex.usdc_asset_index as usize]
+        dex.usdc_asset_index
     };
+    let market_asset_index = mi.asset_index;
+    let ai = &dex.assets[asset as usize];
+    let borrow_fee_rate = ai.borrow_fee_rate;
+
+    // The vault, program signer and user mint account belong to the payout asset
+    let swap_out = out_asset != NIL8 && out_asset != asset;
+    let payout_asset = if swap_out { out_asset } else { asset };
+    if swap_out {
+        dex.check_asset_trading(payout_asset, false)?;
+    }
 
+    let poi = dex.asset_as_ref(payout_asset)?;
     require!(
-        ai.valid
-            && ai.vault == ctx.accounts.vault.key()
-            && ai.program_signer == ctx.accounts.program_signer.key(),
+        poi.vault == ctx.accounts.vault.key()
+            && poi.program_signer == ctx.accounts.program_signer.key(),
         DexError::InvalidMarketIndex
     );
     require!(
-        ai.mint == ctx.accounts.user_mint_acc.mint,
+        poi.mint == ctx.accounts.user_mint_acc.mint,
         DexError::InvalidUserMintAccount
     );
-    let mint = ai.mint;
+    let mint = poi.mint;
 
     let seeds = &[
         mint.as_ref(),
         ctx.accounts.dex.to_account_info().key.as_ref(),
-        &[ai.nonce],
+        &[poi.nonce],
     ];
 
+    let (swap_oracles, referral_accounts) = if swap_out {
+        require!(
+            ctx.remaining_accounts.len() >= 2,
+            DexError::InvalidRemainingAccounts
+        );
+        ctx.remaining_accounts.split_at(2)
+    } else {
+        ctx.remaining_accounts.split_at(0)
+    };
+
     let price_feed = &ctx.accounts.price_feed.load()?;
     // Get oracle price
     let price = get_price(
@@ -105,20 +144,80 @@ pub fn handler(ctx: Context<ClosePosition>, market: u8, long: bool, size: u64) -
         price_feed,
     )?;
 
-    let mfr = mi.get_fee_rates(ai.borrow_fee_rate);
+    // Funds only flow back to the owner
+    require_keys_eq!(
+        ctx.accounts.user_mint_acc.owner,
+        us.borrow().owner(),
+        DexError::InvalidUserMintAccount
+    );
+    let mfr = mi.get_fee_rates(
+        borrow_fee_rate,
+        dex.fee_discount(us.borrow().meta.vdx.fee_tier_amount()?),
+    );
 
     // User close position
-    let us = UserState::mount(&ctx.accounts.user_state, true)?;
     let (borrow, collateral, pnl, closed_size, close_fee, borrow_fee) = us
         .borrow_mut()
         .close_position(market, size, price, long, &mfr, false, false)?;
 
-    // Update market global position
-    dex.decrease_global_position(market, long, closed_size, collateral)?;
+    // Update market global position, its long collateral is kept in the market asset
+    let global_collateral = if long && !base_collateral {
+        0
+    } else {
+        collateral
+    };
+    dex.decrease_global_position(market, long, closed_size, global_collateral)?;
+
+    let withdrawable = dex.settle_pnl(
+        market,
+        base_collateral,
+        collateral,
+        borrow,
+        pnl,
+        close_fee,
+        borrow_fee,
+    )?;
+    // The market asset is valued at the close price, the others at their feed prices
+    let mut asset_prices = dex.feed_prices(price_feed)?;
+    asset_prices[market_asset_index as usize] = price;
+    us.borrow_mut().cover_shortfall(
+        dex,
+        market,
+        base_collateral,
+        collateral,
+        pnl,
+        close_fee.safe_add(borrow_fee)?,
+        &asset_prices,
+    )?;
+    pay_referral_fee(dex, &us, referral_accounts, asset, close_fee)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+    let user_state_key = ctx.accounts.user_state.key().to_bytes();
+
+    let payout = if swap_out && withdrawable > 0 {
+        let oracles = &[&swap_oracles[0], &swap_oracles[1]];
+        let (out, fee) = dex.swap(asset, payout_asset, withdrawable, true, oracles, price_feed)?;
+        require!(out >= minimum_out, DexError::InsufficientSwapOutput);
+
+        dex.swap_in(asset, withdrawable.safe_sub(fee)?, fee)?;
+        dex.swap_out(payout_asset, out)?;
+
+        event_queue.swap_asset(
+            user_state_key,
+            dex.assets[asset as usize].mint.to_bytes(),
+            mint.to_bytes(),
+            withdrawable,
+            out,
+            fee,
+        )?;
+
+        out
+    } else {
+        withdrawable
+    };
 
-    let withdrawable =
-        dex.settle_pnl(market, long, collateral, borrow, pnl, close_fee, borrow_fee)?;
-    if withdrawable > 0 {
+    if payout > 0 {
         let signer = &[&seeds[..]];
         let cpi_accounts = Transfer {
             from: ctx.accounts.vault.to_account_info(),
@@ -129,14 +228,10 @@ pub fn handler(ctx: Context<ClosePosition>, market: u8, long: bool, size: u64) -
         // let cpi_program = ctx.accounts.token_program.clone();
         let cpi_ctx =
             CpiContext::new_with_signer(ctx.accounts.token_program.clone(), cpi_accounts, signer);
-        token::transfer(cpi_ctx, withdrawable)?;
+        token::transfer(cpi_ctx, payout)?;
     }
 
     // Save to event queue
-    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
-        .map_err(|_| DexError::FailedMountEventQueue)?;
-
-    let user_state_key = ctx.accounts.user_state.key().to_bytes();
     event_queue.fill_position(
         user_state_key,
         us.borrow().get_position_status(),
@@ -150,6 +245,7 @@ pub fn handler(ctx: Context<ClosePosition>, market: u8, long: bool, size: u64) -
         close_fee,
         borrow_fee,
         pnl,
+        us.borrow().liquidation_price(market, long, &mfr)?,
     )?;
 
     Ok(())
@@ -21,7 +21,7 @@ pub struct LiquidatePosition<'info> {
     pub user: AccountInfo<'info>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), user.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
@@ -109,6 +109,7 @@ fn relay_native_mint_to_user(ctx: &Context<LiquidatePosition>, lamports: u64) ->
 
 // Layout of remaining accounts:
 //  offset 0 ~ m: order pool remaining pages
+//  offset m + 1 ~ n: cross-margin only, asset and market oracles as in refresh_aum
 pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexResult {
     let dex = &mut ctx.accounts.dex.load_mut()?;
 
@@ -118,6 +119,7 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
     );
 
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, false)?;
 
     require!(
         dex.event_queue == ctx.accounts.event_queue.key(),
@@ -134,13 +136,13 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
     );
 
     // Check remaining accounts
-    require_eq!(
-        mi.order_pool_remaining_pages_number as usize,
-        ctx.remaining_accounts.len(),
+    let pages = mi.order_pool_remaining_pages_number as usize;
+    require!(
+        pages <= ctx.remaining_accounts.len(),
         DexError::InvalidRemainingAccounts
     );
 
-    for i in 0..mi.order_pool_remaining_pages_number as usize {
+    for i in 0..pages {
         require_eq!(
             mi.order_pool_remaining_pages[i],
             ctx.remaining_accounts[i].key(),
@@ -151,7 +153,16 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
     let user_mint_acc =
         Account::<TokenAccount>::try_from_unchecked(&ctx.accounts.user_mint_acc).ok();
 
-    let (market_asset_index, mai) = if long {
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
+
+    // Stable collateral longs are settled in USDC
+    let base_collateral = long && !us.borrow().is_stable_collateral(market, long)?;
+    let (market_asset_index, mai) = if base_collateral {
         (mi.asset_index, &dex.assets[mi.asset_index as usize])
     } else {
         (
@@ -183,31 +194,65 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
         price_feed,
     )?;
 
-    let mfr = mi.get_fee_rates(mai.borrow_fee_rate);
+    let mfr = mi.get_fee_rates(mai.borrow_fee_rate, 0);
+
+    // A cross-margin account is liquidated on its equity across all positions
+    let cross_margin = us.borrow().is_cross_margin();
+    let asset_prices = if cross_margin {
+        let (asset_prices, market_prices) =
+            dex.oracle_prices(&ctx.remaining_accounts[pages..], price_feed)?;
+        us.borrow()
+            .require_cross_margin_liquidate(dex, &asset_prices, &market_prices)?;
+
+        asset_prices
+    } else {
+        require_eq!(
+            pages,
+            ctx.remaining_accounts.len(),
+            DexError::InvalidRemainingAccounts
+        );
+        us.borrow()
+            .require_position_liquidate(market, long, price, &mfr)?;
+
+        vec![]
+    };
 
     // User close position
-    let us = UserState::mount(&ctx.accounts.user_state, true)?;
     let size = us.borrow().get_position_size(market, long)?;
     let (borrow, collateral, pnl, _closed_size, close_fee, borrow_fee) = us
         .borrow_mut()
         .close_position(market, size, price, long, &mfr, true, false)?;
 
-    // Update market global position
-    dex.decrease_global_position(market, long, size, collateral)?;
+    // Update market global position, its long collateral is kept in the market asset
+    let global_collateral = if long && !base_collateral {
+        0
+    } else {
+        collateral
+    };
+    dex.decrease_global_position(market, long, size, global_collateral)?;
 
-    let withdrawable =
-        dex.settle_pnl(market, long, collateral, borrow, pnl, close_fee, borrow_fee)?;
+    let withdrawable = dex.settle_pnl(
+        market,
+        base_collateral,
+        collateral,
+        borrow,
+        pnl,
+        close_fee,
+        borrow_fee,
+    )?;
 
-    // Should the position be liquidated?
-    if withdrawable
-        > collateral
-            .safe_mul(mfr.liquidate_threshold as u64)?
-            .safe_div(100u128)? as u64
-    {
-        return Err(error!(DexError::RequireNoLiquidation));
-    }
+    us.borrow_mut().cover_shortfall(
+        dex,
+        market,
+        base_collateral,
+        collateral,
+        pnl,
+        close_fee.safe_add(borrow_fee)?,
+        &asset_prices,
+    )?;
 
-    if user_mint_acc.is_some() {
+    // The remainder of a cross-margin position keeps backing the other positions
+    if user_mint_acc.is_some() && !cross_margin {
         if let Some(acc) = user_mint_acc {
             if ctx.accounts.market_mint.key() == token::spl_token::native_mint::id() {
                 require!(
@@ -238,7 +283,7 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
     let order_book = OrderBook::mount(&ctx.accounts.order_book, true)?;
     let order_pool = PagedList::<Order>::mount(
         &ctx.accounts.order_pool_entry_page,
-        &ctx.remaining_accounts[0..],
+        &ctx.remaining_accounts[0..pages],
         ORDER_POOL_MAGIC_BYTE,
         MountMode::ReadWrite,
     )
@@ -288,6 +333,7 @@ pub fn handler(ctx: Context<LiquidatePosition>, market: u8, long: bool) -> DexRe
         close_fee,
         borrow_fee,
         pnl,
+        us.borrow().liquidation_price(market, long, &mfr)?,
     )?;
 
     Ok(())
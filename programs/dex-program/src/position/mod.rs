@@ -1,7 +1,13 @@
 pub mod close;
 pub mod liquidate;
 pub mod open;
+pub mod set_leverage;
+pub mod settle_borrow_fee;
+pub mod settle_delisted;
 
 pub use close::*;
 pub use liquidate::*;
 pub use open::*;
+pub use set_leverage::*;
+pub use settle_borrow_fee::*;
+pub use settle_delisted::*;
@@ -5,8 +5,8 @@ use crate::{
         get_price, Dex, PriceFeed,
     },
     errors::{DexError, DexResult},
-    user::state::*,
-    utils::{value, SafeMath, LEVERAGE_POW_DECIMALS},
+    user::{pay_referral_fee, state::*},
+    utils::{value, SafeMath, DELEGATE_OPEN, LEVERAGE_POW_DECIMALS},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, TokenAccount, Transfer};
@@ -46,7 +46,7 @@ pub struct OpenPosition<'info> {
     pub user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     #[account(mut)]
@@ -71,6 +71,7 @@ pub fn handler(
     long: bool,
     amount: u64,
     leverage: u32,
+    stable_collateral: bool,
 ) -> DexResult {
     let dex = &mut ctx.accounts.dex.load_mut()?;
 
@@ -80,6 +81,7 @@ pub fn handler(
     );
 
     require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, true)?;
 
     require!(
         dex.event_queue == ctx.accounts.event_queue.key(),
@@ -98,8 +100,9 @@ pub fn handler(
         DexError::InvalidLeverage
     );
 
-    // Read market asset info
-    let (market_asset_index, mai) = if long {
+    // Read market asset info, stable collateral longs keep USDC
+    let base_collateral = long && !stable_collateral;
+    let (market_asset_index, mai) = if base_collateral {
         (mi.asset_index, &dex.assets[mi.asset_index as usize])
     } else {
         (
@@ -133,10 +136,22 @@ pub fn handler(
     let market_mint_decimals = mai.decimals;
     let minimum_collateral = mi.minimum_collateral;
 
-    let mfr = mi.get_fee_rates(mai.borrow_fee_rate);
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_authority(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+        DELEGATE_OPEN,
+    )?;
+    let mfr = mi.get_fee_rates(
+        mai.borrow_fee_rate,
+        dex.fee_discount(us.borrow().meta.vdx.fee_tier_amount()?),
+    );
 
     // Read user input asset info
     let (input_asset_index, ai) = dex.find_asset_by_mint(ctx.accounts.in_mint.key())?;
+    dex.check_asset_trading(input_asset_index, true)?;
+    dex.check_asset_trading(market_asset_index, true)?;
 
     let actual_amount = if ai.mint == mai.mint {
         let cpi_accounts = Transfer {
@@ -190,7 +205,8 @@ pub fn handler(
     };
 
     // User open position
-    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow_mut()
+        .set_stable_collateral(market, long, stable_collateral)?;
     let (size, collateral, borrow, open_fee) =
         us.borrow_mut()
             .open_position(market, price, actual_amount, long, leverage, &mfr)?;
@@ -201,10 +217,18 @@ pub fn handler(
     );
 
     // Update asset info (collateral amount, borrow amount, fee)
-    dex.borrow_fund(market, long, collateral, borrow, open_fee)?;
+    dex.borrow_fund(market, base_collateral, collateral, borrow, open_fee)?;
+    pay_referral_fee(
+        dex,
+        &us,
+        ctx.remaining_accounts,
+        market_asset_index,
+        open_fee,
+    )?;
 
-    // Update market global position & volume
-    dex.increase_global_position(market, long, price, size, collateral)?;
+    // Update market global position & volume, its long collateral is kept in the market asset
+    let global_collateral = if stable_collateral { 0 } else { collateral };
+    dex.increase_global_position(market, long, price, size, global_collateral)?;
     dex.increase_volume(market, price, size)?;
 
     // Save to event queue
@@ -225,6 +249,7 @@ pub fn handler(
         open_fee,
         0,
         0,
+        us.borrow().liquidation_price(market, long, &mfr)?,
     )?;
 
     Ok(())
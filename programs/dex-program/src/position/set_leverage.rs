@@ -0,0 +1,187 @@
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, get_price, Dex, PriceFeed},
+    errors::{DexError, DexResult},
+    user::state::*,
+    utils::LEVERAGE_POW_DECIMALS,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+pub struct SetPositionLeverage<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub oracle: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// CHECK
+    pub program_signer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = (user_mint_acc.owner == *authority.key)
+    )]
+    pub user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK
+    #[account(mut, constraint = event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(executable, constraint = (token_program.key == &token::ID))]
+    pub token_program: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(owner = *program_id)]
+    pub price_feed: AccountLoader<'info, PriceFeed>,
+}
+
+pub fn handler(
+    ctx: Context<SetPositionLeverage>,
+    market: u8,
+    long: bool,
+    leverage: u32,
+) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    dex.check_market_trading(market, false)?;
+
+    require!(
+        dex.price_feed == ctx.accounts.price_feed.key(),
+        DexError::InvalidPriceFeed
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let mi = &dex.markets[market as usize];
+    require!(
+        mi.valid && mi.oracle == ctx.accounts.oracle.key(),
+        DexError::InvalidMarketIndex
+    );
+
+    require!(
+        leverage >= LEVERAGE_POW_DECIMALS && leverage <= mi.max_leverage,
+        DexError::InvalidLeverage
+    );
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    // Stable collateral longs keep USDC
+    let base_collateral = long && !us.borrow().is_stable_collateral(market, long)?;
+    let asset = if base_collateral {
+        mi.asset_index
+    } else {
+        dex.usdc_asset_index
+    };
+    dex.check_asset_trading(asset, false)?;
+
+    let ai = &dex.assets[asset as usize];
+    require!(
+        ai.valid
+            && ai.vault == ctx.accounts.vault.key()
+            && ai.program_signer == ctx.accounts.program_signer.key(),
+        DexError::InvalidMarketIndex
+    );
+    require!(
+        ai.mint == ctx.accounts.user_mint_acc.mint,
+        DexError::InvalidUserMintAccount
+    );
+    let mint = ai.mint;
+
+    let seeds = &[
+        mint.as_ref(),
+        ctx.accounts.dex.to_account_info().key.as_ref(),
+        &[ai.nonce],
+    ];
+
+    let price_feed = &ctx.accounts.price_feed.load()?;
+    let price = get_price(
+        mi.asset_index,
+        mi.oracle_source,
+        &ctx.accounts.oracle,
+        price_feed,
+    )?;
+
+    let mfr = mi.get_fee_rates(
+        ai.borrow_fee_rate,
+        dex.fee_discount(us.borrow().meta.vdx.fee_tier_amount()?),
+    );
+
+    let (added, refunded) = us
+        .borrow_mut()
+        .set_position_leverage(market, long, leverage, price, &mfr)?;
+
+    dex.adjust_collateral(market, long, base_collateral, added, refunded)?;
+
+    // Collateral of cross-margin accounts is taken from and kept in the account equity
+    let cross_margin = us.borrow().is_cross_margin();
+    if added > 0 && cross_margin {
+        us.borrow_mut().debit_asset(asset, added)?;
+    } else if added > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_mint_acc.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.clone(), cpi_accounts);
+        token::transfer(cpi_ctx, added)?;
+    }
+
+    if refunded > 0 {
+        if cross_margin {
+            us.borrow_mut().deposit_asset(asset, refunded)?;
+        } else {
+            let signer = &[&seeds[..]];
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.user_mint_acc.to_account_info(),
+                authority: ctx.accounts.program_signer.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.clone(),
+                cpi_accounts,
+                signer,
+            );
+            token::transfer(cpi_ctx, refunded)?;
+        }
+    }
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+    event_queue.set_position_leverage(
+        ctx.accounts.user_state.key().to_bytes(),
+        market,
+        long,
+        leverage,
+        price,
+        added,
+        refunded,
+        us.borrow().liquidation_price(market, long, &mfr)?,
+    )?;
+
+    Ok(())
+}
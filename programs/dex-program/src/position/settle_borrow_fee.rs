@@ -0,0 +1,72 @@
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, Dex},
+    errors::{DexError, DexResult},
+    user::state::*,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SettleBorrowFee<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub user: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint = event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Permissionless, keepers move the accrued borrow fee of a position from its collateral
+// to the pool fees so that LPs get paid before the position is closed.
+pub fn handler(ctx: Context<SettleBorrowFee>, market: u8, long: bool) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+    let mi = &dex.markets[market as usize];
+    require!(mi.valid, DexError::InvalidMarketIndex);
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
+
+    // Stable collateral longs are charged in USDC
+    let base_collateral = long && !us.borrow().is_stable_collateral(market, long)?;
+    let asset = if base_collateral {
+        mi.asset_index
+    } else {
+        dex.usdc_asset_index
+    };
+
+    let mfr = mi.get_fee_rates(dex.assets[asset as usize].borrow_fee_rate, 0);
+    let fee = us.borrow_mut().settle_borrow_fee(market, long, &mfr)?;
+    require!(fee > 0, DexError::InvalidAmount);
+
+    dex.collect_borrow_fee(market, long, base_collateral, fee)?;
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+    event_queue.settle_borrow_fee(
+        ctx.accounts.user_state.key().to_bytes(),
+        market,
+        long,
+        asset,
+        fee,
+    )
+}
@@ -0,0 +1,190 @@
+use crate::{
+    collections::{EventQueue, MountMode, OrderBook, PagedList},
+    dex::{
+        event::{AppendEvent, PositionAct},
+        Dex,
+    },
+    errors::{DexError, DexResult},
+    order::{select_side, Order},
+    user::state::*,
+    utils::ORDER_POOL_MAGIC_BYTE,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SettleDelisted<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub user: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= order_book.owner == program_id)]
+    pub order_book: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= order_pool_entry_page.owner == program_id)]
+    pub order_pool_entry_page: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Permissionless, cancels the user's orders and closes the positions of a delisted market
+// at its settle price. Refunds and payouts are credited to the user's asset balance.
+//
+// Layout of remaining accounts:
+//  offset 0 ~ m: order pool remaining pages
+pub fn handler(ctx: Context<SettleDelisted>, market: u8) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(market < dex.markets_number, DexError::InvalidMarketIndex);
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let mi = &dex.markets[market as usize];
+    require!(
+        mi.valid
+            && mi.order_book == ctx.accounts.order_book.key()
+            && mi.order_pool_entry_page == ctx.accounts.order_pool_entry_page.key(),
+        DexError::InvalidMarketIndex
+    );
+    require!(mi.settle_price > 0, DexError::NotDelisted);
+
+    require_eq!(
+        mi.order_pool_remaining_pages_number as usize,
+        ctx.remaining_accounts.len(),
+        DexError::InvalidRemainingAccounts
+    );
+
+    for i in 0..mi.order_pool_remaining_pages_number as usize {
+        require_eq!(
+            mi.order_pool_remaining_pages[i],
+            ctx.remaining_accounts[i].key(),
+            DexError::InvalidRemainingAccounts
+        );
+    }
+
+    let price = mi.settle_price;
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
+
+    // Drain orders
+    let order_book = OrderBook::mount(&ctx.accounts.order_book, true)?;
+    let order_pool = PagedList::<Order>::mount(
+        &ctx.accounts.order_pool_entry_page,
+        ctx.remaining_accounts,
+        ORDER_POOL_MAGIC_BYTE,
+        MountMode::ReadWrite,
+    )
+    .map_err(|_| DexError::FailedMountOrderPool)?;
+
+    let mut orders = us.borrow().collect_orders(market as usize, true);
+    orders.append(&mut us.borrow().collect_orders(market as usize, false));
+    for user_order_slot in orders {
+        let order_slot = us
+            .borrow()
+            .get_order_info(user_order_slot)
+            .map_err(|_| DexError::InvalidOrderSlot)?;
+
+        let (_, open, long, asset, size) = us
+            .borrow_mut()
+            .unlink_order(user_order_slot, true)
+            .map_err(|_| DexError::InvalidOrderSlot)?;
+
+        if open {
+            us.borrow_mut().deposit_asset(asset, size)?;
+        }
+
+        let order = order_pool
+            .from_index(order_slot)
+            .map_err(|_| DexError::InvalidOrderSlot)?;
+
+        require!(
+            order.in_use() && order.data.user_order_slot == user_order_slot,
+            DexError::InvalidOrderSlot
+        );
+
+        order_book.unlink_order(select_side(open, long), order, &order_pool)?;
+    }
+
+    // Close positions
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+    let user_state_key = ctx.accounts.user_state.key().to_bytes();
+
+    for long in [true, false] {
+        let size = us.borrow().get_position_size(market, long)?;
+        if size == 0 {
+            continue;
+        }
+
+        // Stable collateral longs are settled in USDC
+        let base_collateral = long && !us.borrow().is_stable_collateral(market, long)?;
+        let mi = &dex.markets[market as usize];
+        let market_asset_index = if base_collateral {
+            mi.asset_index
+        } else {
+            dex.usdc_asset_index
+        };
+        let mfr = mi.get_fee_rates(dex.assets[market_asset_index as usize].borrow_fee_rate, 0);
+
+        let (borrow, collateral, pnl, closed_size, close_fee, borrow_fee) = us
+            .borrow_mut()
+            .close_position(market, size, price, long, &mfr, false, false)?;
+
+        let global_collateral = if long && !base_collateral {
+            0
+        } else {
+            collateral
+        };
+        dex.decrease_global_position(market, long, closed_size, global_collateral)?;
+
+        let withdrawable = dex.settle_pnl(
+            market,
+            base_collateral,
+            collateral,
+            borrow,
+            pnl,
+            close_fee,
+            borrow_fee,
+        )?;
+        if withdrawable > 0 {
+            us.borrow_mut()
+                .deposit_asset(market_asset_index, withdrawable)?;
+        }
+
+        event_queue.fill_position(
+            user_state_key,
+            us.borrow().get_position_status(),
+            market,
+            PositionAct::Close,
+            long,
+            price,
+            closed_size,
+            collateral,
+            0,
+            close_fee,
+            borrow_fee,
+            pnl,
+            us.borrow().liquidation_price(market, long, &mfr)?,
+        )?;
+    }
+
+    Ok(())
+}
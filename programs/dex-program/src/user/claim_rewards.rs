@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, Dex, PriceFeed},
+    errors::{DexError, DexResult},
+    user::UserState,
+};
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub oracle: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// CHECK
+    pub program_signer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = (user_mint_acc.owner == *authority.key)
+    )]
+    pub user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK
+    #[account(executable, constraint = (token_program.key == &token::ID))]
+    pub token_program: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(owner = *program_id)]
+    pub price_feed: AccountLoader<'info, PriceFeed>,
+}
+
+pub fn handler(ctx: Context<ClaimRewards>, asset: u8) -> DexResult {
+    let mut dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    require!(
+        dex.price_feed == ctx.accounts.price_feed.key(),
+        DexError::InvalidPriceFeed
+    );
+
+    dex.check_asset_trading(asset, false)?;
+
+    let ai = dex.asset_as_ref(asset)?;
+    require!(
+        ai.vault == ctx.accounts.vault.key()
+            && ai.program_signer == ctx.accounts.program_signer.key(),
+        DexError::InvalidVault
+    );
+    require!(
+        ai.mint == ctx.accounts.user_mint_acc.mint,
+        DexError::InvalidUserMintAccount
+    );
+
+    let (mint, nonce) = (ai.mint, ai.nonce);
+    let seeds = &[
+        mint.as_ref(),
+        ctx.accounts.dex.to_account_info().key.as_ref(),
+        &[nonce],
+    ];
+    let price_feed = &ctx.accounts.price_feed.load()?;
+
+    dex.update_staking_pool(true)?;
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    let vlp_amount = us.borrow().claimable_rewards(dex)?;
+    require!(vlp_amount > 0, DexError::InvalidAmount);
+
+    // Redeem at current AUM before the rewards leave the VLP supply
+    let (withdraw, fee) =
+        dex.remove_liquidity(asset, vlp_amount, &ctx.accounts.oracle, price_feed)?;
+
+    let claimed = us.borrow_mut().claim_rewards(&mut dex)?;
+    require_eq!(claimed, vlp_amount, DexError::InvalidAmount);
+
+    if withdraw > 0 {
+        let signer = &[&seeds[..]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.user_mint_acc.to_account_info(),
+            authority: ctx.accounts.program_signer.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token::transfer(cpi_ctx, withdraw)?;
+    }
+
+    // Save to event queue
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    event_queue.claim_rewards(
+        ctx.accounts.user_state.key().to_bytes(),
+        asset,
+        vlp_amount,
+        withdraw,
+        fee,
+    )
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DexResult, user::state::*};
+
+#[derive(Accounts)]
+pub struct ClearUserDelegate<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ClearUserDelegate>) -> DexResult {
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    us.borrow_mut().set_delegate(Pubkey::default(), 0)?;
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::{
+    errors::{DexError, DexResult},
+    user::state::*,
+    utils::SafeMath,
+};
+use anchor_lang::{prelude::*, system_program};
+
+#[derive(Accounts)]
+pub struct CloseUserState<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CloseUserState>) -> DexResult {
+    {
+        let us = UserState::mount(&ctx.accounts.user_state, true)?;
+        us.borrow().check_owner(
+            &ctx.accounts.user_state.key(),
+            &ctx.accounts.dex.key(),
+            ctx.accounts.authority.key,
+        )?;
+        require!(us.borrow().is_closable(), DexError::UserStateNotEmpty);
+    }
+
+    // Return the rent to the owner
+    let user_state = ctx.accounts.user_state.to_account_info();
+    let authority = ctx.accounts.authority.to_account_info();
+
+    **authority.lamports.borrow_mut() = authority.lamports().safe_add(user_state.lamports())?;
+    **user_state.lamports.borrow_mut() = 0;
+
+    user_state.assign(&system_program::ID);
+    user_state.realloc(0, false)?;
+
+    Ok(())
+}
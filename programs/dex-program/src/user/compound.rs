@@ -1,12 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, MintTo, TokenAccount};
 
-use crate::{
-    dex::{Dex, PriceFeed},
-    errors::DexError,
-    errors::DexResult,
-    user::UserState,
-};
+use crate::{dex::Dex, errors::DexError, errors::DexResult, user::UserState};
 
 #[derive(Accounts)]
 pub struct Compound<'info> {
@@ -14,13 +9,9 @@ pub struct Compound<'info> {
     pub dex: AccountLoader<'info, Dex>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
-    /// CHECK
-    #[account(owner = *program_id)]
-    pub price_feed: AccountLoader<'info, PriceFeed>,
-
     /// CHECK:
     pub vdx_program_signer: AccountInfo<'info>,
 
@@ -40,43 +31,16 @@ pub struct Compound<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
-// Remaining accounts layout:
-// dex.assets.map({
-//   asset index price oracle account
-// })
-// dex.markets.map({
-//    market index price oracle account
-// })
 pub fn handler(ctx: Context<Compound>) -> DexResult {
     let mut dex = &mut ctx.accounts.dex.load_mut()?;
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
 
-    let assets_oracles_len = dex.assets.iter().filter(|a| a.valid).count();
-    let expected_oracles_len = assets_oracles_len + dex.markets.iter().filter(|m| m.valid).count();
-
-    require_eq!(
-        expected_oracles_len,
-        ctx.remaining_accounts.len(),
-        DexError::InvalidRemainingAccounts
-    );
-
-    require!(
-        dex.price_feed == ctx.accounts.price_feed.key(),
-        DexError::InvalidPriceFeed
-    );
-
-    let mut i = 0usize;
-    for asset in dex.assets.iter().filter(|a| a.valid) {
-        require!(
-            asset.oracle == ctx.remaining_accounts[i].key(),
-            DexError::InvalidRemainingAccounts
-        );
-        i += 1;
-    }
-
-    let price_feed = &ctx.accounts.price_feed.load()?;
-
-    dex.update_staking_pool(&ctx.remaining_accounts, price_feed, false)?;
+    dex.update_staking_pool(false)?;
 
     let vdx_vested = us.borrow_mut().stake_and_compound_vdx(&mut dex, 0)?;
     if vdx_vested > 0 {
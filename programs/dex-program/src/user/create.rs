@@ -1,7 +1,7 @@
 use crate::{errors::DexResult, user::state::*};
 use anchor_lang::prelude::*;
 
-const USER_ASSET_COUNT: u8 = 10;
+pub const USER_ASSET_COUNT: u8 = 10;
 
 #[derive(Accounts)]
 #[instruction(order_slot_count: u8, position_slot_count: u8, di_option_slot_count: u8)]
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+    user::{ReferralCode, UserState},
+};
+
+#[derive(Accounts)]
+#[instruction(code: [u8; 16])]
+pub struct CreateReferralCode<'info> {
+    #[account(
+        init,
+        seeds = [dex.key().as_ref(), b"referral", code.as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ReferralCode>()
+    )]
+    pub referral_code: AccountLoader<'info, ReferralCode>,
+
+    #[account(owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    #[account(owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateReferralCode>, code: [u8; 16]) -> DexResult {
+    require!(code[0] != 0, DexError::InvalidReferralCode);
+
+    // Rebates are credited to the referrer's user state
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    let rc = &mut ctx.accounts.referral_code.load_init()?;
+    rc.dex = ctx.accounts.dex.key();
+    rc.referrer = ctx.accounts.user_state.key();
+    rc.code = code;
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::{
+    errors::{DexError, DexResult},
+    user::{state::*, USER_ASSET_COUNT},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(sub_account: u8, order_slot_count: u8, position_slot_count: u8, di_option_slot_count: u8)]
+pub struct CreateSubAccount<'info> {
+    /// CHECK
+    #[account(
+        init,
+        seeds = [dex.key().as_ref(), authority.key.as_ref(), &[sub_account]],
+        bump,
+        payer = authority,
+        space = UserState::required_account_size(order_slot_count, position_slot_count, di_option_slot_count, USER_ASSET_COUNT)
+    )]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Sub-account 0 is the user state created by create_user_state
+pub fn handler(
+    ctx: Context<CreateSubAccount>,
+    sub_account: u8,
+    order_slot_count: u8,
+    position_slot_count: u8,
+    di_option_slot_count: u8,
+) -> DexResult {
+    require!(sub_account != 0, DexError::InvalidSubAccount);
+
+    let user_state = &mut ctx.accounts.user_state;
+
+    UserState::initialize(
+        user_state,
+        order_slot_count,
+        position_slot_count,
+        di_option_slot_count,
+        USER_ASSET_COUNT,
+        ctx.accounts.authority.key(),
+    )?;
+
+    let us = UserState::mount(user_state, true)?;
+    us.borrow_mut().set_sub_account(sub_account);
+
+    Ok(())
+}
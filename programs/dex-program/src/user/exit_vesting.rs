@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer};
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, Dex},
+    errors::DexError,
+    errors::DexResult,
+    user::UserState,
+    utils::SafeMath,
+};
+
+#[derive(Accounts)]
+pub struct ExitVesting<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    #[account(
+         mut,
+         constraint = (user_mint_acc.owner == *authority.key)
+     )]
+    user_mint_acc: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint= event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    /// CHECK:
+    pub vdx_program_signer: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account(mut)]
+    vdx_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Vault for locking asset
+    #[account(mut,constraint = vdx_vault.mint == vdx_mint.key() && vdx_vault.owner == vdx_program_signer.key()  @DexError::InvalidMint)]
+    vdx_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK
+    pub authority: Signer<'info>,
+
+    /// CHECK
+    #[account(executable, constraint = (token_program.key == &token::ID))]
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<ExitVesting>, amount: u64, newest_first: bool) -> DexResult {
+    let mut dex = &mut ctx.accounts.dex.load_mut()?;
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    require!(
+        dex.vdx_pool.mint == ctx.accounts.vdx_mint.key(),
+        DexError::InvalidMint
+    );
+
+    require!(
+        ctx.accounts.vdx_vault.key() == dex.vdx_pool.vault,
+        DexError::InvalidVault
+    );
+
+    require!(
+        ctx.accounts.vdx_program_signer.key() == dex.vdx_pool.program_signer,
+        DexError::InvalidProgramSigner
+    );
+
+    require!(
+        ctx.accounts.user_mint_acc.mint.key() == dex.vdx_pool.mint,
+        DexError::InvalidUserMintAccount
+    );
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    dex.update_staking_pool(true)?;
+
+    let (vdx_vested, released, forfeited) =
+        us.borrow_mut()
+            .exit_vesting(&mut dex, amount, newest_first)?;
+
+    let seeds = &[
+        dex.vdx_pool.mint.as_ref(),
+        ctx.accounts.dex.to_account_info().key.as_ref(),
+        &[dex.vdx_pool.nonce],
+    ];
+    let signer = &[&seeds[..]];
+    let mint_amount = vdx_vested.safe_add(released)?;
+    if mint_amount > 0 {
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.vdx_mint.to_account_info(),
+            to: ctx.accounts.vdx_vault.to_account_info(),
+            authority: ctx.accounts.vdx_program_signer.to_account_info(),
+        };
+        let cpi_ctx =
+            CpiContext::new_with_signer(ctx.accounts.token_program.clone(), cpi_accounts, signer);
+
+        token::mint_to(cpi_ctx, mint_amount)?;
+    }
+
+    if released > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vdx_vault.to_account_info(),
+            to: ctx.accounts.user_mint_acc.to_account_info(),
+            authority: ctx.accounts.vdx_program_signer.to_account_info(),
+        };
+
+        let cpi_ctx =
+            CpiContext::new_with_signer(ctx.accounts.token_program.clone(), cpi_accounts, signer);
+        token::transfer(cpi_ctx, released)?;
+    }
+
+    // Save to event queue
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+
+    let user_state_key = ctx.accounts.user_state.key().to_bytes();
+    event_queue.exit_vesting(user_state_key, amount, released, forfeited, newest_first)
+}
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DexResult, user::stake_vdx};
+
+use super::StakeVdx;
+
+// Locked stakes take the same accounts as plain stakes. A top-up joins the
+// current lock and keeps its expiry, a tier that would run past it is rejected.
+pub fn handler(ctx: Context<StakeVdx>, amount: u64, lock_tier: u8) -> DexResult {
+    stake_vdx::stake(ctx, amount, Some(lock_tier))
+}
@@ -1,11 +1,38 @@
+pub mod claim_rewards;
+pub mod clear_user_delegate;
+pub mod close;
 pub mod compound;
 pub mod create;
+pub mod create_referral_code;
+pub mod create_sub_account;
+pub mod exit_vesting;
+pub mod lock_vdx;
 pub mod redeem_vdx;
+pub mod referral;
+pub mod release_expired_lock;
+pub mod resize;
+pub mod set_margin_mode;
+pub mod set_referral;
+pub mod set_user_delegate;
 pub mod stake_vdx;
 pub mod state;
+pub mod transfer_asset;
 
+pub use claim_rewards::*;
+pub use clear_user_delegate::*;
+pub use close::*;
 pub use compound::*;
 pub use create::*;
+pub use create_referral_code::*;
+pub use create_sub_account::*;
+pub use exit_vesting::*;
 pub use redeem_vdx::*;
+pub use referral::*;
+pub use release_expired_lock::*;
+pub use resize::*;
+pub use set_margin_mode::*;
+pub use set_referral::*;
+pub use set_user_delegate::*;
 pub use stake_vdx::*;
 pub use state::*;
+pub use transfer_asset::*;
@@ -3,7 +3,7 @@ use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer};
 
 use crate::{
     collections::EventQueue,
-    dex::{event::AppendEvent, Dex, PriceFeed},
+    dex::{event::AppendEvent, Dex},
     errors::DexError,
     errors::DexResult,
     user::UserState,
@@ -22,17 +22,13 @@ pub struct RedeemVdx<'info> {
     user_mint_acc: Box<Account<'info, TokenAccount>>,
 
     /// CHECK
-    #[account(mut, seeds = [dex.key().as_ref(), authority.key().as_ref()], bump, owner = *program_id)]
+    #[account(mut, owner = *program_id)]
     pub user_state: UncheckedAccount<'info>,
 
     /// CHECK
     #[account(mut, constraint= event_queue.owner == program_id)]
     pub event_queue: UncheckedAccount<'info>,
 
-    /// CHECK
-    #[account(owner = *program_id)]
-    pub price_feed: AccountLoader<'info, PriceFeed>,
-
     /// CHECK:
     pub vdx_program_signer: AccountInfo<'info>,
 
@@ -52,39 +48,14 @@ pub struct RedeemVdx<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
-// Remaining accounts layout:
-// dex.assets.map({
-//   asset index price oracle account
-// })
-// dex.markets.map({
-//    market index price oracle account
-// })
 pub fn handler(ctx: Context<RedeemVdx>, amount: u64) -> DexResult {
     let mut dex = &mut ctx.accounts.dex.load_mut()?;
     let us = UserState::mount(&ctx.accounts.user_state, true)?;
-
-    let assets_oracles_len = dex.assets.iter().filter(|a| a.valid).count();
-    let expected_oracles_len = assets_oracles_len + dex.markets.iter().filter(|m| m.valid).count();
-
-    require_eq!(
-        expected_oracles_len,
-        ctx.remaining_accounts.len(),
-        DexError::InvalidRemainingAccounts
-    );
-
-    require!(
-        dex.price_feed == ctx.accounts.price_feed.key(),
-        DexError::InvalidPriceFeed
-    );
-
-    let mut i = 0usize;
-    for asset in dex.assets.iter().filter(|a| a.valid) {
-        require!(
-            asset.oracle == ctx.remaining_accounts[i].key(),
-            DexError::InvalidRemainingAccounts
-        );
-        i += 1;
-    }
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
 
     require!(
         dex.vdx_pool.mint == ctx.accounts.vdx_mint.key(),
@@ -111,9 +82,7 @@ pub fn handler(ctx: Context<RedeemVdx>, amount: u64) -> DexResult {
         DexError::InvalidEventQueue
     );
 
-    let price_feed = &ctx.accounts.price_feed.load()?;
-
-    dex.update_staking_pool(&ctx.remaining_accounts, price_feed, true)?;
+    dex.update_staking_pool(true)?;
 
     let (vdx_vested, redeemable) = us.borrow_mut().redeem_vdx(&mut dex, amount)?;
 
@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+    user::UserState,
+};
+
+#[account(zero_copy)]
+pub struct ReferralCode {
+    pub dex: Pubkey,
+    pub referrer: Pubkey, // User state of the referrer
+    pub code: [u8; 16],
+}
+
+fn referrer_of<'a, 'info>(
+    referral_code: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> DexResult<&'a AccountInfo<'info>> {
+    require!(remaining_accounts.len() >= 2, DexError::InvalidReferralCode);
+    let (code, referrer) = (&remaining_accounts[0], &remaining_accounts[1]);
+    require_keys_eq!(code.key(), referral_code, DexError::InvalidReferralCode);
+
+    let loader = AccountLoader::<ReferralCode>::try_from(code)?;
+    require_keys_eq!(
+        loader.load()?.referrer,
+        referrer.key(),
+        DexError::InvalidReferralCode
+    );
+
+    Ok(referrer)
+}
+
+fn credit_referral_fee(
+    dex: &mut Dex,
+    us: &RefCell<UserState>,
+    referrer: &AccountInfo,
+    asset: u8,
+    fee: u64,
+) -> DexResult {
+    // A referrer that closed its user state gets no rebate
+    let with_rebate = referrer.owner == &crate::id() && !referrer.data_is_empty();
+    let (discount, rebate) = dex.take_referral_fee(asset, fee, with_rebate)?;
+    if discount > 0 {
+        us.borrow_mut().deposit_asset(asset, discount)?;
+    }
+
+    if rebate > 0 {
+        let referrer_us = UserState::mount(referrer, true)?;
+        referrer_us.borrow_mut().deposit_asset(asset, rebate)?;
+    }
+
+    Ok(())
+}
+
+// Trades of a referred user expect the referral code and the referrer's user
+// state as the first two remaining accounts. The discount is credited to the
+// trader and the rebate to the referrer, both withdrawable by withdraw_asset.
+pub fn pay_referral_fee(
+    dex: &mut Dex,
+    us: &RefCell<UserState>,
+    remaining_accounts: &[AccountInfo],
+    asset: u8,
+    fee: u64,
+) -> DexResult {
+    let referral_code = us.borrow().meta.referral_code;
+    if referral_code == Pubkey::default() || fee == 0 {
+        return Ok(());
+    }
+
+    let referrer = referrer_of(referral_code, remaining_accounts)?;
+    credit_referral_fee(dex, us, referrer, asset, fee)
+}
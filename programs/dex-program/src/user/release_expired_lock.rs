@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    collections::EventQueue,
+    dex::{event::AppendEvent, Dex},
+    errors::{DexError, DexResult},
+    user::state::*,
+};
+
+#[derive(Accounts)]
+pub struct ReleaseExpiredLock<'info> {
+    #[account(mut, owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    /// CHECK
+    pub user: AccountInfo<'info>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, constraint = event_queue.owner == program_id)]
+    pub event_queue: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Permissionless, moves a matured VDX lock back to the plain stake so that it stops
+// earning the boosted weight even if its owner never touches the stake again.
+pub fn handler(ctx: Context<ReleaseExpiredLock>) -> DexResult {
+    let dex = &mut ctx.accounts.dex.load_mut()?;
+
+    require!(
+        dex.event_queue == ctx.accounts.event_queue.key(),
+        DexError::InvalidEventQueue
+    );
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.user.key,
+    )?;
+
+    dex.update_staking_pool(false)?;
+
+    let released = us.borrow_mut().release_expired_lock(dex)?;
+    require!(released > 0, DexError::InvalidAmount);
+
+    let mut event_queue = EventQueue::mount(&ctx.accounts.event_queue, true)
+        .map_err(|_| DexError::FailedMountEventQueue)?;
+    event_queue.release_lock(ctx.accounts.user_state.key().to_bytes(), released)
+}
@@ -0,0 +1,68 @@
+use crate::{errors::DexResult, user::state::*};
+use anchor_lang::{prelude::*, system_program};
+
+#[derive(Accounts)]
+pub struct ResizeUserState<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Slot counts can only grow, slot indices referenced by orders are preserved
+pub fn handler(
+    ctx: Context<ResizeUserState>,
+    order_slot_count: u8,
+    position_slot_count: u8,
+    di_option_slot_count: u8,
+    asset_slot_count: u8,
+) -> DexResult {
+    {
+        let us = UserState::mount(&ctx.accounts.user_state, true)?;
+        us.borrow().check_owner(
+            &ctx.accounts.user_state.key(),
+            &ctx.accounts.dex.key(),
+            ctx.accounts.authority.key,
+        )?;
+    }
+
+    let user_state = ctx.accounts.user_state.to_account_info();
+    let new_size = UserState::required_account_size(
+        order_slot_count,
+        position_slot_count,
+        di_option_slot_count,
+        asset_slot_count,
+    );
+
+    if new_size > user_state.data_len() {
+        let rent = Rent::get()?.minimum_balance(new_size);
+        let lamports = user_state.lamports();
+        if rent > lamports {
+            let cpi_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: user_state.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, rent - lamports)?;
+        }
+
+        user_state.realloc(new_size, false)?;
+    }
+
+    UserState::resize(
+        &user_state,
+        order_slot_count,
+        position_slot_count,
+        di_option_slot_count,
+        asset_slot_count,
+    )
+}
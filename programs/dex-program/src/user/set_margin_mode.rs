@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DexResult, user::state::*};
+
+#[derive(Accounts)]
+pub struct SetMarginMode<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// In cross-margin mode free asset balances back all positions, and the account is
+// liquidated on its total equity. The mode can only change without open positions.
+pub fn handler(ctx: Context<SetMarginMode>, cross_margin: bool) -> DexResult {
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    us.borrow_mut().set_cross_margin(cross_margin)?;
+
+    Ok(())
+}
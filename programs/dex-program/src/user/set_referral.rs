@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dex::Dex,
+    errors::{DexError, DexResult},
+    user::{ReferralCode, UserState},
+};
+
+#[derive(Accounts)]
+pub struct SetReferral<'info> {
+    #[account(owner = *program_id)]
+    pub dex: AccountLoader<'info, Dex>,
+
+    pub referral_code: AccountLoader<'info, ReferralCode>,
+
+    /// CHECK: User state of the referrer
+    #[account(owner = *program_id)]
+    pub referrer: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// A user can be linked to a referral code only once, and never to one of its own sub-accounts
+pub fn handler(ctx: Context<SetReferral>) -> DexResult {
+    let rc = ctx.accounts.referral_code.load()?;
+    require_keys_eq!(
+        rc.dex,
+        ctx.accounts.dex.key(),
+        DexError::InvalidReferralCode
+    );
+    require_keys_eq!(
+        rc.referrer,
+        ctx.accounts.referrer.key(),
+        DexError::InvalidReferralCode
+    );
+
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    let referrer_us = UserState::mount(&ctx.accounts.referrer, true)?;
+    require_keys_neq!(
+        referrer_us.borrow().meta.owner,
+        us.borrow().meta.owner,
+        DexError::InvalidReferralCode
+    );
+    us.borrow_mut()
+        .set_referral_code(ctx.accounts.referral_code.key())?;
+
+    Ok(())
+}
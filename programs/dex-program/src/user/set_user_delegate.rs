@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::DexResult, user::state::*};
+
+#[derive(Accounts)]
+pub struct SetUserDelegate<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub delegate: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Permissions are a mask of DELEGATE_OPEN, DELEGATE_CLOSE and DELEGATE_ORDER.
+// A delegate can never withdraw funds.
+pub fn handler(ctx: Context<SetUserDelegate>, permissions: u8) -> DexResult {
+    let us = UserState::mount(&ctx.accounts.user_state, true)?;
+    us.borrow().check_owner(
+        &ctx.accounts.user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+    us.borrow_mut()
+        .set_delegate(ctx.accounts.delegate.key(), permissions)?;
+
+    Ok(())
+}
@@ -6,9 +6,13 @@ use crate::dual_invest::DIOption;
 use crate::errors::{DexError, DexResult};
 
 use crate::utils::{
-    time::get_timestamp, SafeMath, NIL32, SECONDS_PER_DAY, USER_STATE_MAGIC_NUMBER,
+    time::get_timestamp, ISafeAddSub, ISafeMath, SafeMath, NIL32, NIL8, SECONDS_PER_DAY,
+    USER_STATE_MAGIC_NUMBER,
+};
+use crate::utils::{
+    DELEGATE_ALL, ES_VDX_EXIT_PENALTY_PERCENTAGE, MASK_DI_OPTION, MASK_PERP_POSITION,
+    VESTING_PERIOD,
 };
-use crate::utils::{MASK_DI_OPTION, MASK_PERP_POSITION, VESTING_PERIOD};
 use anchor_lang::prelude::*;
 use std::mem;
 
@@ -29,7 +33,11 @@ pub struct MetaInfo {
     pub position_slot_count: u8,
     pub di_option_slot_count: u8,
     pub asset_slot_count: u8,
-    reserved: [u8; 64],
+    pub referral_code: Pubkey,
+    pub delegate_permissions: u8,
+    pub sub_account: u8,
+    pub cross_margin: bool,
+    reserved: [u8; 29],
 }
 
 #[repr(C)]
@@ -204,6 +212,53 @@ impl VestingMint {
         Ok(vested)
     }
 
+    // Take still-vesting esVDX out of the ring, must be called right after `roll`.
+    // Partially taken days are rescaled so that they keep vesting linearly.
+    pub fn exit(&mut self, amount: u64, newest_first: bool) -> DexResult<u64> {
+        if self.last_day == -1 || amount == 0 {
+            return Ok(0);
+        }
+
+        let (mut offset, end) = if newest_first {
+            (self.last_day_offset, self.first_day_offset)
+        } else {
+            (self.first_day_offset, self.last_day_offset)
+        };
+
+        let mut left = amount;
+        loop {
+            let age = (self.last_day_offset + VESTING_PERIOD - offset) % VESTING_PERIOD;
+            let remaining = self.amounts[offset as usize].safe_sub(self.vest(offset, age)?)?;
+
+            if remaining <= left {
+                self.amounts[offset as usize] = 0;
+                left -= remaining;
+            } else {
+                self.amounts[offset as usize] = (remaining - left)
+                    .safe_mul(VESTING_PERIOD as u64)?
+                    .safe_div((VESTING_PERIOD - age) as u128)?
+                    as u64;
+                left = 0;
+            }
+
+            if left == 0 || offset == end {
+                break;
+            }
+
+            offset = if newest_first {
+                (offset + VESTING_PERIOD - 1) % VESTING_PERIOD
+            } else {
+                (offset + 1) % VESTING_PERIOD
+            };
+        }
+
+        amount.safe_sub(left)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.amounts.iter().all(|amount| *amount == 0)
+    }
+
     #[cfg(feature = "client-support")]
     pub fn vesting(&self) -> DexResult<u64> {
         let mut vesting = 0u64;
@@ -324,7 +379,7 @@ impl VestingMint {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct UserOrder {
     pub list_time: i64,
     pub size: u64, // Refer to collateral size for opening position
@@ -337,7 +392,10 @@ pub struct UserOrder {
     pub open: bool,
     pub asset: u8,
     pub market: u8,
-    padding: [u8; 20],
+    pub out_asset: u8,
+    _align: [u8; 3],
+    pub minimum_out: u64,
+    padding: [u8; 8],
 }
 
 impl UserOrder {
@@ -359,6 +417,8 @@ impl UserOrder {
         self.market = market;
         self.asset = asset;
         self.open = true;
+        self.out_asset = NIL8;
+        self.minimum_out = 0;
 
         self.list_time = get_timestamp()?;
         Ok(())
@@ -370,6 +430,8 @@ impl UserOrder {
         self.long = long;
         self.market = market;
         self.open = false;
+        self.out_asset = NIL8;
+        self.minimum_out = 0;
 
         self.list_time = get_timestamp()?;
         Ok(())
@@ -426,6 +488,28 @@ impl UserPosition {
         }
     }
 
+    pub fn settle_borrow_fee(&mut self, long: bool, mfr: &MarketFeeRates) -> DexResult<u64> {
+        if long {
+            self.long.settle_borrow_fee(mfr)
+        } else {
+            self.short.settle_borrow_fee(mfr)
+        }
+    }
+
+    pub fn set_leverage(
+        &mut self,
+        long: bool,
+        leverage: u32,
+        price: u64,
+        mfr: &MarketFeeRates,
+    ) -> DexResult<(u64, u64)> {
+        if long {
+            self.long.set_leverage(leverage, price, mfr)
+        } else {
+            self.short.set_leverage(leverage, price, mfr)
+        }
+    }
+
     pub fn sub_closing(&mut self, long: bool, closing_size: u64) -> DexResult {
         if long {
             self.long.sub_closing(closing_size)
@@ -567,6 +651,91 @@ impl<'a> UserState<'a> {
         Ok(())
     }
 
+    // Grows the pools of an account that has already been reallocated to the size
+    // required by the new slot counts. Pools are moved towards the end starting with
+    // the last one, so every slot keeps its index.
+    pub fn resize(
+        account: &'a AccountInfo,
+        max_order_count: u8,
+        max_position_count: u8,
+        max_di_option_count: u8,
+        max_asset_count: u8,
+    ) -> DexResult {
+        let required_size = Self::required_account_size(
+            max_order_count,
+            max_position_count,
+            max_di_option_count,
+            max_asset_count,
+        );
+        require!(
+            required_size <= account.data_len(),
+            DexError::InvalidSlotCount
+        );
+
+        let data_ptr = match account.try_borrow_mut_data() {
+            Ok(p) => RefMut::map(p, |data| *data).as_mut_ptr(),
+            Err(_) => return Err(error!(DexError::FailedMountAccount)),
+        };
+
+        let meta = unsafe { &mut *(data_ptr as *mut MetaInfo) };
+        require!(
+            meta.magic == USER_STATE_MAGIC_NUMBER
+                && max_order_count >= meta.order_slot_count
+                && max_position_count >= meta.position_slot_count
+                && max_di_option_count >= meta.di_option_slot_count
+                && max_asset_count >= meta.asset_slot_count,
+            DexError::InvalidSlotCount
+        );
+
+        let old_lens = [
+            SmallList::<UserOrder>::required_data_len(meta.order_slot_count),
+            SmallList::<UserPosition>::required_data_len(meta.position_slot_count),
+            SmallList::<UserDIOption>::required_data_len(meta.di_option_slot_count),
+            SmallList::<UserAsset>::required_data_len(meta.asset_slot_count),
+        ];
+        let new_lens = [
+            SmallList::<UserOrder>::required_data_len(max_order_count),
+            SmallList::<UserPosition>::required_data_len(max_position_count),
+            SmallList::<UserDIOption>::required_data_len(max_di_option_count),
+            SmallList::<UserAsset>::required_data_len(max_asset_count),
+        ];
+
+        let mut old_offsets = [mem::size_of::<MetaInfo>(); 4];
+        let mut new_offsets = [mem::size_of::<MetaInfo>(); 4];
+        for i in 1..4 {
+            old_offsets[i] = old_offsets[i - 1] + old_lens[i - 1];
+            new_offsets[i] = new_offsets[i - 1] + new_lens[i - 1];
+        }
+
+        for i in (0..4).rev() {
+            unsafe {
+                std::ptr::copy(
+                    data_ptr.add(old_offsets[i]),
+                    data_ptr.add(new_offsets[i]),
+                    old_lens[i],
+                );
+                std::ptr::write_bytes(
+                    data_ptr.add(new_offsets[i] + old_lens[i]),
+                    0,
+                    new_lens[i] - old_lens[i],
+                );
+            }
+        }
+
+        meta.order_slot_count = max_order_count;
+        meta.position_slot_count = max_position_count;
+        meta.di_option_slot_count = max_di_option_count;
+        meta.asset_slot_count = max_asset_count;
+
+        let us = Self::mount_internal(data_ptr, account.data_len(), true)?;
+        us.borrow().order_pool.grow(max_order_count)?;
+        us.borrow().position_pool.grow(max_position_count)?;
+        us.borrow().di_option_pool.grow(max_di_option_count)?;
+        us.borrow().asset_pool.grow(max_asset_count)?;
+
+        Ok(())
+    }
+
     fn mount_internal(
         data_ptr: *mut u8,
         data_size: usize,
@@ -647,23 +816,7 @@ impl<'a> UserState<'a> {
         market_price: u64,
         mfr: &MarketFeeRates,
     ) -> DexResult {
-        let position = self.find_or_new_position(market, false)?;
-        let (_, collateral, pnl, _, close_fee, borrow_fee) =
-            position
-                .data
-                .close(u64::MAX, market_price, long, mfr, true, false)?;
-
-        if pnl < 0 {
-            let loss = (pnl.abs() as u64) + close_fee + borrow_fee;
-            if loss
-                >= collateral
-                    .safe_mul((100 - mfr.liquidate_threshold) as u64)?
-                    .safe_div(100u128)? as u64
-            {
-                return Ok(());
-            }
-        }
-        Err(error!(DexError::RequireNoLiquidation))
+        self.require_position_liquidate(market, long, market_price, mfr)
     }
 
     #[inline]
@@ -681,6 +834,279 @@ impl<'a> UserState<'a> {
         self.meta.user_list_index = index;
     }
 
+    // Sub-account 0 keeps the original `[dex, owner]` seeds, so existing user states
+    // stay valid. Other sub-accounts append their index.
+    pub fn find_address(dex: &Pubkey, owner: &Pubkey, sub_account: u8) -> (Pubkey, u8) {
+        if sub_account == 0 {
+            Pubkey::find_program_address(&[dex.as_ref(), owner.as_ref()], &crate::id())
+        } else {
+            Pubkey::find_program_address(
+                &[dex.as_ref(), owner.as_ref(), &[sub_account]],
+                &crate::id(),
+            )
+        }
+    }
+
+    pub fn check_address(&self, user_state: &Pubkey, dex: &Pubkey) -> DexResult {
+        let (expected, _) = Self::find_address(dex, &self.meta.owner, self.meta.sub_account);
+        require_keys_eq!(*user_state, expected, DexError::InvalidUser);
+
+        Ok(())
+    }
+
+    pub fn check_owner(&self, user_state: &Pubkey, dex: &Pubkey, owner: &Pubkey) -> DexResult {
+        self.check_address(user_state, dex)?;
+        require_keys_eq!(self.meta.owner, *owner, DexError::InvalidUser);
+
+        Ok(())
+    }
+
+    // The owner has full access, a delegate only what its permissions grant. Without
+    // the PDA seeds constraint the user state is checked to be derived from its owner.
+    pub fn check_authority(
+        &self,
+        user_state: &Pubkey,
+        dex: &Pubkey,
+        authority: &Pubkey,
+        permissions: u8,
+    ) -> DexResult {
+        self.check_address(user_state, dex)?;
+
+        if self.meta.owner == *authority {
+            return Ok(());
+        }
+
+        require!(
+            self.meta.delegate == *authority
+                && permissions != 0
+                && self.meta.delegate_permissions & permissions == permissions,
+            DexError::InvalidUserDelegate
+        );
+
+        Ok(())
+    }
+
+    pub fn set_delegate(&mut self, delegate: Pubkey, permissions: u8) -> DexResult {
+        require!(
+            permissions & !DELEGATE_ALL == 0,
+            DexError::InvalidUserDelegate
+        );
+        require!(
+            (delegate == Pubkey::default()) == (permissions == 0) && delegate != self.meta.owner,
+            DexError::InvalidUserDelegate
+        );
+
+        self.meta.delegate = delegate;
+        self.meta.delegate_permissions = permissions;
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn owner(&self) -> Pubkey {
+        self.meta.owner
+    }
+
+    #[inline]
+    pub fn sub_account(&self) -> u8 {
+        self.meta.sub_account
+    }
+
+    #[inline]
+    pub fn set_sub_account(&mut self, sub_account: u8) {
+        self.meta.sub_account = sub_account;
+    }
+
+    #[inline]
+    pub fn is_cross_margin(&self) -> bool {
+        self.meta.cross_margin
+    }
+
+    pub fn set_cross_margin(&mut self, cross_margin: bool) -> DexResult {
+        require!(!self.has_position(), DexError::MarginModeLocked);
+        self.meta.cross_margin = cross_margin;
+
+        Ok(())
+    }
+
+    // Account equity and maintenance margin of a cross-margin account, both in USD.
+    // Equity is the value of the free asset balances plus every position's collateral,
+    // PnL and liquidation fees. Prices are indexed as returned by Dex::oracle_prices.
+    pub fn cross_margin_status(
+        &self,
+        dex: &Dex,
+        asset_prices: &[u64],
+        market_prices: &[u64],
+    ) -> DexResult<(i64, u64)> {
+        let value = |asset: u8, amount: i64| -> DexResult<i64> {
+            require!(
+                (asset as usize) < asset_prices.len() && asset_prices[asset as usize] > 0,
+                DexError::InvalidOracle
+            );
+            let decimals = dex.assets[asset as usize].decimals as u32;
+
+            (amount as i128)
+                .i_safe_mul(asset_prices[asset as usize] as i128)?
+                .i_safe_div(10i128.pow(decimals))
+                .map(|v| v as i64)
+        };
+
+        let mut equity = 0i64;
+        let mut maintenance = 0i64;
+
+        for slot in self.asset_pool.into_iter() {
+            if slot.data.amount > 0 {
+                equity = equity.i_safe_add(value(slot.data.asset, slot.data.amount as i64)?)?;
+            }
+        }
+
+        for slot in self.position_pool.into_iter() {
+            let market = slot.data.market;
+            require!(
+                (market as usize) < market_prices.len(),
+                DexError::InvalidMarketIndex
+            );
+            let mi = &dex.markets[market as usize];
+
+            for p in [slot.data.long, slot.data.short].iter() {
+                if p.size == 0 {
+                    continue;
+                }
+
+                let asset = if p.base_collateral() {
+                    mi.asset_index
+                } else {
+                    dex.usdc_asset_index
+                };
+                let mfr = mi.get_fee_rates(dex.assets[asset as usize].borrow_fee_rate, 0);
+
+                let mut position = *p;
+                position.closing_size = 0;
+                let (_, collateral, pnl, _, close_fee, borrow_fee) =
+                    position.close(u64::MAX, market_prices[market as usize], &mfr, true, false)?;
+
+                let remain = (collateral as i64)
+                    .i_safe_add(pnl)?
+                    .i_safe_sub(close_fee.safe_add(borrow_fee)? as i64)?;
+                equity = equity.i_safe_add(value(asset, remain)?)?;
+
+                let margin = collateral
+                    .safe_mul(mfr.liquidate_threshold as u64)?
+                    .safe_div(100u128)? as i64;
+                maintenance = maintenance.i_safe_add(value(asset, margin)?)?;
+            }
+        }
+
+        Ok((equity, maintenance as u64))
+    }
+
+    pub fn require_cross_margin_liquidate(
+        &self,
+        dex: &Dex,
+        asset_prices: &[u64],
+        market_prices: &[u64],
+    ) -> DexResult {
+        let (equity, maintenance) = self.cross_margin_status(dex, asset_prices, market_prices)?;
+        require!(equity <= maintenance as i64, DexError::RequireNoLiquidation);
+
+        Ok(())
+    }
+
+    // A cross-margin position can lose more than its collateral. The shortfall is taken
+    // from the free balance of the same asset first, then from the other free balances
+    // valued at `asset_prices` (indexed by asset, unpriced assets are skipped). What is
+    // taken joins the pool's liquidity. Returns the covered part of the shortfall.
+    #[allow(clippy::too_many_arguments)]
+    pub fn cover_shortfall(
+        &mut self,
+        dex: &mut Dex,
+        market: u8,
+        base_collateral: bool,
+        collateral: u64,
+        pnl: i64,
+        fee: u64,
+        asset_prices: &[u64],
+    ) -> DexResult<u64> {
+        let remain = (collateral as i64)
+            .i_safe_add(pnl)?
+            .i_safe_sub(fee as i64)?;
+        if !self.meta.cross_margin || remain >= 0 {
+            return Ok(0);
+        }
+
+        let asset = if base_collateral {
+            dex.markets[market as usize].asset_index
+        } else {
+            dex.usdc_asset_index
+        };
+        let shortfall = i64::abs(remain) as u64;
+        let mut uncovered = shortfall;
+
+        if let Ok(asset_slot) = self.find_or_new_asset(asset, false) {
+            let covered = asset_slot.data.amount.min(uncovered);
+            asset_slot.data.amount -= covered;
+            uncovered -= covered;
+
+            dex.credit_liquidity(asset, covered)?;
+        }
+
+        let price = asset_prices.get(asset as usize).copied().unwrap_or(0);
+        if uncovered == 0 || price == 0 {
+            return Ok(shortfall - uncovered);
+        }
+
+        // Values are rounded in favor of the pool
+        let div_up =
+            |a: u128, b: u128| -> DexResult<u128> { a.safe_add(b.safe_sub(1)?)?.safe_div(b) };
+        let pow = 10u128.pow(dex.assets[asset as usize].decimals as u32);
+        let mut owed = div_up((uncovered as u128).safe_mul(price as u128)?, pow)?;
+
+        let others: Vec<u8> = self
+            .asset_pool
+            .into_iter()
+            .filter(|s| s.data.asset != asset && s.data.amount > 0)
+            .map(|s| s.data.asset)
+            .collect();
+
+        for other in others {
+            let other_price = asset_prices.get(other as usize).copied().unwrap_or(0) as u128;
+            if other_price == 0 {
+                continue;
+            }
+            let other_pow = 10u128.pow(dex.assets[other as usize].decimals as u32);
+
+            let asset_slot = self.find_or_new_asset(other, false)?;
+            let needed = div_up(owed.safe_mul(other_pow)?, other_price)?;
+            let taken = (asset_slot.data.amount as u128).min(needed) as u64;
+            asset_slot.data.amount -= taken;
+
+            dex.credit_liquidity(other, taken)?;
+
+            owed = if taken as u128 == needed {
+                0
+            } else {
+                owed.saturating_sub((taken as u128).safe_mul(other_price)?.safe_div(other_pow)?)
+            };
+            if owed == 0 {
+                break;
+            }
+        }
+
+        uncovered = (div_up(owed.safe_mul(pow)?, price as u128)? as u64).min(uncovered);
+
+        Ok(shortfall - uncovered)
+    }
+
+    pub fn set_referral_code(&mut self, referral_code: Pubkey) -> DexResult {
+        require!(
+            self.meta.referral_code == Pubkey::default(),
+            DexError::ReferralAlreadyLinked
+        );
+        self.meta.referral_code = referral_code;
+
+        Ok(())
+    }
+
     #[inline]
     pub fn inc_serial_number(&mut self) {
         self.meta.serial_number += 1;
@@ -710,6 +1136,31 @@ impl<'a> UserState<'a> {
         position.data.open(price, amount, long, leverage, mfr)
     }
 
+    pub fn set_stable_collateral(
+        &mut self,
+        market: u8,
+        long: bool,
+        stable_collateral: bool,
+    ) -> DexResult {
+        let position = self.find_or_new_position(market, true)?;
+        if long {
+            position.data.long.set_stable_collateral(stable_collateral)
+        } else {
+            position.data.short.set_stable_collateral(stable_collateral)
+        }
+    }
+
+    pub fn is_stable_collateral(&self, market: u8, long: bool) -> DexResult<bool> {
+        let position = self.find_or_new_position(market, false)?;
+        let p = if long {
+            position.data.long
+        } else {
+            position.data.short
+        };
+
+        Ok(p.stable_collateral)
+    }
+
     pub fn close_position(
         &mut self,
         market: u8,
@@ -726,6 +1177,67 @@ impl<'a> UserState<'a> {
             .close(size, price, long, mfr, liquidate, limit_order)
     }
 
+    pub fn liquidation_price(
+        &self,
+        market: u8,
+        long: bool,
+        mfr: &MarketFeeRates,
+    ) -> DexResult<u64> {
+        let position = self.find_or_new_position(market, false)?;
+        let p = if long {
+            position.data.long
+        } else {
+            position.data.short
+        };
+
+        p.liquidation_price(get_timestamp()?, mfr)
+    }
+
+    pub fn require_position_liquidate(
+        &self,
+        market: u8,
+        long: bool,
+        market_price: u64,
+        mfr: &MarketFeeRates,
+    ) -> DexResult {
+        require!(
+            self.get_position_size(market, long)? > 0,
+            DexError::PositionNotExist
+        );
+
+        let liquidation_price = self.liquidation_price(market, long, mfr)?;
+        let liquidate = if long {
+            market_price <= liquidation_price
+        } else {
+            market_price >= liquidation_price
+        };
+        require!(liquidate, DexError::RequireNoLiquidation);
+
+        Ok(())
+    }
+
+    pub fn settle_borrow_fee(
+        &mut self,
+        market: u8,
+        long: bool,
+        mfr: &MarketFeeRates,
+    ) -> DexResult<u64> {
+        let position = self.find_or_new_position(market, false)?;
+        position.data.settle_borrow_fee(long, mfr)
+    }
+
+    pub fn set_position_leverage(
+        &mut self,
+        market: u8,
+        long: bool,
+        leverage: u32,
+        price: u64,
+        mfr: &MarketFeeRates,
+    ) -> DexResult<(u64, u64)> {
+        let position = self.find_or_new_position(market, false)?;
+        position.data.set_leverage(long, leverage, price, mfr)
+    }
+
     pub fn new_bid_order(
         &mut self,
         order_slot: u32,
@@ -775,6 +1287,20 @@ impl<'a> UserState<'a> {
         Ok(())
     }
 
+    pub fn set_ask_order_payout(
+        &mut self,
+        user_order_slot: u8,
+        out_asset: u8,
+        minimum_out: u64,
+    ) -> DexResult {
+        let order = self.order_pool.from_index(user_order_slot)?;
+        require!(order.in_use() && !order.data.open, DexError::InvalidIndex);
+        order.data.out_asset = out_asset;
+        order.data.minimum_out = minimum_out;
+
+        Ok(())
+    }
+
     pub fn get_order(&self, user_order_slot: u8) -> DexResult<UserOrder> {
         let order = self.order_pool.from_index(user_order_slot)?;
         require!(order.in_use(), DexError::InvalidIndex);
@@ -919,7 +1445,30 @@ impl<'a> UserState<'a> {
         Ok(withdrawable.min(amount))
     }
 
+    pub fn claimable_rewards(&self, dex: &Dex) -> DexResult<u64> {
+        let rewards_of_vlp_pool = self.meta.vlp.pending_reward(&dex.vlp_pool)?;
+        let rewards_of_vdx_pool = self.meta.vdx.pending_reward(&dex.vdx_pool)?;
+
+        rewards_of_vlp_pool.safe_add(rewards_of_vdx_pool)
+    }
+
+    pub fn claim_rewards(&mut self, dex: &mut Dex) -> DexResult<u64> {
+        self.meta.vdx.release_locked(&mut dex.vdx_pool)?;
+
+        let rewards_of_vlp_pool = self.meta.vlp.withdraw_reward(&mut dex.vlp_pool, u64::MAX)?;
+        let rewards_of_vdx_pool = self.meta.vdx.withdraw_reward(&mut dex.vdx_pool, u64::MAX)?;
+
+        rewards_of_vlp_pool.safe_add(rewards_of_vdx_pool)
+    }
+
+    // A matured lock stops earning the boosted weight on the first touch of the stake
+    pub fn release_expired_lock(&mut self, dex: &mut Dex) -> DexResult<u64> {
+        self.meta.vdx.release_locked(&mut dex.vdx_pool)
+    }
+
     pub fn stake_and_compound_vdx(&mut self, dex: &mut Dex, vdx_staked: u64) -> DexResult<u64> {
+        self.release_expired_lock(dex)?;
+
         let amount_of_vlp_pool = self.meta.vlp.withdraw_es_vdx(&mut dex.vlp_pool)?;
         let amount_of_vdx_pool = self.meta.vdx.withdraw_es_vdx(&mut dex.vdx_pool)?;
 
@@ -936,11 +1485,43 @@ impl<'a> UserState<'a> {
         Ok(vdx_vested)
     }
 
+    pub fn lock_vdx(&mut self, dex: &mut Dex, amount: u64, lock_tier: u8) -> DexResult<u64> {
+        // A matured lock has been released, the new amount then starts a fresh one
+        let vdx_vested = self.stake_and_compound_vdx(dex, 0)?;
+        self.meta
+            .vdx
+            .enter_locked_staking(&mut dex.vdx_pool, amount, lock_tier)?;
+
+        Ok(vdx_vested)
+    }
+
     pub fn redeem_vdx(&mut self, dex: &mut Dex, amount: u64) -> DexResult<(u64, u64)> {
         let vdx_vested = self.stake_and_compound_vdx(dex, 0)?;
-        let redeemable = self.meta.vdx.leave_staking(&mut dex.vdx_pool, amount)?;
+        let redeemable = self.meta.vdx.leave_staking(&mut dex.vdx_pool, amount)?;
+
+        Ok((vdx_vested, redeemable))
+    }
+
+    pub fn exit_vesting(
+        &mut self,
+        dex: &mut Dex,
+        amount: u64,
+        newest_first: bool,
+    ) -> DexResult<(u64, u64, u64)> {
+        let vdx_vested = self.stake_and_compound_vdx(dex, 0)?;
 
-        Ok((vdx_vested, redeemable))
+        let taken = self.meta.es_vdx.exit(amount, newest_first)?;
+        require_eq!(taken, amount, DexError::InsufficientVestingAmount);
+
+        let forfeited = amount
+            .safe_mul(ES_VDX_EXIT_PENALTY_PERCENTAGE)?
+            .safe_div(100)? as u64;
+        let released = amount.safe_sub(forfeited)?;
+
+        // Forfeited esVDX goes back to stakers
+        dex.distribute_es_vdx(forfeited)?;
+
+        Ok((vdx_vested, released, forfeited))
     }
 
     pub fn di_new_option(
@@ -1155,6 +1736,23 @@ impl<'a> UserState<'a> {
         Ok(())
     }
 
+    pub fn debit_asset(&mut self, asset: u8, amount: u64) -> DexResult {
+        let asset_slot = self.find_or_new_asset(asset, false)?;
+        require!(
+            amount > 0 && asset_slot.data.amount >= amount,
+            DexError::InvalidAmount
+        );
+        asset_slot.data.amount -= amount;
+
+        Ok(())
+    }
+
+    pub fn transfer_asset(&mut self, to: &mut UserState, asset: u8, amount: u64) -> DexResult {
+        self.debit_asset(asset, amount)?;
+
+        to.deposit_asset(asset, amount)
+    }
+
     pub fn withdraw_asset(&mut self, asset: u8) -> DexResult<u64> {
         let asset_slot = self.find_or_new_asset(asset, false)?;
         let amount = asset_slot.data.amount;
@@ -1175,6 +1773,19 @@ impl<'a> UserState<'a> {
         return Err(error!(DexError::AssetNotExist));
     }
 
+    // Nothing is left that could be lost by closing the account. Position and asset
+    // slots are kept once used, so only their sizes and amounts are checked.
+    pub fn is_closable(&self) -> bool {
+        self.order_pool.head() == NIL8
+            && self.di_option_pool.head() == NIL8
+            && !self.has_position()
+            && self.asset_pool.into_iter().all(|a| a.data.amount == 0)
+            && self.meta.vlp.is_empty()
+            && self.meta.vdx.is_empty()
+            && self.meta.es_vdx.is_empty()
+            && self.meta.user_list_index == NIL32
+    }
+
     pub fn release_user_list_slot(&self) -> bool {
         !self.has_position() && !self.has_unsettled_di_option()
     }
@@ -1198,7 +1809,9 @@ impl<'a> UserState<'a> {
 #[allow(dead_code)]
 mod test {
     use super::*;
-    use crate::utils::{test::*, BORROW_FEE_RATE_BASE, FEE_RATE_BASE};
+    use crate::utils::{
+        test::*, BORROW_FEE_RATE_BASE, DELEGATE_CLOSE, DELEGATE_OPEN, DELEGATE_ORDER, FEE_RATE_BASE,
+    };
     use bumpalo::Bump;
 
     impl<'a> UserState<'a> {
@@ -1283,6 +1896,307 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_resize_user_state() {
+        let bump = Bump::new();
+        let account = gen_account(UserState::required_account_size(16, 8, 8, 16), &bump);
+        UserState::initialize(&account, 2, 1, 1, 1, Pubkey::default()).assert_ok();
+
+        {
+            let us = UserState::mount(&account, true).assert_unwrap();
+            us.borrow_mut()
+                .new_bid_order(0, usdc(100.), usdc(20000.), 10, true, 0, 1)
+                .assert_ok();
+            us.borrow_mut()
+                .new_bid_order(0, usdc(200.), usdc(19000.), 10, true, 0, 1)
+                .assert_ok();
+            us.borrow_mut()
+                .new_bid_order(0, usdc(300.), usdc(18000.), 10, true, 0, 1)
+                .assert_err();
+
+            us.borrow_mut()
+                .open_position(0, usdc(20000.), btc(1.0), true, 10 * 1000, &mock_mfr())
+                .assert_ok();
+            us.borrow_mut().deposit_asset(1, usdc(10.)).assert_ok();
+            us.borrow_mut().deposit_asset(2, usdc(10.)).assert_err();
+        }
+
+        // Shrinking is not supported
+        UserState::resize(&account, 1, 1, 1, 1).assert_err();
+        // Exceeds the account size
+        UserState::resize(&account, 32, 8, 8, 16).assert_err();
+
+        UserState::resize(&account, 16, 8, 8, 16).assert_ok();
+
+        let us = UserState::mount(&account, true).assert_unwrap();
+        assert_eq!(us.borrow().meta.order_slot_count, 16);
+        assert_eq!(us.borrow().meta.asset_slot_count, 16);
+
+        // Existing slots are kept at their indices
+        let order = us.borrow().get_order(0).assert_unwrap();
+        assert_eq!(order.size, usdc(100.));
+        let order = us.borrow().get_order(1).assert_unwrap();
+        assert_eq!(order.size, usdc(200.));
+        assert!(us.borrow().get_position(0, true).assert_unwrap().size > 0);
+        assert_eq!(
+            us.borrow()
+                .find_or_new_asset(1, false)
+                .assert_unwrap()
+                .data
+                .amount,
+            usdc(10.)
+        );
+
+        // New slots are available
+        let slot = us
+            .borrow_mut()
+            .new_bid_order(0, usdc(300.), usdc(18000.), 10, true, 0, 1)
+            .assert_unwrap();
+        assert_eq!(slot, 2);
+        us.borrow_mut().deposit_asset(2, usdc(10.)).assert_ok();
+    }
+
+    #[test]
+    fn test_user_state_closable() {
+        let bump = Bump::new();
+        let account = gen_account(UserState::required_account_size(8, 8, 8, 8), &bump);
+        UserState::initialize(&account, 8, 8, 8, 8, Pubkey::default()).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+        assert!(us.borrow().is_closable());
+
+        // Withdrawn assets leave an empty slot behind
+        us.borrow_mut().deposit_asset(1, usdc(10.)).assert_ok();
+        assert!(!us.borrow().is_closable());
+        us.borrow_mut().withdraw_asset(1).assert_ok();
+        assert!(us.borrow().is_closable());
+
+        let slot = us
+            .borrow_mut()
+            .new_bid_order(0, usdc(100.), usdc(20000.), 10, true, 0, 1)
+            .assert_unwrap();
+        assert!(!us.borrow().is_closable());
+        us.borrow_mut().unlink_order(slot, true).assert_ok();
+        assert!(us.borrow().is_closable());
+
+        us.borrow_mut().meta.vdx.staked = 1;
+        assert!(!us.borrow().is_closable());
+        us.borrow_mut().meta.vdx.staked = 0;
+
+        us.borrow_mut().meta.es_vdx.amounts[0] = 1;
+        assert!(!us.borrow().is_closable());
+        us.borrow_mut().meta.es_vdx.amounts[0] = 0;
+
+        us.borrow_mut()
+            .open_position(0, usdc(20000.), btc(1.0), true, 10 * 1000, &mock_mfr())
+            .assert_ok();
+        assert!(!us.borrow().is_closable());
+    }
+
+    #[test]
+    fn test_user_delegate() {
+        let bump = Bump::new();
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let dex = Pubkey::new_unique();
+
+        let account = gen_account(UserState::required_account_size(8, 4, 8, 8), &bump);
+        UserState::initialize(&account, 8, 4, 8, 8, owner).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let (user_state, _) =
+            Pubkey::find_program_address(&[dex.as_ref(), owner.as_ref()], &crate::id());
+
+        // Owner has full access, only to its own user state
+        us.borrow()
+            .check_authority(&user_state, &dex, &owner, DELEGATE_ALL)
+            .assert_ok();
+        us.borrow()
+            .check_authority(&account.key(), &dex, &owner, DELEGATE_ALL)
+            .assert_err();
+        us.borrow()
+            .check_authority(&user_state, &dex, &delegate, DELEGATE_OPEN)
+            .assert_err();
+
+        // Invalid permissions or delegate
+        us.borrow_mut().set_delegate(delegate, 0x8).assert_err();
+        us.borrow_mut().set_delegate(delegate, 0).assert_err();
+        us.borrow_mut()
+            .set_delegate(Pubkey::default(), DELEGATE_OPEN)
+            .assert_err();
+        us.borrow_mut()
+            .set_delegate(owner, DELEGATE_OPEN)
+            .assert_err();
+
+        us.borrow_mut()
+            .set_delegate(delegate, DELEGATE_OPEN | DELEGATE_ORDER)
+            .assert_ok();
+        us.borrow()
+            .check_authority(&user_state, &dex, &delegate, DELEGATE_OPEN)
+            .assert_ok();
+        us.borrow()
+            .check_authority(&user_state, &dex, &delegate, DELEGATE_ORDER)
+            .assert_ok();
+        us.borrow()
+            .check_authority(&user_state, &dex, &delegate, DELEGATE_CLOSE)
+            .assert_err();
+
+        us.borrow_mut()
+            .set_delegate(Pubkey::default(), 0)
+            .assert_ok();
+        us.borrow()
+            .check_authority(&user_state, &dex, &delegate, DELEGATE_OPEN)
+            .assert_err();
+    }
+
+    #[test]
+    fn test_sub_account() {
+        let bump = Bump::new();
+        let owner = Pubkey::new_unique();
+        let dex = Pubkey::new_unique();
+
+        let main_account = gen_account(UserState::required_account_size(8, 4, 8, 8), &bump);
+        UserState::initialize(&main_account, 8, 4, 8, 8, owner).assert_ok();
+        let main = UserState::mount(&main_account, true).assert_unwrap();
+
+        let sub_account = gen_account(UserState::required_account_size(8, 4, 8, 8), &bump);
+        UserState::initialize(&sub_account, 8, 4, 8, 8, owner).assert_ok();
+        let sub = UserState::mount(&sub_account, true).assert_unwrap();
+        sub.borrow_mut().set_sub_account(1);
+
+        // Sub-account 0 keeps the legacy seeds
+        let (main_key, _) =
+            Pubkey::find_program_address(&[dex.as_ref(), owner.as_ref()], &crate::id());
+        assert_eq!(UserState::find_address(&dex, &owner, 0).0, main_key);
+
+        let (sub_key, _) = UserState::find_address(&dex, &owner, 1);
+        assert_ne!(sub_key, main_key);
+
+        main.borrow()
+            .check_owner(&main_key, &dex, &owner)
+            .assert_ok();
+        main.borrow()
+            .check_owner(&sub_key, &dex, &owner)
+            .assert_err();
+        sub.borrow().check_owner(&sub_key, &dex, &owner).assert_ok();
+        sub.borrow()
+            .check_owner(&sub_key, &dex, &Pubkey::new_unique())
+            .assert_err();
+
+        // Move asset balances between sub-accounts
+        main.borrow_mut()
+            .transfer_asset(&mut sub.borrow_mut(), 0, 100)
+            .assert_err();
+
+        main.borrow_mut().deposit_asset(0, 100).assert_ok();
+        main.borrow_mut()
+            .transfer_asset(&mut sub.borrow_mut(), 0, 101)
+            .assert_err();
+        main.borrow_mut()
+            .transfer_asset(&mut sub.borrow_mut(), 0, 0)
+            .assert_err();
+        main.borrow_mut()
+            .transfer_asset(&mut sub.borrow_mut(), 0, 60)
+            .assert_ok();
+
+        assert_eq!(main.borrow_mut().withdraw_asset(0).assert_unwrap(), 40);
+        assert_eq!(sub.borrow_mut().withdraw_asset(0).assert_unwrap(), 60);
+    }
+
+    #[test]
+    fn test_cross_margin() {
+        let bump = Bump::new();
+        let account = gen_account(UserState::required_account_size(8, 4, 8, 8), &bump);
+        UserState::initialize(&account, 8, 4, 8, 8, Pubkey::default()).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let mut dex = Dex::default();
+        dex.mock_dex();
+        dex.markets[0].liquidate_threshold = 10;
+        let mfr = dex.markets[0].get_fee_rates(dex.assets[0].borrow_fee_rate, 0);
+
+        us.borrow_mut().set_cross_margin(true).assert_ok();
+        us.borrow_mut()
+            .open_position(0, usdc(20000.), btc(1.0), true, 10 * 1000, &mfr)
+            .assert_ok();
+
+        // The mode is locked while positions are open
+        us.borrow_mut().set_cross_margin(false).assert_err();
+
+        let (equity, maintenance) = us
+            .borrow()
+            .cross_margin_status(&dex, &[usdc(20000.), usdc(1.)], &[usdc(20000.)])
+            .assert_unwrap();
+        assert!(equity > maintenance as i64);
+
+        // A 9% drop leaves the position below its maintenance margin
+        let asset_prices = [usdc(18200.), usdc(1.)];
+        let market_prices = [usdc(18200.)];
+        us.borrow()
+            .require_cross_margin_liquidate(&dex, &asset_prices, &market_prices)
+            .assert_ok();
+
+        // Free balances back the position
+        us.borrow_mut().deposit_asset(1, usdc(2000.)).assert_ok();
+        us.borrow()
+            .require_cross_margin_liquidate(&dex, &asset_prices, &market_prices)
+            .assert_err();
+
+        // Missing prices are rejected
+        us.borrow()
+            .cross_margin_status(&dex, &[usdc(18200.), 0], &market_prices)
+            .assert_err();
+
+        // Shortfall is covered by the free balance of the same asset
+        us.borrow_mut().deposit_asset(0, btc(0.2)).assert_ok();
+        let covered = us
+            .borrow_mut()
+            .cover_shortfall(&mut dex, 0, true, btc(1.), -btc_i(1.2), btc(0.1), &[0, 0])
+            .assert_unwrap();
+        assert_eq!(covered, btc(0.2));
+        assert_eq!(dex.assets[0].liquidity_amount, btc(0.2));
+        assert_eq!(us.borrow_mut().withdraw_asset(0).assert_unwrap(), 0);
+
+        // Then by the other free balances at their prices
+        us.borrow_mut().deposit_asset(0, btc(0.1)).assert_ok();
+        us.borrow_mut().deposit_asset(1, usdc(2000.)).assert_ok();
+        let liquidity = dex.assets[1].liquidity_amount;
+        let covered = us
+            .borrow_mut()
+            .cover_shortfall(
+                &mut dex,
+                0,
+                true,
+                btc(1.),
+                -btc_i(1.2),
+                btc(0.1),
+                &asset_prices,
+            )
+            .assert_unwrap();
+        assert_eq!(covered, btc(0.3));
+        assert_eq!(dex.assets[0].liquidity_amount, btc(0.3));
+        assert_eq!(dex.assets[1].liquidity_amount, liquidity + usdc(3640.));
+        assert_eq!(us.borrow_mut().withdraw_asset(0).assert_unwrap(), 0);
+        assert_eq!(
+            us.borrow_mut().withdraw_asset(1).assert_unwrap(),
+            usdc(4000.) - usdc(3640.)
+        );
+
+        // No shortfall when the collateral covers the loss
+        let covered = us
+            .borrow_mut()
+            .cover_shortfall(
+                &mut dex,
+                0,
+                false,
+                usdc(100.),
+                -usdc_i(50.),
+                usdc(1.),
+                &asset_prices,
+            )
+            .assert_unwrap();
+        assert_eq!(covered, 0);
+    }
+
     #[test]
     fn test_open_long() {
         let bump = Bump::new();
@@ -1814,6 +2728,67 @@ mod test {
         assert_eq!(position.closing_size, size / 2);
     }
 
+    #[test]
+    fn test_user_order_layout() {
+        assert_eq!(std::mem::size_of::<UserOrder>(), 72);
+
+        // Payout fields are carved from the padding of existing orders
+        let order = UserOrder::default();
+        assert_eq!(field_offset!(order, market), 51);
+        assert_eq!(field_offset!(order, out_asset), 52);
+        assert_eq!(field_offset!(order, minimum_out), 56);
+        assert_eq!(
+            field_offset!(order, padding) + order.padding.len(),
+            std::mem::size_of::<UserOrder>()
+        );
+    }
+
+    #[test]
+    fn test_ask_order_payout() {
+        let bump = Bump::new();
+        let max_order_count = 8u8;
+        let required_size = UserState::required_account_size(max_order_count, 8u8, 8u8, 8u8);
+        let account = gen_account(required_size, &bump);
+        UserState::initialize(&account, max_order_count, 8u8, 8u8, 8u8, Pubkey::default())
+            .assert_ok();
+
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let mfr = mock_mfr();
+        let (size, _, _, _) = us
+            .borrow_mut()
+            .open_position(0, usdc(20000.), usdc(2000.), false, 10 * 1000, &mfr)
+            .assert_unwrap();
+
+        let (user_order_slot, _) = us
+            .borrow_mut()
+            .new_ask_order(size / 2, usdc(21000.), false, 0)
+            .assert_unwrap();
+
+        // Collateral asset by default
+        let order = us.borrow().get_order(user_order_slot).assert_unwrap();
+        assert_eq!(order.out_asset, NIL8);
+        assert_eq!(order.minimum_out, 0);
+
+        us.borrow_mut()
+            .set_ask_order_payout(user_order_slot, 1, btc(0.01))
+            .assert_ok();
+
+        let order = us.borrow().get_order(user_order_slot).assert_unwrap();
+        assert_eq!(order.out_asset, 1);
+        assert_eq!(order.minimum_out, btc(0.01));
+
+        // Bid orders don't pay out
+        let bid_slot = us
+            .borrow_mut()
+            .new_bid_order(0, usdc(100.), usdc(19000.), 10 * 1000, true, 0, 0)
+            .assert_unwrap();
+        assert!(us
+            .borrow_mut()
+            .set_ask_order_payout(bid_slot, 1, 0)
+            .is_err());
+    }
+
     #[test]
     fn test_new_ask_order_size_error() {
         let bump = Bump::new();
@@ -2277,4 +3252,153 @@ mod test {
         let vested = vest.roll(usdc(0.)).assert_unwrap();
         assert_eq!(vested, usdc(110.));
     }
+
+    #[test]
+    fn test_claim_rewards() {
+        let bump = Bump::new();
+        let required_size = UserState::required_account_size(8u8, 8u8, 8u8, 8u8);
+        let account = gen_account(required_size, &bump);
+        UserState::initialize(&account, 8u8, 8u8, 8u8, 8u8, Pubkey::default()).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let mut dex = Dex::default();
+        us.borrow_mut()
+            .enter_staking_vlp(&mut dex, usdc(1000.))
+            .assert_ok();
+        assert_eq!(us.borrow().claimable_rewards(&dex).assert_unwrap(), 0);
+
+        dex.vlp_pool.add_rewards(usdc(10.)).assert_ok();
+        assert_eq!(
+            us.borrow().claimable_rewards(&dex).assert_unwrap(),
+            usdc(10.)
+        );
+
+        let claimed = us.borrow_mut().claim_rewards(&mut dex).assert_unwrap();
+        assert_eq!(claimed, usdc(10.));
+        assert_eq!(dex.vlp_pool.reward_total, 0);
+        assert_eq!(dex.vlp_pool.staked_total, usdc(1000.));
+        assert_eq!(us.borrow().claimable_rewards(&dex).assert_unwrap(), 0);
+    }
+
+    #[test]
+    fn test_release_expired_lock() {
+        let bump = Bump::new();
+        let required_size = UserState::required_account_size(8u8, 8u8, 8u8, 8u8);
+        let account = gen_account(required_size, &bump);
+        UserState::initialize(&account, 8u8, 8u8, 8u8, 8u8, Pubkey::default()).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let mut dex = Dex::default();
+        assert_eq!(
+            us.borrow_mut()
+                .release_expired_lock(&mut dex)
+                .assert_unwrap(),
+            0
+        );
+
+        us.borrow_mut()
+            .lock_vdx(&mut dex, usdc(1000.), 0)
+            .assert_ok();
+        assert_eq!(dex.vdx_pool.staked_total, usdc(1100.));
+
+        // Not matured yet
+        assert_eq!(
+            us.borrow_mut()
+                .release_expired_lock(&mut dex)
+                .assert_unwrap(),
+            0
+        );
+        us.borrow_mut().claim_rewards(&mut dex).assert_ok();
+        assert_eq!(us.borrow().meta.vdx.locked, usdc(1000.));
+
+        // Any touch of the stake releases a matured lock
+        us.borrow_mut().meta.vdx.unlock_time = 1;
+        us.borrow_mut().claim_rewards(&mut dex).assert_ok();
+        assert_eq!(us.borrow().meta.vdx.locked, 0);
+        assert_eq!(us.borrow().meta.vdx.staked, usdc(1000.));
+        assert_eq!(dex.vdx_pool.staked_total, usdc(1000.));
+
+        us.borrow_mut()
+            .lock_vdx(&mut dex, usdc(1000.), 0)
+            .assert_ok();
+        us.borrow_mut().meta.vdx.unlock_time = 1;
+        us.borrow_mut()
+            .stake_and_compound_vdx(&mut dex, 0)
+            .assert_ok();
+        assert_eq!(us.borrow().meta.vdx.staked, usdc(2000.));
+        assert_eq!(dex.vdx_pool.staked_total, usdc(2000.));
+
+        us.borrow_mut()
+            .lock_vdx(&mut dex, usdc(1000.), 0)
+            .assert_ok();
+        us.borrow_mut().meta.vdx.unlock_time = 1;
+        assert_eq!(
+            us.borrow_mut()
+                .release_expired_lock(&mut dex)
+                .assert_unwrap(),
+            usdc(1000.)
+        );
+        assert_eq!(dex.vdx_pool.staked_total, usdc(3000.));
+    }
+
+    #[test]
+    fn test_vest_exit() {
+        let mut vest = VestingMint::default();
+        vest.init_test();
+        vest.roll(usdc(360.)).assert_ok();
+        vest.assert_roll(DAY * 180, usdc(720.), usdc(180.));
+
+        // Day 0: 180 remaining, day 180: 720 remaining
+        let mut newest = vest;
+        assert_eq!(newest.exit(usdc(360.), true).assert_unwrap(), usdc(360.));
+        newest.assert_roll(DAY * 180, 0, usdc(180. + 180.));
+
+        let mut oldest = vest;
+        assert_eq!(oldest.exit(usdc(360.), false).assert_unwrap(), usdc(360.));
+        oldest.assert_roll(DAY * 180, 0, usdc(270.));
+
+        // Can not take more than what is still vesting
+        let mut all = vest;
+        assert_eq!(all.exit(usdc(1000.), false).assert_unwrap(), usdc(900.));
+        all.assert_roll(DAY * 360, 0, 0);
+    }
+
+    #[test]
+    fn test_exit_vesting() {
+        let bump = Bump::new();
+        let required_size = UserState::required_account_size(8u8, 8u8, 8u8, 8u8);
+        let account = gen_account(required_size, &bump);
+        UserState::initialize(&account, 8u8, 8u8, 8u8, 8u8, Pubkey::default()).assert_ok();
+        let us = UserState::mount(&account, true).assert_unwrap();
+
+        let mut dex = Dex::default();
+        dex.es_vdx_percentage_for_vdx_pool = 50;
+        us.borrow_mut()
+            .enter_staking_vlp(&mut dex, usdc(1000.))
+            .assert_ok();
+        us.borrow_mut()
+            .meta
+            .vdx
+            .enter_staking(&mut dex.vdx_pool, usdc(1000.))
+            .assert_ok();
+
+        dex.vlp_pool.add_es_vdx(usdc(100.)).assert_ok();
+
+        let (vested, released, forfeited) = us
+            .borrow_mut()
+            .exit_vesting(&mut dex, usdc(100.), true)
+            .assert_unwrap();
+        assert_eq!(vested, 0);
+        assert_eq!(released, usdc(50.));
+        assert_eq!(forfeited, usdc(50.));
+
+        // Forfeited esVDX is shared by both pools
+        assert_eq!(dex.vlp_pool.es_vdx_total, usdc(25.));
+        assert_eq!(dex.vdx_pool.es_vdx_total, usdc(25.));
+
+        // The staker gets back the forfeited part, not more
+        us.borrow_mut()
+            .exit_vesting(&mut dex, usdc(51.), true)
+            .assert_err();
+    }
 }
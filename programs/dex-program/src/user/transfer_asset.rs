@@ -0,0 +1,54 @@
+use crate::{
+    errors::{DexError, DexResult},
+    user::state::*,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferUserAsset<'info> {
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub from_user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    #[account(mut, owner = *program_id)]
+    pub to_user_state: UncheckedAccount<'info>,
+
+    /// CHECK
+    pub dex: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+// Moves a withdrawable asset balance between two sub-accounts of the same owner
+pub fn handler(ctx: Context<TransferUserAsset>, asset: u8, amount: u64) -> DexResult {
+    require_keys_neq!(
+        ctx.accounts.from_user_state.key(),
+        ctx.accounts.to_user_state.key(),
+        DexError::InvalidSubAccount
+    );
+
+    let from = UserState::mount(&ctx.accounts.from_user_state, true)?;
+    from.borrow().check_owner(
+        &ctx.accounts.from_user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    require!(
+        !from.borrow().is_cross_margin() || !from.borrow().has_position(),
+        DexError::CrossMarginPositionOpen
+    );
+
+    let to = UserState::mount(&ctx.accounts.to_user_state, true)?;
+    to.borrow().check_owner(
+        &ctx.accounts.to_user_state.key(),
+        &ctx.accounts.dex.key(),
+        ctx.accounts.authority.key,
+    )?;
+
+    from.borrow_mut()
+        .transfer_asset(&mut to.borrow_mut(), asset, amount)?;
+
+    Ok(())
+}
@@ -12,6 +12,7 @@ pub const MOCK_ORACLE_MAGIC_NUMBER: u64 = 0x66667;
 pub const USER_STATE_MAGIC_NUMBER: u32 = 0x6668;
 pub const DI_ACCOUNT_MAGIC_NUMBER: u32 = 0x6669;
 pub const PRICE_FEED_MAGIC_NUMBER: u64 = 0x666A;
+pub const TIMELOCK_MAGIC_NUMBER: u64 = 0x666B;
 pub const ORDER_POOL_MAGIC_BYTE: u8 = 0x30;
 pub const USER_LIST_MAGIC_BYTE: u8 = 0x31;
 
@@ -19,6 +20,12 @@ pub const LEVERAGE_POW_DECIMALS: u32 = 1000;
 pub const FEE_RATE_DECIMALS: u32 = 4;
 pub const FEE_RATE_BASE: u128 = 10000;
 pub const BORROW_FEE_RATE_BASE: u128 = 100_0000;
+pub const FLASH_LOAN_FEE_RATE: u16 = 9;
+pub const MAX_MARKET_FEE_RATE: u16 = 500;
+pub const MAX_ASSET_FEE_RATE: u16 = 500;
+pub const MAX_TARGET_WEIGHT: u16 = 1000;
+pub const MAX_LIQUIDATE_THRESHOLD: u16 = 50;
+pub const MAX_LEVERAGE: u32 = 200;
 
 pub const USDC_DECIMALS: u8 = 6;
 pub const USD_POW_DECIMALS: u64 = 10u64.pow(USDC_DECIMALS as u32);
@@ -35,12 +42,34 @@ pub const ES_VDX_PER_SECOND: u64 = 32000; // Carrying decimals
 
 pub const ES_VDX_PERCENTAGE_FOR_VDX_POOL: u32 = 50;
 pub const REWARD_PERCENTAGE_FOR_VDX_POOL: u32 = 30;
+pub const MAX_PROTOCOL_FEE_PERCENTAGE: u16 = 50;
+pub const MAX_REFERRAL_PERCENTAGE: u16 = 50;
+pub const FEE_TIER_COUNT: usize = 4;
+pub const MAX_FEE_TIER_DISCOUNT: u16 = 50;
 
 pub const VESTING_PERIOD: u16 = 360;
+pub const ES_VDX_EXIT_PENALTY_PERCENTAGE: u64 = 50;
+pub const VDX_LOCK_PERIODS: [i64; 4] = [
+    30 * SECONDS_PER_DAY,
+    90 * SECONDS_PER_DAY,
+    180 * SECONDS_PER_DAY,
+    360 * SECONDS_PER_DAY,
+];
+pub const VDX_LOCK_MULTIPLIERS: [u64; 4] = [110, 125, 150, 200]; // Percentage
 pub const UPDATE_REWARDS_PERIOD: i64 = 1800;
+pub const MAX_AUM_AGE: i64 = 60;
+pub const MAX_TIMELOCK_ACTIONS: usize = 16;
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * SECONDS_PER_DAY;
+pub const MIN_REDUCE_ONLY_DURATION: i64 = SECONDS_PER_DAY;
+pub const MAX_SETTLE_PRICE_DEVIATION: u64 = 5; // Percentage of the oracle price
 
 pub const ASSET_VDX: u8 = u8::MAX;
 pub const ASSET_REWARDS: u8 = u8::MAX - 1;
 
 pub const MASK_PERP_POSITION: u8 = 0x1;
 pub const MASK_DI_OPTION: u8 = 0x2;
+
+pub const DELEGATE_OPEN: u8 = 0x1;
+pub const DELEGATE_CLOSE: u8 = 0x2;
+pub const DELEGATE_ORDER: u8 = 0x4;
+pub const DELEGATE_ALL: u8 = DELEGATE_OPEN | DELEGATE_CLOSE | DELEGATE_ORDER;
@@ -132,3 +132,11 @@ pub fn sol_i(size: f64) -> i64 {
 pub fn leverage(l: u32) -> u32 {
     l * 10u32.pow(LEVERAGE_POW_DECIMALS as u32)
 }
+
+// Byte offset of a field, used to check zero copy layouts stay compatible
+macro_rules! field_offset {
+    ($value:expr, $field:ident) => {
+        (&$value.$field as *const _ as usize) - (&$value as *const _ as usize)
+    };
+}
+pub(crate) use field_offset;
@@ -1,8 +1,12 @@
 use std::{borrow::Borrow, cell::RefCell, mem, rc::Rc};
 
 use crate::utils::{
-    compose_add_asset_ix, compose_add_market_ixs, compose_di_set_admin_ix,
-    compose_di_set_fee_rate_ix, compose_init_dex_ixs, compose_set_liquidity_fee_rate_ix,
+    compose_add_asset_ix, compose_add_market_ixs, compose_append_order_pool_page_ixs,
+    compose_cancel_timelock_ix, compose_delist_market_ix, compose_di_set_admin_ix,
+    compose_di_set_fee_rate_ix, compose_execute_timelock_ix, compose_init_dex_ixs,
+    compose_init_timelock_ix, compose_set_delegate_ix, compose_set_fee_tiers_ix,
+    compose_set_liquidity_fee_rate_ix, compose_set_protocol_fee_percentage_ix,
+    compose_set_referral_rates_ix, compose_set_trading_state_ix, compose_update_market_ix,
     constant::{
         TEST_BTC_ADD_LIQUIDITY_FEE_RATE, TEST_BTC_ASSET_INDEX, TEST_BTC_BORROW_FEE_RATE,
         TEST_BTC_CHARGE_BORROW_FEE_INTERVAL, TEST_BTC_CLOSE_FEE_RATE, TEST_BTC_DECIMALS,
@@ -34,22 +38,22 @@ use crate::utils::{
 use crate::utils::{TestResult, INIT_ADD_SOL_AMOUNT};
 use anchor_client::{
     solana_sdk::{
-        clock::UnixTimestamp, signature::Keypair, signer::Signer, sysvar, transaction::Transaction,
-        transport::TransportError,
+        clock::UnixTimestamp, instruction::Instruction, signature::Keypair, signer::Signer, sysvar,
+        transaction::Transaction, transport::TransportError,
     },
     Program,
 };
 use anchor_lang::{
     error,
-    prelude::{Clock, Pubkey},
+    prelude::{AccountMeta, Clock, Pubkey},
 };
 use bincode::deserialize;
-use dex_program::dex::PriceFeed;
+use dex_program::dex::{AssetInfo, MarketInfo, PriceFeed, Timelock};
 use dex_program::{
     dex::Dex,
     dual_invest::DI,
     errors::{DexError, DexResult},
-    utils::VDX_DECIMALS,
+    utils::{FEE_TIER_COUNT, LEVERAGE_POW_DECIMALS, VDX_DECIMALS},
 };
 use solana_program_test::ProgramTestContext;
 
@@ -387,6 +391,9 @@ impl DexTestContext {
             users.push(user);
         }
 
+        // Value the pool at the initial oracle prices
+        users[0].refresh_aum().await.unwrap();
+
         //10.init reward asset
         if add_sol_liquidity {
             let user = UserTestContext::new(context.clone(), dex.pubkey()).await;
@@ -557,6 +564,10 @@ impl DexTestContext {
     }
 
     pub async fn after(&self, span: i64) -> i64 {
+        // Repeated instructions would otherwise be dropped as duplicate transactions
+        let slot = self.get_clock().await.slot;
+        self.context.borrow_mut().warp_to_slot(slot + 1).unwrap();
+
         let mut clock: Clock = self.get_clock().await;
 
         clock.epoch_start_timestamp += span;
@@ -628,6 +639,332 @@ impl DexTestContext {
 
         assert!(vlp_pool_rewards.abs_diff(rewards) <= 2);
     }
+
+    async fn process_admin_ixs(&self, ixs: Vec<Instruction>) -> Result<(), TransportError> {
+        let context = &mut self.context.borrow_mut();
+
+        let transaction = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&self.admin.pubkey()),
+            &[&self.admin],
+            context.banks_client.get_latest_blockhash().await.unwrap(),
+        );
+
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    pub async fn init_timelock(&self, delay: i64) -> Pubkey {
+        let timelock = Keypair::new();
+        create_account(
+            &mut self.context.borrow_mut(),
+            &self.admin,
+            &timelock,
+            8 + mem::size_of::<Timelock>(),
+        )
+        .await
+        .assert_ok();
+
+        let ix = compose_init_timelock_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &timelock.pubkey(),
+            delay,
+        )
+        .await;
+        self.process_admin_ixs(vec![ix]).await.assert_ok();
+
+        timelock.pubkey()
+    }
+
+    // Parameter changes carry the attached timelock (if any) as the first remaining account
+    pub async fn timelock_accounts(&self) -> Vec<AccountMeta> {
+        let timelock = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex)
+            .await
+            .borrow()
+            .timelock;
+
+        if timelock == Pubkey::default() {
+            vec![]
+        } else {
+            vec![AccountMeta::new(timelock, false)]
+        }
+    }
+
+    pub async fn update_market_fee_rates(
+        &self,
+        market: u8,
+        open_fee_rate: u16,
+        close_fee_rate: u16,
+    ) -> Result<(), TransportError> {
+        let (oracle, event_queue) = {
+            let di = self.dex_info.borrow();
+            (di.markets[market as usize].oracle, di.event_queue)
+        };
+
+        let ix = compose_update_market_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &oracle,
+            &event_queue,
+            self.timelock_accounts().await,
+            market,
+            u64::MAX,
+            u64::MAX,
+            open_fee_rate,
+            close_fee_rate,
+            u16::MAX,
+            u16::MAX,
+            // Fixture markets are listed above the leverage cap, which any update enforces
+            MAX_LEVERAGE / LEVERAGE_POW_DECIMALS,
+            u8::MAX,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn set_protocol_fee_percentage(&self, percentage: u16) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+
+        let ix = compose_set_protocol_fee_percentage_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &event_queue,
+            self.timelock_accounts().await,
+            percentage,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    // Appends a new page and returns it, the passed pages stand in for the market's remaining pages
+    pub async fn append_order_pool_page(
+        &self,
+        market: u8,
+        remaining_pages: &[Pubkey],
+    ) -> Result<Pubkey, TransportError> {
+        let order_pool_entry_page = self.market_info(market).await.order_pool_entry_page;
+        let new_page = Keypair::new();
+
+        let context = &mut self.context.borrow_mut();
+        let ixs = compose_append_order_pool_page_ixs(
+            context,
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &order_pool_entry_page,
+            &new_page.pubkey(),
+            remaining_pages
+                .iter()
+                .map(|page| AccountMeta::new(*page, false))
+                .collect(),
+            market,
+        )
+        .await;
+
+        let transaction = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&self.admin.pubkey()),
+            &[&self.admin, &new_page],
+            context.banks_client.get_latest_blockhash().await.unwrap(),
+        );
+
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+            .map(|_| new_page.pubkey())
+    }
+
+    pub async fn set_trading_state(
+        &self,
+        scope: u8,
+        index: u8,
+        state: u8,
+    ) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+
+        let ix = compose_set_trading_state_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &event_queue,
+            scope,
+            index,
+            state,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn delist_market(&self, market: u8, settle_price: f64) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+        let oracle = self.dex_info.borrow().markets[market as usize].oracle;
+
+        let ix = compose_delist_market_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &oracle,
+            &event_queue,
+            self.timelock_accounts().await,
+            market,
+            convert_to_big_number(settle_price, TEST_USDC_DECIMALS),
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn set_delegate(&self, delegate: &Pubkey) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+
+        let ix = compose_set_delegate_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            delegate,
+            &event_queue,
+            self.timelock_accounts().await,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn delegate(&self) -> Pubkey {
+        let dex = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex).await;
+        let delegate = dex.borrow().delegate;
+        delegate
+    }
+
+    pub async fn set_fee_tiers(
+        &self,
+        staked: [u64; FEE_TIER_COUNT],
+        discounts: [u16; FEE_TIER_COUNT],
+    ) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+
+        let ix = compose_set_fee_tiers_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &event_queue,
+            self.timelock_accounts().await,
+            staked,
+            discounts,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn fee_tiers(&self) -> ([u64; FEE_TIER_COUNT], [u16; FEE_TIER_COUNT]) {
+        let dex = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex).await;
+        let dex = dex.borrow();
+        (dex.fee_tier_staked, dex.fee_tier_discounts)
+    }
+
+    pub async fn set_referral_rates(
+        &self,
+        discount: u16,
+        rebate: u16,
+    ) -> Result<(), TransportError> {
+        let event_queue = self.dex_info.borrow().event_queue;
+
+        let ix = compose_set_referral_rates_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &event_queue,
+            self.timelock_accounts().await,
+            discount,
+            rebate,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn protocol_fee_percentage(&self) -> u16 {
+        get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex)
+            .await
+            .borrow()
+            .protocol_fee_percentage
+    }
+
+    pub async fn referral_rates(&self) -> (u16, u16) {
+        let dex = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex).await;
+        let dex = dex.borrow();
+        (
+            dex.referral_discount_percentage,
+            dex.referral_rebate_percentage,
+        )
+    }
+
+    pub async fn execute_timelock(&self, id: u64, oracle: &Pubkey) -> Result<(), TransportError> {
+        let (timelock, event_queue) = {
+            let dex = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex).await;
+            let dex = dex.borrow();
+            (dex.timelock, dex.event_queue)
+        };
+
+        let ix = compose_execute_timelock_ix(
+            &self.program,
+            &self.dex,
+            &timelock,
+            oracle,
+            &event_queue,
+            id,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
+
+    pub async fn asset_info(&self, asset: u8) -> AssetInfo {
+        get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex)
+            .await
+            .borrow()
+            .assets[asset as usize]
+    }
+
+    pub async fn market_info(&self, market: u8) -> MarketInfo {
+        get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex)
+            .await
+            .borrow()
+            .markets[market as usize]
+    }
+
+    pub async fn cancel_timelock(&self, id: u64) -> Result<(), TransportError> {
+        let (timelock, event_queue) = {
+            let dex = get_dex_info(&mut self.context.borrow_mut().banks_client, self.dex).await;
+            let dex = dex.borrow();
+            (dex.timelock, dex.event_queue)
+        };
+
+        let ix = compose_cancel_timelock_ix(
+            &self.program,
+            &self.admin,
+            &self.dex,
+            &timelock,
+            &event_queue,
+            id,
+        )
+        .await;
+
+        self.process_admin_ixs(vec![ix]).await
+    }
 }
 
 pub async fn add_market(
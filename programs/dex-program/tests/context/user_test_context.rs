@@ -5,15 +5,19 @@ use std::{
 };
 
 use crate::utils::{
-    assert_eq_with_dust, btc, convert_to_big_number, create_associated_token_account,
-    create_token_account, get_dex_info, get_keypair, get_price_feed_info, get_program,
-    get_token_balance, mint_tokens, set_add_liquidity, set_ask, set_bid, set_cancel,
-    set_cancel_all, set_close, set_compound, set_crank, set_di_buy, set_di_create,
-    set_di_remove_option, set_di_set_settle_price, set_di_settle, set_di_update_option,
-    set_di_withdraw_settled, set_feed_mock_oracle, set_fill, set_market_swap, set_open,
-    set_redeem_vdx, set_remove_liquidity, set_stake_vdx, set_update_price, set_user_state,
-    set_withdraw_asset, transfer, usdc, DexAsset, DexMarket, MAX_ASSET_COUNT, PRICE_FEED_DECIMALS,
-    TEST_USDC_DECIMALS,
+    assert_eq_with_dust, btc, compose_create_referral_code_ix, compose_create_sub_account_ix,
+    compose_deposit_asset_ix, compose_liquidate_position_ix, compose_lock_vdx_ix,
+    compose_open_market_position_ix, compose_set_margin_mode_ix, compose_set_position_leverage_ix,
+    compose_set_referral_ix, compose_settle_borrow_fee_ix, compose_settle_delisted_ix,
+    compose_stake_vdx_ix, compose_transfer_user_asset_ix, convert_to_big_number,
+    create_associated_token_account, create_token_account, get_dex_info, get_keypair,
+    get_price_feed_info, get_program, get_token_balance, mint_tokens, set_add_liquidity, set_ask,
+    set_bid, set_cancel, set_cancel_all, set_close, set_compound, set_crank, set_di_buy,
+    set_di_create, set_di_remove_option, set_di_set_settle_price, set_di_settle,
+    set_di_update_option, set_di_withdraw_settled, set_feed_mock_oracle, set_fill, set_flash_loan,
+    set_market_swap, set_open, set_redeem_vdx, set_refresh_aum, set_remove_liquidity,
+    set_stake_vdx, set_update_price, set_user_state, set_withdraw_asset, transfer, usdc, DexAsset,
+    DexMarket, MAX_ASSET_COUNT, PRICE_FEED_DECIMALS, TEST_USDC_DECIMALS,
 };
 use anchor_client::{
     solana_sdk::{
@@ -35,12 +39,12 @@ use crate::utils::constant::TEST_VLP_DECIMALS;
 use crate::utils::TestResult;
 use dex_program::{
     collections::{OrderBook, SingleEvent, SingleEventQueue},
-    dex::{Dex, MockOracle},
+    dex::{Dex, MockOracle, Position},
     dual_invest::{DIOption, DI},
     errors::{DexError, DexResult},
     order::MatchEvent,
-    user::UserState,
-    utils::USDC_POW_DECIMALS,
+    user::{ReferralCode, UserState},
+    utils::{NIL8, USDC_POW_DECIMALS},
 };
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
@@ -193,6 +197,21 @@ impl UserTestContext {
             .await;
     }
 
+    pub async fn refresh_aum(&self) -> DexResult {
+        let remaining_accounts = self.get_oracle_remaining_accounts().await;
+
+        set_refresh_aum::setup(
+            &mut self.context.borrow_mut(),
+            &self.program,
+            &self.user,
+            &self.dex,
+            &self.dex_info.borrow().price_feed,
+            remaining_accounts,
+        )
+        .await
+        .map_err(|_| error!(DexError::StaleAUM))
+    }
+
     pub async fn generate_random_user(&self) -> Keypair {
         let user = Keypair::new();
         //transfer sol to user
@@ -343,7 +362,6 @@ impl UserTestContext {
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
         let asset_info = self.dex_info.borrow().assets[asset as usize];
         let deposit_amount = convert_to_big_number(amount, asset_info.decimals);
-        let remaining_accounts = self.get_oracle_remaining_accounts().await;
 
         set_add_liquidity::setup(
             context,
@@ -351,12 +369,12 @@ impl UserTestContext {
             &self.user,
             &self.dex,
             &asset_info.mint,
+            &asset_info.oracle,
             &asset_info.vault,
             &self.dex_info.borrow().event_queue,
             &self.user_state,
             &self.dex_info.borrow().price_feed,
             deposit_amount,
-            remaining_accounts,
         )
         .await
         .unwrap();
@@ -369,6 +387,19 @@ impl UserTestContext {
         long: bool,
         amount: f64,
         leverage: u32,
+    ) -> Result<(), TransportError> {
+        self.open_with_stable_collateral(in_asset, market, long, amount, leverage, false)
+            .await
+    }
+
+    pub async fn open_with_stable_collateral(
+        &self,
+        in_asset: DexAsset,
+        market: DexMarket,
+        long: bool,
+        amount: f64,
+        leverage: u32,
+        stable_collateral: bool,
     ) -> Result<(), TransportError> {
         let di = self.dex_info.borrow();
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
@@ -381,7 +412,7 @@ impl UserTestContext {
         let in_mint_oracle = ai.oracle;
         let in_mint_vault = ai.vault;
 
-        let mai = if long {
+        let mai = if long && !stable_collateral {
             di.assets[mi.asset_index as usize]
         } else {
             di.assets[di.usdc_asset_index as usize]
@@ -394,6 +425,7 @@ impl UserTestContext {
         let market_oracle = mi.oracle;
 
         let user_state = self.user_state;
+        let referral_accounts = self.referral_accounts(context, &user_state).await;
 
         set_open::setup(
             context,
@@ -410,14 +442,350 @@ impl UserTestContext {
             &user_state,
             &di.event_queue,
             &self.dex_info.borrow().price_feed,
+            referral_accounts,
             market as u8,
             long,
             open_amount,
             leverage,
+            stable_collateral,
         )
         .await
     }
 
+    // A referred user passes its referral code and the referrer's user state
+    async fn referral_accounts(
+        &self,
+        context: &mut ProgramTestContext,
+        user_state: &Pubkey,
+    ) -> Vec<AccountMeta> {
+        let account = context
+            .banks_client
+            .get_account(*user_state)
+            .await
+            .unwrap()
+            .unwrap();
+        let referral_code = UserState::mount_buf(account.data)
+            .unwrap()
+            .borrow()
+            .meta
+            .referral_code;
+        if referral_code == Pubkey::default() {
+            return vec![];
+        }
+
+        let account = context
+            .banks_client
+            .get_account(referral_code)
+            .await
+            .unwrap()
+            .unwrap();
+        let rc = unsafe { account.data.as_ptr().add(8).cast::<ReferralCode>().as_ref() }.unwrap();
+
+        vec![
+            AccountMeta::new_readonly(referral_code, false),
+            AccountMeta::new(rc.referrer, false),
+        ]
+    }
+
+    pub async fn create_sub_account(&self, sub_account: u8) -> Result<Pubkey, TransportError> {
+        let (user_state, _) = Pubkey::find_program_address(
+            &[
+                self.dex.as_ref(),
+                self.user.pubkey().as_ref(),
+                &[sub_account],
+            ],
+            &self.program.id(),
+        );
+
+        let ix = compose_create_sub_account_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &user_state,
+            sub_account,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await.map(|_| user_state)
+    }
+
+    pub async fn deposit_asset(&self, asset: DexAsset, amount: f64) -> Result<(), TransportError> {
+        self.deposit_asset_into(&self.user_state, asset, amount)
+            .await
+    }
+
+    pub async fn deposit_asset_into(
+        &self,
+        user_state: &Pubkey,
+        asset: DexAsset,
+        amount: f64,
+    ) -> Result<(), TransportError> {
+        let ai = self.dex_info.borrow().assets[asset as usize];
+
+        let ix = compose_deposit_asset_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            user_state,
+            &get_associated_token_address(&self.user.pubkey(), &ai.mint),
+            &ai.vault,
+            asset as u8,
+            convert_to_big_number(amount, ai.decimals),
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    pub async fn transfer_user_asset(
+        &self,
+        from_user_state: &Pubkey,
+        to_user_state: &Pubkey,
+        asset: DexAsset,
+        amount: f64,
+    ) -> Result<(), TransportError> {
+        let ai = self.dex_info.borrow().assets[asset as usize];
+
+        let ix = compose_transfer_user_asset_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            from_user_state,
+            to_user_state,
+            asset as u8,
+            convert_to_big_number(amount, ai.decimals),
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    pub async fn set_margin_mode(&self, cross_margin: bool) -> Result<(), TransportError> {
+        let ix = compose_set_margin_mode_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &self.user_state,
+            cross_margin,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    // Liquidates a position of `target`, a cross-margin account also passes all oracles
+    pub async fn liquidate(
+        &self,
+        target: &UserTestContext,
+        market: DexMarket,
+        long: bool,
+    ) -> Result<(), TransportError> {
+        let account = self.get_account(target.user_state).await;
+        let us = UserState::mount_buf(account.data).unwrap();
+        let cross_margin = us.borrow().is_cross_margin();
+        let stable_collateral = us
+            .borrow()
+            .is_stable_collateral(market as u8, long)
+            .unwrap_or(false);
+
+        let mut remaining_accounts = self
+            .get_market_order_pool_remaining_accounts(market as u8)
+            .await;
+        if cross_margin {
+            remaining_accounts.append(&mut self.get_oracle_remaining_accounts().await);
+        }
+
+        let ix = {
+            let di = self.dex_info.borrow();
+            let mi = di.markets[market as usize];
+            let mai = if long && !stable_collateral {
+                di.assets[mi.asset_index as usize]
+            } else {
+                di.assets[di.usdc_asset_index as usize]
+            };
+
+            compose_liquidate_position_ix(
+                &self.program,
+                &self.user,
+                &self.dex,
+                &target.user.pubkey(),
+                &target.user_state,
+                &get_associated_token_address(&target.user.pubkey(), &mai.mint),
+                &mai.mint,
+                &mi.oracle,
+                &mai.vault,
+                &mai.program_signer,
+                &mi.order_book,
+                &mi.order_pool_entry_page,
+                &di.event_queue,
+                &di.price_feed,
+                remaining_accounts,
+                market as u8,
+                long,
+            )
+            .await
+        };
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    pub async fn set_position_leverage(
+        &self,
+        market: DexMarket,
+        long: bool,
+        leverage: u32,
+    ) -> Result<(), TransportError> {
+        let account = self.get_account(self.user_state).await;
+        let us = UserState::mount_buf(account.data).unwrap();
+        let stable_collateral = us
+            .borrow()
+            .is_stable_collateral(market as u8, long)
+            .unwrap_or(false);
+
+        let ix = {
+            let di = self.dex_info.borrow();
+            let mi = di.markets[market as usize];
+            let ai = if long && !stable_collateral {
+                di.assets[mi.asset_index as usize]
+            } else {
+                di.assets[di.usdc_asset_index as usize]
+            };
+
+            compose_set_position_leverage_ix(
+                &self.program,
+                &self.user,
+                &self.dex,
+                &mi.oracle,
+                &ai.vault,
+                &ai.program_signer,
+                &get_associated_token_address(&self.user.pubkey(), &ai.mint),
+                &self.user_state,
+                &di.event_queue,
+                &di.price_feed,
+                market as u8,
+                long,
+                leverage,
+            )
+            .await
+        };
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    // Settles the borrow fee of a position of `target` as a keeper
+    pub async fn settle_borrow_fee(
+        &self,
+        target: &UserTestContext,
+        market: DexMarket,
+        long: bool,
+    ) -> Result<(), TransportError> {
+        let ix = compose_settle_borrow_fee_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &target.user.pubkey(),
+            &target.user_state,
+            &self.dex_info.borrow().event_queue,
+            market as u8,
+            long,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    pub async fn settle_delisted(
+        &self,
+        target: &UserTestContext,
+        market: DexMarket,
+    ) -> Result<(), TransportError> {
+        let mi = self.dex_info.borrow().markets[market as usize];
+        let remaining_accounts = self
+            .get_market_order_pool_remaining_accounts(market as u8)
+            .await;
+
+        let ix = compose_settle_delisted_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &target.user.pubkey(),
+            &target.user_state,
+            &mi.order_book,
+            &mi.order_pool_entry_page,
+            &self.dex_info.borrow().event_queue,
+            remaining_accounts,
+            market as u8,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    pub async fn create_referral_code(&self, code: &str) -> Pubkey {
+        let mut code_bytes = [0u8; 16];
+        code_bytes[..code.len()].copy_from_slice(code.as_bytes());
+
+        let (referral_code, _) = Pubkey::find_program_address(
+            &[self.dex.as_ref(), b"referral", code_bytes.as_ref()],
+            &self.program.id(),
+        );
+
+        let ix = compose_create_referral_code_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &referral_code,
+            &self.user_state,
+            code_bytes,
+        )
+        .await;
+        self.process_ixs(vec![ix]).await.assert_ok();
+
+        referral_code
+    }
+
+    pub async fn set_referral(&self, referral_code: &Pubkey) -> Result<(), TransportError> {
+        self.set_referral_of(&self.user_state, referral_code).await
+    }
+
+    pub async fn set_referral_of(
+        &self,
+        user_state: &Pubkey,
+        referral_code: &Pubkey,
+    ) -> Result<(), TransportError> {
+        let account = self.get_account(*referral_code).await;
+        let rc = unsafe { account.data.as_ptr().add(8).cast::<ReferralCode>().as_ref() }.unwrap();
+
+        let ix = compose_set_referral_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            referral_code,
+            &rc.referrer,
+            user_state,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    async fn process_ixs(&self, ixs: Vec<Instruction>) -> Result<(), TransportError> {
+        let context = &mut self.context.borrow_mut();
+
+        let transaction = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&self.user.pubkey()),
+            &[&self.user],
+            context.banks_client.get_latest_blockhash().await.unwrap(),
+        );
+
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+    }
+
     pub async fn assert_open(
         &self,
         in_asset: DexAsset,
@@ -518,7 +886,6 @@ impl UserTestContext {
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
         let asset_info = self.dex_info.borrow().assets[asset as usize];
         let withdraw_vlp_amount = convert_to_big_number(vlp_amount, TEST_VLP_DECIMALS);
-        let remaining_accounts = self.get_oracle_remaining_accounts().await;
 
         set_remove_liquidity::setup(
             context,
@@ -526,18 +893,44 @@ impl UserTestContext {
             &self.user,
             &self.dex,
             &asset_info.mint,
+            &asset_info.oracle,
             &asset_info.vault,
             &asset_info.program_signer,
             &self.dex_info.borrow().event_queue,
             &self.user_state,
             &self.dex_info.borrow().price_feed,
             withdraw_vlp_amount,
-            remaining_accounts,
         )
         .await
         .unwrap();
     }
 
+    pub async fn flash_loan(
+        &self,
+        asset: DexAsset,
+        amount: f64,
+        inner_ixs: Vec<Instruction>,
+        repay: bool,
+    ) -> Result<(), TransportError> {
+        let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
+        let ai = self.dex_info.borrow().assets[asset as usize];
+        let loan_amount = convert_to_big_number(amount, ai.decimals);
+
+        set_flash_loan::setup(
+            context,
+            &self.program,
+            &self.user,
+            &self.dex,
+            &ai.mint,
+            &ai.vault,
+            &ai.program_signer,
+            loan_amount,
+            inner_ixs,
+            repay,
+        )
+        .await
+    }
+
     pub async fn assert_usdc_balance(&self, amount: f64) {
         let user_asset_acc = self.get_user_usdc_token_pubkey().await;
         self.assert_mint_balance(&user_asset_acc, DexAsset::USDC as usize, amount)
@@ -788,6 +1181,10 @@ impl UserTestContext {
     }
 
     pub async fn get_position_size(&self, market: DexMarket, long: bool) -> u64 {
+        self.get_position(market, long).await.size
+    }
+
+    pub async fn get_position(&self, market: DexMarket, long: bool) -> Position {
         let mut user_state_account = self.get_account(self.user_state).await;
         let user_state_account_info: AccountInfo =
             (&self.user_state, true, &mut user_state_account).into();
@@ -798,16 +1195,38 @@ impl UserTestContext {
         let position = ref_us
             .find_or_new_position(market as u8, false)
             .assert_unwrap();
-        let size = if long {
-            position.data.long.size
+        if long {
+            position.data.long
         } else {
-            position.data.short.size
-        };
-
-        size
+            position.data.short
+        }
     }
 
     pub async fn crank(&self, create_user_mint_acc: bool) {
+        self.try_crank(create_user_mint_acc, None).await.unwrap()
+    }
+
+    // Close orders paying out in another asset pass its accounts as the in mint,
+    // unless the accounts are overridden by `in_asset`
+    pub async fn try_crank(
+        &self,
+        create_user_mint_acc: bool,
+        in_asset: Option<DexAsset>,
+    ) -> Result<(), TransportError> {
+        self.crank_with(create_user_mint_acc, in_asset, true).await
+    }
+
+    // A keeper leaving out the referral accounts of a referred user
+    pub async fn try_crank_without_referral(&self) -> Result<(), TransportError> {
+        self.crank_with(true, None, false).await
+    }
+
+    async fn crank_with(
+        &self,
+        create_user_mint_acc: bool,
+        in_asset: Option<DexAsset>,
+        pass_referral: bool,
+    ) -> Result<(), TransportError> {
         let payer = self.generate_random_user().await;
         let di = self.dex_info.borrow();
 
@@ -818,7 +1237,7 @@ impl UserTestContext {
         let match_queue =
             SingleEventQueue::<MatchEvent>::mount(&match_queue_account_info, true).assert_unwrap();
         if match_queue.read_head().is_err() {
-            return; // No event
+            return Ok(()); // No event
         }
         let SingleEvent { data } = match_queue.read_head().assert_unwrap();
 
@@ -837,16 +1256,26 @@ impl UserTestContext {
 
         let order = ref_us.get_order(data.user_order_slot).assert_unwrap();
 
-        let in_asset = di.asset_as_ref(order.asset).assert_unwrap();
+        let in_asset_index = match in_asset {
+            Some(asset) => asset as u8,
+            None if !order.open && order.out_asset != NIL8 => order.out_asset,
+            None => order.asset,
+        };
+        let in_asset = di.asset_as_ref(in_asset_index).assert_unwrap();
 
         let mai = di
             .market_asset_as_ref(order.market, order.long)
             .assert_unwrap();
 
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
+        let referral_accounts = if pass_referral {
+            self.referral_accounts(context, &user_state).await
+        } else {
+            vec![]
+        };
 
-        let out_mint = if order.open {
-            in_asset.mint // don't care
+        let out_mint = if order.open || order.out_asset != NIL8 {
+            in_asset.mint
         } else {
             mai.mint
         };
@@ -871,10 +1300,10 @@ impl UserTestContext {
             &di.match_queue,
             &di.event_queue,
             &self.dex_info.borrow().price_feed,
+            referral_accounts,
             create_user_mint_acc,
         )
         .await
-        .unwrap();
     }
 
     pub async fn fill(&self, market: DexMarket) {
@@ -911,6 +1340,39 @@ impl UserTestContext {
         long: bool,
         price: f64,
         size: f64,
+    ) -> Result<(), TransportError> {
+        self.limit_ask(market, long, price, size, NIL8, 0).await
+    }
+
+    pub async fn ask_with_payout(
+        &self,
+        market: DexMarket,
+        long: bool,
+        price: f64,
+        size: f64,
+        out_asset: DexAsset,
+        minimum_out: f64,
+    ) -> Result<(), TransportError> {
+        let decimals = self.dex_info.borrow().assets[out_asset as usize].decimals;
+        self.limit_ask(
+            market,
+            long,
+            price,
+            size,
+            out_asset as u8,
+            convert_to_big_number(minimum_out, decimals),
+        )
+        .await
+    }
+
+    async fn limit_ask(
+        &self,
+        market: DexMarket,
+        long: bool,
+        price: f64,
+        size: f64,
+        out_asset: u8,
+        minimum_out: u64,
     ) -> Result<(), TransportError> {
         let di = self.dex_info.borrow();
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
@@ -935,6 +1397,8 @@ impl UserTestContext {
             long,
             convert_to_big_number(price, TEST_USDC_DECIMALS),
             convert_to_big_number(size, mi.decimals),
+            out_asset,
+            minimum_out,
         )
         .await
     }
@@ -2140,8 +2604,25 @@ impl UserTestContext {
     }
 
     pub async fn assert_asset(&self, asset: DexAsset, amount: f64) {
+        self.assert_asset_of(&self.user_state, asset, amount).await
+    }
+
+    // Free balance of an asset in the user state
+    pub async fn asset_amount(&self, asset: DexAsset) -> u64 {
         let user_state_account = self.get_account(self.user_state).await;
         let us = UserState::mount_buf(user_state_account.data).unwrap();
+        let amount = us
+            .borrow()
+            .find_asset(asset as u8)
+            .map(|a| a.data.amount)
+            .unwrap_or(0);
+
+        amount
+    }
+
+    pub async fn assert_asset_of(&self, user_state: &Pubkey, asset: DexAsset, amount: f64) {
+        let user_state_account = self.get_account(*user_state).await;
+        let us = UserState::mount_buf(user_state_account.data).unwrap();
 
         let ai = self.dex_info.borrow().assets[asset as usize];
         let big_amount = (amount * (10u64.pow(ai.decimals as u32) as f64)) as u64;
@@ -2156,6 +2637,10 @@ impl UserTestContext {
     }
 
     pub async fn withdraw_asset(&self, asset: DexAsset) -> DexResult {
+        self.withdraw_asset_from(&self.user_state, asset).await
+    }
+
+    pub async fn withdraw_asset_from(&self, user_state: &Pubkey, asset: DexAsset) -> DexResult {
         let ai = self.dex_info.borrow().assets[asset as usize];
 
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
@@ -2164,7 +2649,7 @@ impl UserTestContext {
             &self.program,
             &self.user,
             &self.dex,
-            &self.user_state,
+            user_state,
             &ai.mint,
             &ai.vault,
             &ai.program_signer,
@@ -2295,19 +2780,15 @@ impl UserTestContext {
     pub async fn compound(&self) -> DexResult {
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
 
-        let remaining_accounts = self.get_oracle_remaining_accounts().await;
-
         if let Ok(_) = set_compound::setup(
             context,
             &self.program,
             &self.user,
             &self.dex,
-            &self.dex_info.borrow().price_feed,
             &self.user_state,
             &self.dex_info.borrow().vdx_pool.mint,
             &self.dex_info.borrow().vdx_pool.program_signer,
             &self.dex_info.borrow().vdx_pool.vault,
-            remaining_accounts,
         )
         .await
         {
@@ -2320,20 +2801,16 @@ impl UserTestContext {
     pub async fn stake_vdx(&self, amount: u64) -> DexResult {
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
 
-        let remaining_accounts = self.get_oracle_remaining_accounts().await;
-
         if let Ok(_) = set_stake_vdx::setup(
             context,
             &self.program,
             &self.user,
             &self.dex,
-            &self.dex_info.borrow().price_feed,
             &self.user_state,
             &self.dex_info.borrow().event_queue,
             &self.dex_info.borrow().vdx_pool.mint,
             &self.dex_info.borrow().vdx_pool.program_signer,
             &self.dex_info.borrow().vdx_pool.vault,
-            remaining_accounts,
             amount,
         )
         .await
@@ -2347,20 +2824,16 @@ impl UserTestContext {
     pub async fn redeem_vdx(&self, amount: u64) -> DexResult {
         let context: &mut ProgramTestContext = &mut self.context.borrow_mut();
 
-        let remaining_accounts = self.get_oracle_remaining_accounts().await;
-
         if let Ok(_) = set_redeem_vdx::setup(
             context,
             &self.program,
             &self.user,
             &self.dex,
-            &self.dex_info.borrow().price_feed,
             &self.user_state,
             &self.dex_info.borrow().event_queue,
             &self.dex_info.borrow().vdx_pool.mint,
             &self.dex_info.borrow().vdx_pool.program_signer,
             &self.dex_info.borrow().vdx_pool.vault,
-            remaining_accounts,
             amount,
         )
         .await
@@ -2371,6 +2844,90 @@ impl UserTestContext {
         }
     }
 
+    pub async fn lock_vdx(&self, amount: u64, lock_tier: u8) -> Result<(), TransportError> {
+        let vdx_pool = self.dex_info.borrow().vdx_pool;
+        let user_mint_acc = get_associated_token_address(&self.user.pubkey(), &vdx_pool.mint);
+
+        let ix = compose_lock_vdx_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &user_mint_acc,
+            &self.user_state,
+            &self.dex_info.borrow().event_queue,
+            &vdx_pool.mint,
+            &vdx_pool.program_signer,
+            &vdx_pool.vault,
+            amount,
+            lock_tier,
+        )
+        .await;
+
+        self.process_ixs(vec![ix]).await
+    }
+
+    // Stakes the VDX and opens with the market asset in one transaction
+    pub async fn stake_vdx_and_open(
+        &self,
+        vdx_amount: u64,
+        market: DexMarket,
+        long: bool,
+        amount: f64,
+        leverage: u32,
+    ) -> Result<(), TransportError> {
+        let di = *self.dex_info.borrow();
+        let mi = di.markets[market as usize];
+        let ai = if long {
+            di.assets[mi.asset_index as usize]
+        } else {
+            di.assets[di.usdc_asset_index as usize]
+        };
+
+        let vdx_mint_acc = get_associated_token_address(&self.user.pubkey(), &di.vdx_pool.mint);
+        let stake_ix = compose_stake_vdx_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &vdx_mint_acc,
+            &self.user_state,
+            &di.event_queue,
+            &di.vdx_pool.mint,
+            &di.vdx_pool.program_signer,
+            &di.vdx_pool.vault,
+            vdx_amount,
+        )
+        .await;
+
+        let referral_accounts = self
+            .referral_accounts(&mut self.context.borrow_mut(), &self.user_state)
+            .await;
+        let open_ix = compose_open_market_position_ix(
+            &self.program,
+            &self.user,
+            &self.dex,
+            &ai.mint,
+            &ai.oracle,
+            &ai.vault,
+            &ai.mint,
+            &ai.oracle,
+            &ai.vault,
+            &mi.oracle,
+            &get_associated_token_address(&self.user.pubkey(), &ai.mint),
+            &self.user_state,
+            &di.event_queue,
+            &di.price_feed,
+            referral_accounts,
+            market as u8,
+            long,
+            convert_to_big_number(amount, ai.decimals),
+            leverage,
+            false,
+        )
+        .await;
+
+        self.process_ixs(vec![stake_ix, open_ix]).await
+    }
+
     pub async fn staked_vdx(&self, timestamp: i64) -> DexResult<u64> {
         let mut user_state_account = self.get_account(self.user_state).await;
         let user_state_account_info: AccountInfo =
@@ -5,8 +5,9 @@ mod utils;
 
 use solana_program_test::tokio;
 
-use crate::utils::{minus_add_fee, DexAsset};
+use crate::utils::{add_fee, minus_add_fee, DexAsset, DexMarket, TestResult};
 use context::DexTestContext;
+use dex_program::utils::MAX_AUM_AGE;
 
 #[tokio::test]
 async fn test_add_liquidity_with_usdc() {
@@ -190,3 +191,29 @@ async fn test_add_multiple_liquidity_use_price_feed() {
 
     market.assert_rewards(40.0).await;
 }
+
+#[tokio::test]
+async fn test_add_liquidity_after_position_change() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    // The position change is applied to the cached AUM, no refresh is needed
+    user.add_liquidity_with_usdc(10_000.0).await;
+
+    // Staking doesn't need a fresh AUM, the fees wait for the next refresh
+    dtc.after(MAX_AUM_AGE + 1).await;
+    user.compound().await.assert_ok();
+    user.assert_fee(DexAsset::USDC, add_fee(10_000.0)).await;
+
+    user.refresh_aum().await.assert_ok();
+    user.assert_fee(DexAsset::USDC, 0.).await;
+}
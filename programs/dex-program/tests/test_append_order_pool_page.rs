@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_append_order_pool_page() {
+    let mut dtc = DexTestContext::new().await;
+    let market = DexMarket::BTC as u8;
+
+    let page0 = dtc
+        .append_order_pool_page(market, &[])
+        .await
+        .assert_unwrap();
+    let mi = dtc.market_info(market).await;
+    assert_eq!(mi.order_pool_remaining_pages_number, 1);
+    assert_eq!(mi.order_pool_remaining_pages[0], page0);
+
+    // The current remaining pages must be passed in order
+    dtc.append_order_pool_page(market, &[]).await.assert_err();
+
+    let page1 = dtc
+        .append_order_pool_page(market, &[page0])
+        .await
+        .assert_unwrap();
+    let mi = dtc.market_info(market).await;
+    assert_eq!(mi.order_pool_remaining_pages_number, 2);
+    assert_eq!(mi.order_pool_remaining_pages[1], page1);
+
+    // Other markets are untouched
+    assert_eq!(
+        dtc.market_info(DexMarket::ETH as u8)
+            .await
+            .order_pool_remaining_pages_number,
+        0
+    );
+
+    // Orders mount the grown order pool
+    for user in dtc.user_context.iter_mut() {
+        user.refresh_dex_info().await;
+    }
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(21000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_bid(DexAsset::BTC, DexMarket::BTC, true, 20000., 0.1, 10 * 1000)
+        .await;
+
+    user.mock_btc_price(20000.).await;
+    user.fill(DexMarket::BTC).await;
+    user.crank(true).await;
+    user.assert_no_match_event().await;
+    alice.assert_no_order().await;
+}
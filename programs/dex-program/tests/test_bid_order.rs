@@ -5,7 +5,7 @@ mod utils;
 
 use solana_program_test::tokio;
 
-use crate::utils::{DexAsset, DexMarket};
+use crate::utils::{DexAsset, DexMarket, TestResult};
 use context::DexTestContext;
 
 #[tokio::test]
@@ -158,3 +158,51 @@ async fn test_bid_fail_of_price_use_price_feed() {
         .await;
     alice.assert_usdc_balance(2000.).await;
 }
+
+#[tokio::test]
+async fn test_bid_fail_of_stable_collateral_long() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    // Prepare liquidity & price
+    user.mock_btc_price(20000.).await;
+    user.mock_eth_price(2000.).await;
+    user.mock_sol_price(20.).await;
+
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_eth(1000.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+
+    // Alice opens a long that keeps USDC collateral
+    alice.mint_usdc(4000.).await;
+    alice
+        .open_with_stable_collateral(DexAsset::USDC, DexMarket::BTC, true, 2000., 5 * 1000, true)
+        .await
+        .assert_ok();
+
+    // Limit bids open with BTC collateral and can't add to it
+    alice.mint_btc(0.2).await;
+    alice
+        .assert_bid_fail(DexAsset::BTC, DexMarket::BTC, true, 19000., 0.2, 8 * 1000)
+        .await;
+    alice.assert_btc_balance(0.2).await;
+
+    // Short bids are not affected
+    alice
+        .assert_bid(
+            DexAsset::USDC,
+            DexMarket::BTC,
+            false,
+            22000.,
+            2000.,
+            5 * 1000,
+        )
+        .await;
+
+    // Nor are other markets
+    alice.mint_eth(1.).await;
+    alice
+        .assert_bid(DexAsset::ETH, DexMarket::ETH, true, 1900., 1., 5 * 1000)
+        .await;
+}
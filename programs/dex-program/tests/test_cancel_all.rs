@@ -5,7 +5,7 @@ mod utils;
 
 use solana_program_test::tokio;
 
-use crate::utils::{btc, collateral_to_size, DexAsset, DexMarket};
+use crate::utils::{btc, collateral_to_size, DexAsset, DexMarket, TestResult};
 use context::DexTestContext;
 
 #[tokio::test]
@@ -233,3 +233,42 @@ async fn test_cancel_bid_and_ask_orders() {
     alice.assert_eth_balance(1.).await;
     alice.assert_no_order().await;
 }
+
+#[tokio::test]
+async fn test_cancel_all_with_order_pool_pages() {
+    let mut dtc = DexTestContext::new().await;
+
+    // Remaining pages of the order pool follow its entry page in the remaining accounts
+    let page = dtc
+        .append_order_pool_page(DexMarket::BTC as u8, &[])
+        .await
+        .assert_unwrap();
+    dtc.append_order_pool_page(DexMarket::BTC as u8, &[page])
+        .await
+        .assert_unwrap();
+    for user in dtc.user_context.iter_mut() {
+        user.refresh_dex_info().await;
+    }
+
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.mock_eth_price(2000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_eth(1000.).await;
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_bid(DexAsset::BTC, DexMarket::BTC, true, 19000., 0.1, 10 * 1000)
+        .await;
+    alice.mint_eth(1.).await;
+    alice
+        .assert_bid(DexAsset::ETH, DexMarket::ETH, true, 1900., 1., 10 * 1000)
+        .await;
+
+    alice.cancel_call().await;
+    alice.assert_btc_balance(0.1).await;
+    alice.assert_eth_balance(1.).await;
+    alice.assert_no_order().await;
+}
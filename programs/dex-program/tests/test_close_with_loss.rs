@@ -171,6 +171,8 @@ async fn test_close_btc_short_with_loss() {
 
     for _ in 0..32 {
         dtc.after(DAY).await;
+        // Fees are collected with the keeper's AUM refresh
+        user.refresh_aum().await.assert_ok();
         bob.compound().await.assert_ok();
 
         bob.assert_liquidity(
@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{close_fee, swap_fee, DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_crank_ask_payout_swap() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    // Prepare liquidity & price
+    user.mock_btc_price(20000.).await;
+    user.mock_eth_price(2000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+
+    // Alice open long
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_collateral = 0.1 - expected_open_fee;
+    let expected_size = expected_collateral * 10.;
+
+    // Close all @21000 and pay out in USDC
+    alice
+        .ask_with_payout(DexMarket::BTC, true, 21000., 1000., DexAsset::USDC, 100.)
+        .await
+        .assert_ok();
+
+    user.mock_btc_price(21000.).await;
+    user.fill(DexMarket::BTC).await;
+    user.read_match_event().await;
+
+    // The payout asset accounts are required
+    user.try_crank(true, Some(DexAsset::ETH)).await.assert_err();
+    user.try_crank(true, Some(DexAsset::BTC)).await.assert_err();
+    user.read_match_event().await;
+
+    user.try_crank(true, None).await.assert_ok();
+    user.assert_no_match_event().await;
+
+    let expected_profit = expected_size * (21000. - 20000.) / 20000.;
+    let withdrawable = expected_collateral - close_fee(expected_size) + expected_profit;
+    let expected_out = (withdrawable - swap_fee(withdrawable)) * 21000.;
+
+    // The swap rounds on the integer amounts
+    let usdc_acc = alice.get_user_usdc_token_pubkey().await;
+    let out = alice.mint_balance(DexAsset::USDC, &usdc_acc).await;
+    assert!((out - expected_out).abs() < 0.001);
+    alice.assert_btc_balance(0.).await;
+    alice
+        .assert_position(DexMarket::BTC, true, 0., 0., 0., 0., 0.)
+        .await;
+}
+
+#[tokio::test]
+async fn test_crank_ask_payout_slippage() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    // Prepare liquidity & price
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+
+    // Alice open long
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_collateral = 0.1 - expected_open_fee;
+    let expected_size = expected_collateral * 10.;
+
+    // The minimum out can't be met at the fill price
+    alice
+        .ask_with_payout(DexMarket::BTC, true, 21000., 1000., DexAsset::USDC, 10000.)
+        .await
+        .assert_ok();
+
+    user.mock_btc_price(21000.).await;
+    user.fill(DexMarket::BTC).await;
+    user.read_match_event().await;
+
+    // The order is still cranked and pays out in the collateral asset
+    user.try_crank(true, None).await.assert_ok();
+    user.assert_no_match_event().await;
+
+    let expected_profit = expected_size * (21000. - 20000.) / 20000.;
+    let withdrawable = expected_collateral - close_fee(expected_size) + expected_profit;
+
+    alice.assert_btc_balance(0.).await;
+    alice.assert_asset(DexAsset::BTC, withdrawable).await;
+    alice
+        .assert_position(DexMarket::BTC, true, 0., 0., 0., 0., 0.)
+        .await;
+}
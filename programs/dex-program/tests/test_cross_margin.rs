@@ -0,0 +1,92 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{usdc, DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_cross_margin_close_covers_shortfall() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+    user.feed_usdc_price(1.).await;
+
+    alice.set_margin_mode(true).await.assert_ok();
+    alice.mint_usdc(1000.).await;
+    alice.deposit_asset(DexAsset::USDC, 1000.).await.assert_ok();
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    // The loss exceeds the collateral, the free USDC balance keeps the account healthy
+    user.mock_btc_price(17800.).await;
+    user.liquidate(alice, DexMarket::BTC, true)
+        .await
+        .assert_err();
+
+    let usdc_liquidity = dtc.asset_info(DexAsset::USDC as u8).await.liquidity_amount;
+
+    let size = alice.get_position_size(DexMarket::BTC, true).await;
+    alice
+        .assert_close(DexMarket::BTC, true, size as f64 / 1e9)
+        .await;
+    alice.assert_btc_balance(0.).await;
+
+    // The loss of 0.106796117 BTC and the close fee exceed the 0.097087379 BTC collateral
+    // by about 0.014563 BTC, taken from the USDC balance at the close price
+    let seized = usdc(1000.) - alice.asset_amount(DexAsset::USDC).await;
+    assert!(seized > usdc(259.) && seized < usdc(259.5));
+    assert_eq!(
+        dtc.asset_info(DexAsset::USDC as u8).await.liquidity_amount,
+        usdc_liquidity + seized
+    );
+}
+
+#[tokio::test]
+async fn test_cross_margin_liquidation_keeps_remainder() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+
+    alice.set_margin_mode(true).await.assert_ok();
+    alice.mint_usdc(10.).await;
+    alice.deposit_asset(DexAsset::USDC, 10.).await.assert_ok();
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    user.mock_btc_price(18500.).await;
+    user.liquidate(alice, DexMarket::BTC, true)
+        .await
+        .assert_err();
+
+    user.mock_btc_price(18200.).await;
+    user.liquidate(alice, DexMarket::BTC, true)
+        .await
+        .assert_ok();
+
+    // The remainder stays in the account instead of going to the wallet
+    alice.assert_btc_balance(0.).await;
+    assert!(alice.asset_amount(DexAsset::BTC).await > 0);
+    alice.assert_asset(DexAsset::USDC, 10.).await;
+
+    alice.withdraw_asset(DexAsset::BTC).await.assert_ok();
+    alice.withdraw_asset(DexAsset::USDC).await.assert_ok();
+    alice.assert_usdc_balance(10.).await;
+}
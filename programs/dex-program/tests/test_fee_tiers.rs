@@ -0,0 +1,77 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{DexAsset, DexMarket, TestResult, DAY};
+use context::{DexTestContext, UserTestContext};
+use dex_program::utils::UPDATE_REWARDS_PERIOD;
+
+// Vest some VDX from the VLP rewards and redeem it to the wallet
+async fn vest_vdx(dtc: &DexTestContext, users: &[&UserTestContext]) -> Vec<u64> {
+    for user in users {
+        user.add_liquidity_with_sol(1000.).await;
+    }
+
+    dtc.after(UPDATE_REWARDS_PERIOD).await;
+    for user in users {
+        user.compound().await.assert_ok();
+    }
+
+    let time = dtc.after(DAY).await;
+    let mut vested = vec![];
+    for user in users {
+        user.compound().await.assert_ok();
+        let vdx = user.staked_vdx(time).await.assert_unwrap();
+        assert!(vdx > 0);
+        user.redeem_vdx(vdx).await.assert_ok();
+        vested.push(vdx);
+    }
+
+    vested
+}
+
+#[tokio::test]
+async fn test_fee_tiers_count_locked_vdx() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+    let carol = &dtc.user_context[3];
+
+    let vested = vest_vdx(&dtc, &[alice, bob]).await;
+    dtc.set_fee_tiers([1, 0, 0, 0], [50, 0, 0, 0])
+        .await
+        .assert_ok();
+
+    user.mock_btc_price(20000.).await;
+    user.refresh_aum().await.assert_ok();
+    user.add_liquidity_with_btc(10.).await;
+
+    // Carol has no VDX and pays the full open fee
+    carol.mint_btc(0.1).await;
+    carol
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+    let full_fee = carol.get_position(DexMarket::BTC, true).await;
+
+    // A stake made in the same transaction as the open earns no discount
+    alice.mint_btc(0.1).await;
+    alice
+        .stake_vdx_and_open(vested[0], DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await
+        .assert_ok();
+    let no_discount = alice.get_position(DexMarket::BTC, true).await;
+    assert_eq!(no_discount.collateral, full_fee.collateral);
+    assert_eq!(no_discount.size, full_fee.size);
+
+    // A running lock does
+    bob.lock_vdx(vested[1], 0).await.assert_ok();
+    bob.mint_btc(0.1).await;
+    bob.assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+    let discounted = bob.get_position(DexMarket::BTC, true).await;
+    assert!(discounted.collateral > full_fee.collateral);
+}
@@ -0,0 +1,88 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use anchor_client::solana_sdk::signer::Signer;
+use solana_program_test::tokio;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::utils::{compose_add_liquidity_ix, convert_to_big_number, DexAsset, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_flash_loan() {
+    let dtc = DexTestContext::new().await;
+    let alice = &dtc.user_context[0];
+    let bob = &dtc.user_context[1];
+
+    alice.add_liquidity_with_btc(10.).await;
+    alice.assert_liquidity(DexAsset::BTC, 9.99).await;
+    alice.assert_fee(DexAsset::BTC, 0.01).await;
+
+    // 0.09% flash loan fee
+    bob.mint_btc(0.01).await;
+    bob.flash_loan(DexAsset::BTC, 5., vec![], true)
+        .await
+        .assert_ok();
+
+    bob.assert_btc_balance(0.0055).await;
+    bob.assert_liquidity(DexAsset::BTC, 9.99).await;
+    bob.assert_fee(DexAsset::BTC, 0.0145).await;
+}
+
+#[tokio::test]
+async fn test_flash_loan_without_repay() {
+    let dtc = DexTestContext::new().await;
+    let alice = &dtc.user_context[0];
+    let bob = &dtc.user_context[1];
+
+    alice.add_liquidity_with_btc(10.).await;
+
+    bob.mint_btc(0.01).await;
+    bob.flash_loan(DexAsset::BTC, 5., vec![], false)
+        .await
+        .assert_err();
+
+    bob.flash_loan(DexAsset::BTC, 10., vec![], true)
+        .await
+        .assert_err();
+
+    bob.assert_btc_balance(0.01).await;
+}
+
+#[tokio::test]
+async fn test_flash_loan_rejects_pool_instructions() {
+    let dtc = DexTestContext::new().await;
+    let alice = &dtc.user_context[0];
+    let bob = &dtc.user_context[1];
+
+    alice.add_liquidity_with_btc(10.).await;
+    bob.mint_btc(0.01).await;
+
+    // Adding the borrowed funds as liquidity would mint VLP against an
+    // overstated pool
+    let di = bob.dex_info.borrow();
+    let ai = di.assets[DexAsset::BTC as usize];
+    let add_liquidity_ix = compose_add_liquidity_ix(
+        &bob.program,
+        &bob.user,
+        &bob.dex,
+        &ai.mint,
+        &ai.oracle,
+        &ai.vault,
+        &get_associated_token_address(&bob.user.pubkey(), &ai.mint),
+        &di.event_queue,
+        &bob.user_state,
+        &di.price_feed,
+        convert_to_big_number(5., ai.decimals),
+    )
+    .await;
+
+    bob.flash_loan(DexAsset::BTC, 5., vec![add_liquidity_ix], true)
+        .await
+        .assert_err();
+
+    bob.assert_btc_balance(0.01).await;
+    bob.assert_liquidity(DexAsset::BTC, 9.99).await;
+}
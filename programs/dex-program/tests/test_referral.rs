@@ -0,0 +1,139 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{add_fee, minus_add_fee, DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_referral_open() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+
+    dtc.set_referral_rates(51, 20).await.assert_err();
+    dtc.set_referral_rates(10, 20).await.assert_ok();
+
+    let referral_code = bob.create_referral_code("bob").await;
+    bob.set_referral(&referral_code).await.assert_err();
+    alice.set_referral(&referral_code).await.assert_ok();
+
+    user.add_liquidity_with_btc(10.).await;
+    user.mock_btc_price(20000.).await;
+
+    // Alice opens with the referral accounts
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_discount = expected_open_fee * 0.1;
+    let expected_rebate = expected_open_fee * 0.2;
+    let expected_size = (0.1 - expected_open_fee) * 10.;
+
+    alice.assert_asset(DexAsset::BTC, expected_discount).await;
+    bob.assert_asset(DexAsset::BTC, expected_rebate).await;
+    user.assert_fee(
+        DexAsset::BTC,
+        add_fee(10.) + expected_open_fee - expected_discount - expected_rebate,
+    )
+    .await;
+    user.assert_liquidity(DexAsset::BTC, minus_add_fee(10.) - expected_size)
+        .await;
+}
+
+#[tokio::test]
+async fn test_referral_crank_without_accounts() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+
+    dtc.set_referral_rates(10, 20).await.assert_ok();
+
+    let referral_code = bob.create_referral_code("bob").await;
+    alice.set_referral(&referral_code).await.assert_ok();
+
+    user.mock_btc_price(21000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_bid(DexAsset::BTC, DexMarket::BTC, true, 20000., 0.1, 10 * 1000)
+        .await;
+
+    user.mock_btc_price(20000.).await;
+    user.fill(DexMarket::BTC).await;
+
+    // The keeper can't skip the discount and rebate by leaving out the referral accounts
+    user.try_crank_without_referral().await.assert_err();
+    user.crank(true).await;
+    user.assert_no_match_event().await;
+    alice.assert_no_order().await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_discount = expected_open_fee * 0.1;
+    let expected_rebate = expected_open_fee * 0.2;
+    let expected_collateral = 0.1 - expected_open_fee;
+    let expected_size = expected_collateral * 10.;
+    alice
+        .assert_position(
+            DexMarket::BTC,
+            true,
+            20000.,
+            expected_size,
+            expected_collateral,
+            expected_size,
+            0.,
+        )
+        .await;
+    alice.assert_asset(DexAsset::BTC, expected_discount).await;
+    bob.assert_asset(DexAsset::BTC, expected_rebate).await;
+    user.assert_fee(
+        DexAsset::BTC,
+        add_fee(10.) + expected_open_fee - expected_discount - expected_rebate,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_referral_sub_account() {
+    let dtc = DexTestContext::new().await;
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+
+    let referral_code = alice.create_referral_code("alice").await;
+    let sub = alice.create_sub_account(1).await.assert_unwrap();
+
+    // A sub-account can't be referred by its owner
+    alice
+        .set_referral_of(&sub, &referral_code)
+        .await
+        .assert_err();
+
+    // Another owner's sub-account can
+    let bob_sub = bob.create_sub_account(1).await.assert_unwrap();
+    bob.set_referral_of(&bob_sub, &referral_code)
+        .await
+        .assert_ok();
+}
+
+#[tokio::test]
+async fn test_timelock_referral_rates() {
+    let dtc = DexTestContext::new().await;
+    let oracle = dtc.dex_info.borrow().markets[0].oracle;
+
+    dtc.init_timelock(3600).await;
+
+    dtc.set_referral_rates(10, 20).await.assert_ok();
+    assert_eq!(dtc.referral_rates().await, (0, 0));
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_ok();
+    assert_eq!(dtc.referral_rates().await, (10, 20));
+}
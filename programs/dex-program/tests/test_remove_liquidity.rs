@@ -5,6 +5,7 @@ mod utils;
 
 use solana_program_test::tokio;
 
+use crate::utils::TestResult;
 use context::DexTestContext;
 
 #[tokio::test]
@@ -98,6 +99,7 @@ async fn test_remove_liquidity_withdraw_sol_use_price_feed() {
 
     alice.mock_sol_price(100.0).await;
     alice.feed_sol_price(20.0).await;
+    alice.refresh_aum().await.assert_ok();
     alice.add_liquidity_with_sol(1.0).await;
 
     //0.1% add liquidity fee
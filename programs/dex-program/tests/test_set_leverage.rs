@@ -0,0 +1,120 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_set_leverage() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.mint_btc(0.2).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_collateral = 0.1 - expected_open_fee;
+    let expected_size = expected_collateral * 10.;
+
+    // Lowering the leverage takes the extra collateral from the wallet
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 5 * 1000)
+        .await
+        .assert_ok();
+
+    let added = expected_size / 5. - expected_collateral;
+    alice.assert_btc_balance(0.1 - added).await;
+    alice
+        .assert_position(
+            DexMarket::BTC,
+            true,
+            20000.,
+            expected_size,
+            expected_size / 5.,
+            expected_size,
+            0.,
+        )
+        .await;
+    user.assert_collateral(DexAsset::BTC, expected_size / 5.)
+        .await;
+
+    // Raising it refunds the collateral
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 10 * 1000)
+        .await
+        .assert_ok();
+
+    alice.assert_btc_balance(0.1).await;
+    user.assert_collateral(DexAsset::BTC, expected_collateral)
+        .await;
+
+    // The remaining collateral has to keep the position clear of liquidation
+    user.mock_btc_price(19000.).await;
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 30 * 1000)
+        .await
+        .assert_err();
+}
+
+#[tokio::test]
+async fn test_set_leverage_cross_margin() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.set_margin_mode(true).await.assert_ok();
+    alice.mint_btc(0.15).await;
+    alice.deposit_asset(DexAsset::BTC, 0.05).await.assert_ok();
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    let expected_open_fee = 0.002912621;
+    let expected_collateral = 0.1 - expected_open_fee;
+    let expected_size = expected_collateral * 10.;
+    let added = expected_size / 5. - expected_collateral;
+
+    // The free balance can't cover the extra collateral
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 5 * 1000)
+        .await
+        .assert_err();
+
+    alice.mint_btc(0.1).await;
+    alice.deposit_asset(DexAsset::BTC, 0.1).await.assert_ok();
+
+    // It is taken from the free balance, the wallet is left untouched
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 5 * 1000)
+        .await
+        .assert_ok();
+
+    alice.assert_btc_balance(0.).await;
+    alice.assert_asset(DexAsset::BTC, 0.15 - added).await;
+    user.assert_collateral(DexAsset::BTC, expected_size / 5.)
+        .await;
+
+    // And refunded to it
+    alice
+        .set_position_leverage(DexMarket::BTC, true, 10 * 1000)
+        .await
+        .assert_ok();
+
+    alice.assert_btc_balance(0.).await;
+    alice.assert_asset(DexAsset::BTC, 0.15).await;
+    user.assert_collateral(DexAsset::BTC, expected_collateral)
+        .await;
+}
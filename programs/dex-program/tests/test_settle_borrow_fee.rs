@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{DexAsset, DexMarket, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_settle_borrow_fee() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+
+    alice.mint_btc(0.1).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+
+    // Nothing accrued yet
+    user.settle_borrow_fee(alice, DexMarket::BTC, true)
+        .await
+        .assert_err();
+
+    let position = alice.get_position(DexMarket::BTC, true).await;
+    let ai = dtc.asset_info(DexAsset::BTC as u8).await;
+    let interval = dtc
+        .market_info(DexMarket::BTC as u8)
+        .await
+        .charge_borrow_fee_interval;
+
+    let now = dtc.after(3600).await;
+    user.settle_borrow_fee(alice, DexMarket::BTC, true)
+        .await
+        .assert_ok();
+
+    // The fee is moved from the position collateral to the pool fees
+    let settled = alice.get_position(DexMarket::BTC, true).await;
+    let fee = position.collateral - settled.collateral;
+    let expected_fee =
+        position.borrowed_amount * ai.borrow_fee_rate as u64 * 3600 / 1_000_000 / interval;
+    assert!(fee >= expected_fee && fee > 0);
+    assert_eq!(settled.size, position.size);
+    assert_eq!(settled.cumulative_fund_fee, 0);
+
+    let settled_ai = dtc.asset_info(DexAsset::BTC as u8).await;
+    assert_eq!(settled_ai.fee_amount, ai.fee_amount + fee);
+    assert_eq!(settled_ai.collateral_amount, ai.collateral_amount - fee);
+
+    // Only the time paid by the fee is settled
+    assert!(settled.last_fill_time > position.last_fill_time);
+    assert!(settled.last_fill_time <= now);
+}
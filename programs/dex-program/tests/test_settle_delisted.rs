@@ -0,0 +1,116 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{close_fee, DexAsset, DexMarket, TestResult, DAY};
+use context::DexTestContext;
+use dex_program::{
+    dex::{Position, TradingScope, TradingState},
+    utils::BORROW_FEE_RATE_BASE,
+};
+
+#[tokio::test]
+async fn test_settle_delisted() {
+    let dtc = DexTestContext::new().await;
+    let user = &dtc.user_context[0];
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+
+    // Prepare liquidity & price
+    user.mock_btc_price(20000.).await;
+    user.add_liquidity_with_btc(10.).await;
+    user.add_liquidity_with_usdc(100000.).await;
+
+    // Alice opens a long, places a bid and an ask
+    alice.mint_btc(0.2).await;
+    alice
+        .assert_open(DexAsset::BTC, DexMarket::BTC, true, 0.1, 10 * 1000)
+        .await;
+    alice
+        .assert_bid(DexAsset::BTC, DexMarket::BTC, true, 19000., 0.1, 10 * 1000)
+        .await;
+    alice
+        .assert_ask(DexMarket::BTC, true, 22000., u64::MAX as f64)
+        .await;
+
+    // Bob opens a short
+    bob.mint_usdc(2000.).await;
+    bob.assert_open(DexAsset::USDC, DexMarket::BTC, false, 2000., 10 * 1000)
+        .await;
+
+    let market = DexMarket::BTC as u8;
+    let reduce_only = TradingState::ReduceOnly as u8;
+    dtc.set_trading_state(TradingScope::Market as u8, market, reduce_only)
+        .await
+        .assert_ok();
+
+    // Users get time to close first
+    dtc.delist_market(market, 20000.).await.assert_err();
+    user.settle_delisted(alice, DexMarket::BTC)
+        .await
+        .assert_err();
+
+    let now = dtc.after(DAY).await;
+    user.mock_btc_price(21000.).await;
+
+    // The settle price must be close to the oracle price
+    dtc.delist_market(market, 23000.).await.assert_err();
+    dtc.delist_market(market, 21000.).await.assert_ok();
+
+    let long = alice.get_position(DexMarket::BTC, true).await;
+    let short = bob.get_position(DexMarket::BTC, false).await;
+    let mi = dtc.market_info(market).await;
+    let borrow_fee = |position: &Position, rate: u16| {
+        (position.borrowed_amount as u128 * rate as u128 * (now - position.last_fill_time) as u128
+            / BORROW_FEE_RATE_BASE
+            / mi.charge_borrow_fee_interval as u128) as u64
+    };
+    let btc_rate = dtc.asset_info(DexAsset::BTC as u8).await.borrow_fee_rate;
+    let usdc_rate = dtc.asset_info(DexAsset::USDC as u8).await.borrow_fee_rate;
+
+    // Orders are drained and the position closed at the settle price
+    user.settle_delisted(alice, DexMarket::BTC)
+        .await
+        .assert_ok();
+    alice.assert_no_order().await;
+    alice
+        .assert_position(DexMarket::BTC, true, 0., 0., 0., 0., 0.)
+        .await;
+
+    let expected_collateral = 0.1 - 0.002912621;
+    let expected_size = expected_collateral * 10.;
+    let expected_profit = expected_size * (21000. - 20000.) / 20000.;
+    let expected_btc = 0.1 + expected_collateral - close_fee(expected_size) + expected_profit
+        - borrow_fee(&long, btc_rate) as f64 / 1e9;
+
+    // The bid refund and the payout are credited to the asset balance
+    alice.assert_btc_balance(0.).await;
+    let btc = alice.asset_amount(DexAsset::BTC).await as f64 / 1e9;
+    assert!((btc - expected_btc).abs() < 0.000001);
+
+    user.settle_delisted(bob, DexMarket::BTC).await.assert_ok();
+    bob.assert_position(DexMarket::BTC, false, 0., 0., 0., 0., 0.)
+        .await;
+
+    let short_size = short.size as f64 / 1e9;
+    let expected_usdc = short.collateral as f64 / 1e6
+        - close_fee(short_size) * 21000.
+        - short_size * (21000. - 20000.)
+        - borrow_fee(&short, usdc_rate) as f64 / 1e6;
+    let usdc = bob.asset_amount(DexAsset::USDC).await as f64 / 1e6;
+    assert!((usdc - expected_usdc).abs() < 0.01);
+
+    user.assert_borrow(DexAsset::BTC, 0.).await;
+    user.assert_collateral(DexAsset::BTC, 0.).await;
+    user.assert_borrow(DexAsset::USDC, 0.).await;
+    user.assert_collateral(DexAsset::USDC, 0.).await;
+
+    // Nothing left to settle, the market can't be delisted again
+    user.settle_delisted(alice, DexMarket::BTC)
+        .await
+        .assert_ok();
+    dtc.delist_market(market, 21000.).await.assert_err();
+}
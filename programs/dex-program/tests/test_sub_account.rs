@@ -0,0 +1,60 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use solana_program_test::tokio;
+
+use crate::utils::{DexAsset, TestResult};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_sub_account_transfer_asset() {
+    let dtc = DexTestContext::new().await;
+    let alice = &dtc.user_context[1];
+    let bob = &dtc.user_context[2];
+
+    // Sub-account 0 is the user state itself
+    alice.create_sub_account(0).await.assert_err();
+    let sub = alice.create_sub_account(1).await.assert_unwrap();
+    dtc.advance_second().await;
+    alice.create_sub_account(1).await.assert_err();
+
+    alice.mint_usdc(1000.).await;
+    alice.deposit_asset(DexAsset::USDC, 1000.).await.assert_ok();
+    alice.assert_usdc_balance(0.).await;
+    alice.assert_asset(DexAsset::USDC, 1000.).await;
+
+    alice
+        .transfer_user_asset(&alice.user_state, &sub, DexAsset::USDC, 600.)
+        .await
+        .assert_ok();
+    alice
+        .transfer_user_asset(&alice.user_state, &sub, DexAsset::USDC, 500.)
+        .await
+        .assert_err();
+    alice.assert_asset(DexAsset::USDC, 400.).await;
+    alice.assert_asset_of(&sub, DexAsset::USDC, 600.).await;
+
+    // Only between sub-accounts of the same owner
+    alice
+        .transfer_user_asset(&sub, &bob.user_state, DexAsset::USDC, 100.)
+        .await
+        .assert_err();
+    bob.transfer_user_asset(&sub, &bob.user_state, DexAsset::USDC, 100.)
+        .await
+        .assert_err();
+    alice
+        .transfer_user_asset(&sub, &sub, DexAsset::USDC, 100.)
+        .await
+        .assert_err();
+
+    // Each sub-account withdraws its own balance
+    alice
+        .withdraw_asset_from(&sub, DexAsset::USDC)
+        .await
+        .assert_ok();
+    alice.assert_usdc_balance(600.).await;
+    alice.withdraw_asset(DexAsset::USDC).await.assert_ok();
+    alice.assert_usdc_balance(1000.).await;
+}
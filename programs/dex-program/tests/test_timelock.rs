@@ -0,0 +1,154 @@
+#![cfg(test)]
+
+mod context;
+mod utils;
+
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::tokio;
+
+use crate::utils::{DexMarket, TestResult, TEST_BTC_OPEN_FEE_RATE};
+use context::DexTestContext;
+
+#[tokio::test]
+async fn test_timelock_update_market() {
+    let dtc = DexTestContext::new().await;
+    let market = DexMarket::BTC as u8;
+    let oracle = dtc.dex_info.borrow().markets[market as usize].oracle;
+
+    // Applied at once without a timelock
+    dtc.update_market_fee_rates(market, 25, 45)
+        .await
+        .assert_ok();
+    let mi = dtc.market_info(market).await;
+    assert_eq!(mi.open_fee_rate, 25);
+    assert_eq!(mi.close_fee_rate, 45);
+
+    dtc.init_timelock(3600).await;
+
+    // Queued once a timelock is attached
+    dtc.update_market_fee_rates(market, 10, u16::MAX)
+        .await
+        .assert_ok();
+    assert_eq!(dtc.market_info(market).await.open_fee_rate, 25);
+
+    dtc.execute_timelock(0, &oracle).await.assert_err();
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_ok();
+
+    let mi = dtc.market_info(market).await;
+    assert_eq!(mi.open_fee_rate, 10);
+    assert_eq!(mi.close_fee_rate, 45);
+
+    // Executed only once
+    dtc.advance_second().await;
+    dtc.execute_timelock(0, &oracle).await.assert_err();
+}
+
+#[tokio::test]
+async fn test_timelock_one_pending_per_market() {
+    let dtc = DexTestContext::new().await;
+    let market = DexMarket::BTC as u8;
+    let oracle = dtc.dex_info.borrow().markets[market as usize].oracle;
+
+    dtc.init_timelock(3600).await;
+
+    dtc.update_market_fee_rates(market, 10, u16::MAX)
+        .await
+        .assert_ok();
+
+    // A second snapshot would revert the first change when executed after it
+    dtc.update_market_fee_rates(market, u16::MAX, 40)
+        .await
+        .assert_err();
+
+    // Other markets can still be queued
+    dtc.update_market_fee_rates(DexMarket::ETH as u8, 10, u16::MAX)
+        .await
+        .assert_ok();
+
+    dtc.cancel_timelock(0).await.assert_ok();
+    dtc.advance_second().await;
+    dtc.cancel_timelock(0).await.assert_err();
+
+    dtc.update_market_fee_rates(market, u16::MAX, 40)
+        .await
+        .assert_ok();
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_err();
+    dtc.execute_timelock(2, &oracle).await.assert_ok();
+
+    let mi = dtc.market_info(market).await;
+    assert_eq!(mi.open_fee_rate, TEST_BTC_OPEN_FEE_RATE);
+    assert_eq!(mi.close_fee_rate, 40);
+}
+
+#[tokio::test]
+async fn test_timelock_protocol_fee_percentage() {
+    let dtc = DexTestContext::new().await;
+    let oracle = dtc.dex_info.borrow().markets[0].oracle;
+
+    dtc.set_protocol_fee_percentage(10).await.assert_ok();
+    assert_eq!(dtc.protocol_fee_percentage().await, 10);
+
+    dtc.init_timelock(3600).await;
+
+    dtc.set_protocol_fee_percentage(100).await.assert_err();
+    dtc.set_protocol_fee_percentage(20).await.assert_ok();
+    assert_eq!(dtc.protocol_fee_percentage().await, 10);
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_ok();
+    assert_eq!(dtc.protocol_fee_percentage().await, 20);
+}
+
+#[tokio::test]
+async fn test_timelock_fee_tiers() {
+    let dtc = DexTestContext::new().await;
+    let oracle = dtc.dex_info.borrow().markets[0].oracle;
+
+    dtc.set_fee_tiers([1000, 5000, 0, 0], [10, 20, 0, 0])
+        .await
+        .assert_ok();
+    assert_eq!(dtc.fee_tiers().await, ([1000, 5000, 0, 0], [10, 20, 0, 0]));
+
+    dtc.init_timelock(3600).await;
+
+    // Invalid tiers are rejected before being queued
+    dtc.set_fee_tiers([1000, 500, 0, 0], [10, 20, 0, 0])
+        .await
+        .assert_err();
+    dtc.set_fee_tiers([1000, 5000, 10000, 0], [10, 20, 30, 0])
+        .await
+        .assert_ok();
+    assert_eq!(dtc.fee_tiers().await, ([1000, 5000, 0, 0], [10, 20, 0, 0]));
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_ok();
+    assert_eq!(
+        dtc.fee_tiers().await,
+        ([1000, 5000, 10000, 0], [10, 20, 30, 0])
+    );
+}
+
+#[tokio::test]
+async fn test_timelock_delegate() {
+    let dtc = DexTestContext::new().await;
+    let oracle = dtc.dex_info.borrow().markets[0].oracle;
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    dtc.set_delegate(&alice).await.assert_ok();
+    assert_eq!(dtc.delegate().await, alice);
+
+    dtc.init_timelock(3600).await;
+
+    // The old delegate stays until the change is due
+    dtc.set_delegate(&bob).await.assert_ok();
+    assert_eq!(dtc.delegate().await, alice);
+    dtc.execute_timelock(0, &oracle).await.assert_err();
+
+    dtc.after(3600).await;
+    dtc.execute_timelock(0, &oracle).await.assert_ok();
+    assert_eq!(dtc.delegate().await, bob);
+}
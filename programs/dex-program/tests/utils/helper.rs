@@ -18,7 +18,7 @@ use dex_program::{
     dex::{Dex, MockOracle, PriceFeed},
     utils::USDC_POW_DECIMALS,
 };
-use solana_program_test::{BanksClient, ProgramTest, ProgramTestContext};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
 
 use {
     anchor_client::{
@@ -34,7 +34,7 @@ pub fn get_dex_program_id() -> Pubkey {
 }
 
 pub async fn get_banks_client(program_id: &Pubkey) -> (BanksClient, Keypair, Hash, Program) {
-    let pt = ProgramTest::new("dex_program", *program_id, None);
+    let pt = ProgramTest::new("dex_program", *program_id, processor!(dex_program::entry));
     let (banks_client, payer, recent_blockhash) = pt.start().await;
     let client = Client::new_with_options(
         Cluster::Debug,
@@ -47,7 +47,11 @@ pub async fn get_banks_client(program_id: &Pubkey) -> (BanksClient, Keypair, Has
 
 pub async fn get_context_and_program() -> (ProgramTestContext, Program) {
     let dex_program_id = get_dex_program_id();
-    let pt = ProgramTest::new("dex_program", dex_program_id, None);
+    let pt = ProgramTest::new(
+        "dex_program",
+        dex_program_id,
+        processor!(dex_program::entry),
+    );
     let context = pt.start_with_context().await;
 
     let client = Client::new_with_options(
@@ -74,7 +78,21 @@ pub async fn get_program() -> Program {
 
 pub async fn get_context() -> Rc<RefCell<ProgramTestContext>> {
     let dex_program_id = get_dex_program_id();
-    let pt = ProgramTest::new("dex_program", dex_program_id, None);
+    let mut pt = ProgramTest::new(
+        "dex_program",
+        dex_program_id,
+        processor!(dex_program::entry),
+    );
+    pt.add_program(
+        "spl_token",
+        spl_token::id(),
+        processor!(spl_token::processor::Processor::process),
+    );
+    pt.add_program(
+        "spl_associated_token_account",
+        spl_associated_token_account::id(),
+        processor!(spl_associated_token_account::processor::process_instruction),
+    );
     let context = pt.start_with_context().await;
 
     Rc::new(RefCell::new(context))
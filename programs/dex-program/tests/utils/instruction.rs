@@ -8,13 +8,18 @@ use anchor_client::{
 use anchor_lang::prelude::{AccountMeta, Pubkey};
 use dex_program::{
     accounts::{
-        AddAsset, AddLiquidity, AddMarket, CancelAllOrders, CancelOrder, ClosePosition, Compound,
-        Crank, CreateUserState, DiBuy, DiCreateOption, DiRemoveOption, DiSetAdmin, DiSetFeeRate,
-        DiSetSettlePrice, DiSettle, DiUpdateOption, DiWithdrawSettled, FeedMockOraclePrice,
-        FillOrder, InitDex, InitMockOracle, LimitAsk, LimitBid, OpenPosition, RedeemVdx,
-        RemoveLiquidity, SetLiquidityFeeRate, StakeVdx, Swap, UpdatePrice, WithdrawAsset,
+        AddAsset, AddLiquidity, AddMarket, AppendOrderPoolPage, CancelAllOrders, CancelOrder,
+        CancelTimelock, ClosePosition, Compound, Crank, CreateReferralCode, CreateSubAccount,
+        CreateUserState, DelistMarket, DepositAsset, DiBuy, DiCreateOption, DiRemoveOption,
+        DiSetAdmin, DiSetFeeRate, DiSetSettlePrice, DiSettle, DiUpdateOption, DiWithdrawSettled,
+        ExecuteTimelock, FeedMockOraclePrice, FillOrder, FlashLoanBegin, FlashLoanEnd, InitDex,
+        InitMockOracle, InitTimelock, LimitAsk, LimitBid, LiquidatePosition, OpenPosition,
+        RedeemVdx, RefreshAum, RemoveLiquidity, SetDelegate, SetFeeTiers, SetLiquidityFeeRate,
+        SetMarginMode, SetPositionLeverage, SetProtocolFeePercentage, SetReferral,
+        SetReferralRates, SetTradingState, SettleBorrowFee, SettleDelisted, StakeVdx, Swap,
+        TransferUserAsset, UpdateMarket, UpdatePrice, WithdrawAsset,
     },
-    utils::MAX_ASSET_COUNT,
+    utils::{FEE_TIER_COUNT, MAX_ASSET_COUNT, NIL8},
 };
 use solana_program_test::ProgramTestContext;
 
@@ -246,24 +251,76 @@ pub async fn compose_init_user_state_ixs(
         .unwrap()
 }
 
+pub async fn compose_create_sub_account_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user_state: &Pubkey,
+    sub_account: u8,
+) -> Instruction {
+    program
+        .request()
+        .accounts(CreateSubAccount {
+            user_state: *user_state,
+            dex: *dex,
+            authority: payer.pubkey(),
+            system_program: system_program::id(),
+        })
+        .args(dex_program::instruction::CreateSubAccount {
+            sub_account,
+            order_slot_count: 8,
+            position_slot_count: 8,
+            di_option_slot_count: 8,
+        })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_transfer_user_asset_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    from_user_state: &Pubkey,
+    to_user_state: &Pubkey,
+    asset: u8,
+    amount: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(TransferUserAsset {
+            from_user_state: *from_user_state,
+            to_user_state: *to_user_state,
+            dex: *dex,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::TransferUserAsset { asset, amount })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
 pub async fn compose_add_liquidity_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
     mint: &Pubkey,
+    oracle: &Pubkey,
     vault: &Pubkey,
     user_mint_acc: &Pubkey,
     event_queue: &Pubkey,
     user_state: &Pubkey,
     price_feed: &Pubkey,
     amount: u64,
-    remaining_accounts: Vec<AccountMeta>,
 ) -> Instruction {
     let add_liquidity_ix = program
         .request()
         .accounts(AddLiquidity {
             dex: *dex,
             mint: *mint,
+            oracle: *oracle,
             vault: *vault,
             user_mint_acc: *user_mint_acc,
             event_queue: *event_queue,
@@ -272,7 +329,6 @@ pub async fn compose_add_liquidity_ix(
             user_state: *user_state,
             price_feed: *price_feed,
         })
-        .accounts(remaining_accounts)
         .args(dex_program::instruction::AddLiquidity { amount })
         .instructions()
         .unwrap()
@@ -287,6 +343,7 @@ pub async fn compose_remove_liquidity_ix(
     payer: &Keypair,
     dex: &Pubkey,
     mint: &Pubkey,
+    oracle: &Pubkey,
     vault: &Pubkey,
     program_signer: &Pubkey,
     user_mint_acc: &Pubkey,
@@ -294,13 +351,13 @@ pub async fn compose_remove_liquidity_ix(
     user_state: &Pubkey,
     price_feed: &Pubkey,
     amount: u64,
-    remaining_accounts: Vec<AccountMeta>,
 ) -> Instruction {
     let remove_liquidity_ix = program
         .request()
         .accounts(RemoveLiquidity {
             dex: *dex,
             mint: *mint,
+            oracle: *oracle,
             vault: *vault,
             program_signer: *program_signer,
             user_mint_acc: *user_mint_acc,
@@ -310,7 +367,6 @@ pub async fn compose_remove_liquidity_ix(
             user_state: *user_state,
             price_feed: *price_feed,
         })
-        .accounts(remaining_accounts)
         .args(dex_program::instruction::RemoveLiquidity { vlp_amount: amount })
         .instructions()
         .unwrap()
@@ -335,10 +391,12 @@ pub async fn compose_open_market_position_ix(
     user_state: &Pubkey,
     event_queue: &Pubkey,
     price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
     market: u8,
     long: bool,
     amount: u64,
     leverage: u32,
+    stable_collateral: bool,
 ) -> Instruction {
     program
         .request()
@@ -358,11 +416,13 @@ pub async fn compose_open_market_position_ix(
             token_program: spl_token::id(),
             price_feed: *price_feed,
         })
+        .accounts(remaining_accounts)
         .args(dex_program::instruction::OpenPosition {
             market,
             long,
             amount,
             leverage,
+            stable_collateral,
         })
         .instructions()
         .unwrap()
@@ -399,7 +459,13 @@ pub async fn compose_close_market_position_ix(
             token_program: spl_token::id(),
             price_feed: *price_feed,
         })
-        .args(dex_program::instruction::ClosePosition { market, long, size })
+        .args(dex_program::instruction::ClosePosition {
+            market,
+            long,
+            size,
+            out_asset: NIL8,
+            minimum_out: 0,
+        })
         .instructions()
         .unwrap()
         .pop()
@@ -474,6 +540,8 @@ pub async fn compose_ask_ix(
     long: bool,
     price: u64,
     size: u64,
+    out_asset: u8,
+    minimum_out: u64,
 ) -> Instruction {
     program
         .request()
@@ -492,6 +560,8 @@ pub async fn compose_ask_ix(
             long,
             price,
             size,
+            out_asset,
+            minimum_out,
         })
         .instructions()
         .unwrap()
@@ -548,6 +618,7 @@ pub async fn compose_crank_ix(
     match_queue: &Pubkey,
     event_queue: &Pubkey,
     price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
 ) -> Instruction {
     program
         .request()
@@ -571,6 +642,7 @@ pub async fn compose_crank_ix(
             system_program: system_program::id(),
             price_feed: *price_feed,
         })
+        .accounts(remaining_accounts)
         .args(dex_program::instruction::Crank {})
         .instructions()
         .unwrap()
@@ -979,149 +1051,780 @@ pub async fn compose_withdraw_asset_ix(
         .unwrap()
 }
 
-pub async fn compose_update_price_ix(
+pub async fn compose_deposit_asset_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    price_feed: &Pubkey,
-    prices: [u64; MAX_ASSET_COUNT],
+    user_state: &Pubkey,
+    user_mint_acc: &Pubkey,
+    vault: &Pubkey,
+    asset: u8,
+    amount: u64,
 ) -> Instruction {
     program
         .request()
-        .accounts(UpdatePrice {
+        .accounts(DepositAsset {
             dex: *dex,
-            price_feed: *price_feed,
+            mint_vault: *vault,
+            user_mint_acc: *user_mint_acc,
+            user_state: *user_state,
             authority: payer.pubkey(),
+            token_program: spl_token::id(),
         })
-        .args(dex_program::instruction::UpdatePrice { prices })
+        .args(dex_program::instruction::DepositAsset { asset, amount })
         .instructions()
         .unwrap()
         .pop()
         .unwrap()
 }
 
-pub async fn compose_compound_ix(
+pub async fn compose_set_margin_mode_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    price_feed: &Pubkey,
     user_state: &Pubkey,
-    vdx_mint: &Pubkey,
-    vdx_program_signer: &Pubkey,
-    vdx_vault: &Pubkey,
-    remaining_accounts: Vec<AccountMeta>,
+    cross_margin: bool,
 ) -> Instruction {
     program
         .request()
-        .accounts(Compound {
-            dex: *dex,
+        .accounts(SetMarginMode {
             user_state: *user_state,
-            price_feed: *price_feed,
-            vdx_program_signer: *vdx_program_signer,
-            vdx_mint: *vdx_mint,
-            vdx_vault: *vdx_vault,
+            dex: *dex,
             authority: payer.pubkey(),
-            token_program: spl_token::id(),
         })
-        .accounts(remaining_accounts)
-        .args(dex_program::instruction::Compound {})
+        .args(dex_program::instruction::SetMarginMode { cross_margin })
         .instructions()
         .unwrap()
         .pop()
         .unwrap()
 }
 
-pub async fn compose_stake_vdx_ix(
+pub async fn compose_set_position_leverage_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
+    oracle: &Pubkey,
+    vault: &Pubkey,
+    program_signer: &Pubkey,
     user_mint_acc: &Pubkey,
-    price_feed: &Pubkey,
     user_state: &Pubkey,
     event_queue: &Pubkey,
-    vdx_mint: &Pubkey,
-    vdx_program_signer: &Pubkey,
-    vdx_vault: &Pubkey,
-    remaining_accounts: Vec<AccountMeta>,
-    amount: u64,
+    price_feed: &Pubkey,
+    market: u8,
+    long: bool,
+    leverage: u32,
 ) -> Instruction {
     program
         .request()
-        .accounts(StakeVdx {
+        .accounts(SetPositionLeverage {
             dex: *dex,
+            oracle: *oracle,
+            vault: *vault,
+            program_signer: *program_signer,
             user_mint_acc: *user_mint_acc,
             user_state: *user_state,
+            authority: payer.pubkey(),
             event_queue: *event_queue,
+            token_program: spl_token::id(),
             price_feed: *price_feed,
-            vdx_program_signer: *vdx_program_signer,
-            vdx_mint: *vdx_mint,
-            vdx_vault: *vdx_vault,
+        })
+        .args(dex_program::instruction::SetPositionLeverage {
+            market,
+            long,
+            leverage,
+        })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_settle_borrow_fee_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user: &Pubkey,
+    user_state: &Pubkey,
+    event_queue: &Pubkey,
+    market: u8,
+    long: bool,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SettleBorrowFee {
+            dex: *dex,
+            user: *user,
+            user_state: *user_state,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::SettleBorrowFee { market, long })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_trading_state_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    event_queue: &Pubkey,
+    scope: u8,
+    index: u8,
+    state: u8,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetTradingState {
+            dex: *dex,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::SetTradingState {
+            scope,
+            index,
+            state,
+        })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_delist_market_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    oracle: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    market: u8,
+    settle_price: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(DelistMarket {
+            dex: *dex,
+            oracle: *oracle,
+            event_queue: *event_queue,
             authority: payer.pubkey(),
-            token_program: spl_token::id(),
         })
         .accounts(remaining_accounts)
-        .args(dex_program::instruction::StakeVdx { amount })
+        .args(dex_program::instruction::DelistMarket {
+            market,
+            settle_price,
+        })
         .instructions()
         .unwrap()
         .pop()
         .unwrap()
 }
 
-pub async fn compose_redeem_vdx_ix(
+#[allow(clippy::too_many_arguments)]
+pub async fn compose_settle_delisted_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    user_mint_acc: &Pubkey,
-    price_feed: &Pubkey,
+    user: &Pubkey,
     user_state: &Pubkey,
+    order_book: &Pubkey,
+    order_pool_entry_page: &Pubkey,
     event_queue: &Pubkey,
-    vdx_mint: &Pubkey,
-    vdx_program_signer: &Pubkey,
-    vdx_vault: &Pubkey,
     remaining_accounts: Vec<AccountMeta>,
-    amount: u64,
+    market: u8,
 ) -> Instruction {
     program
         .request()
-        .accounts(RedeemVdx {
+        .accounts(SettleDelisted {
             dex: *dex,
-            user_mint_acc: *user_mint_acc,
+            user: *user,
             user_state: *user_state,
+            order_book: *order_book,
+            order_pool_entry_page: *order_pool_entry_page,
             event_queue: *event_queue,
-            price_feed: *price_feed,
-            vdx_program_signer: *vdx_program_signer,
-            vdx_mint: *vdx_mint,
-            vdx_vault: *vdx_vault,
             authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::SettleDelisted { market })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_liquidate_position_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user: &Pubkey,
+    user_state: &Pubkey,
+    user_mint_acc: &Pubkey,
+    market_mint: &Pubkey,
+    market_oracle: &Pubkey,
+    market_mint_vault: &Pubkey,
+    program_signer: &Pubkey,
+    order_book: &Pubkey,
+    order_pool_entry_page: &Pubkey,
+    event_queue: &Pubkey,
+    price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    market: u8,
+    long: bool,
+) -> Instruction {
+    program
+        .request()
+        .accounts(LiquidatePosition {
+            dex: *dex,
+            user: *user,
+            user_state: *user_state,
+            user_mint_acc: *user_mint_acc,
+            market_mint: *market_mint,
+            market_oracle: *market_oracle,
+            market_mint_vault: *market_mint_vault,
+            program_signer: *program_signer,
+            order_book: *order_book,
+            order_pool_entry_page: *order_pool_entry_page,
+            authority: payer.pubkey(),
+            event_queue: *event_queue,
             token_program: spl_token::id(),
+            system_program: system_program::id(),
+            price_feed: *price_feed,
         })
         .accounts(remaining_accounts)
-        .args(dex_program::instruction::RedeemVdx { amount })
+        .args(dex_program::instruction::LiquidatePosition { market, long })
         .instructions()
         .unwrap()
         .pop()
         .unwrap()
 }
 
-pub async fn compose_set_liquidity_fee_rate_ix(
+pub async fn compose_update_price_ix(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    index: u8,
-    add_fee_rate: u16,
-    remove_fee_rate: u16,
+    price_feed: &Pubkey,
+    prices: [u64; MAX_ASSET_COUNT],
 ) -> Instruction {
     program
         .request()
-        .accounts(SetLiquidityFeeRate {
+        .accounts(UpdatePrice {
             dex: *dex,
+            price_feed: *price_feed,
             authority: payer.pubkey(),
         })
-        .args(dex_program::instruction::SetLiquidityFeeRate {
-            index,
-            add_fee_rate,
-            remove_fee_rate,
+        .args(dex_program::instruction::UpdatePrice { prices })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_refresh_aum_ix(
+    program: &Program,
+    dex: &Pubkey,
+    price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    program
+        .request()
+        .accounts(RefreshAum {
+            dex: *dex,
+            price_feed: *price_feed,
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::RefreshAum {})
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_compound_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user_state: &Pubkey,
+    vdx_mint: &Pubkey,
+    vdx_program_signer: &Pubkey,
+    vdx_vault: &Pubkey,
+) -> Instruction {
+    program
+        .request()
+        .accounts(Compound {
+            dex: *dex,
+            user_state: *user_state,
+            vdx_program_signer: *vdx_program_signer,
+            vdx_mint: *vdx_mint,
+            vdx_vault: *vdx_vault,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(dex_program::instruction::Compound {})
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_stake_vdx_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user_mint_acc: &Pubkey,
+    user_state: &Pubkey,
+    event_queue: &Pubkey,
+    vdx_mint: &Pubkey,
+    vdx_program_signer: &Pubkey,
+    vdx_vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(StakeVdx {
+            dex: *dex,
+            user_mint_acc: *user_mint_acc,
+            user_state: *user_state,
+            event_queue: *event_queue,
+            vdx_program_signer: *vdx_program_signer,
+            vdx_mint: *vdx_mint,
+            vdx_vault: *vdx_vault,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(dex_program::instruction::StakeVdx { amount })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_lock_vdx_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user_mint_acc: &Pubkey,
+    user_state: &Pubkey,
+    event_queue: &Pubkey,
+    vdx_mint: &Pubkey,
+    vdx_program_signer: &Pubkey,
+    vdx_vault: &Pubkey,
+    amount: u64,
+    lock_tier: u8,
+) -> Instruction {
+    program
+        .request()
+        .accounts(StakeVdx {
+            dex: *dex,
+            user_mint_acc: *user_mint_acc,
+            user_state: *user_state,
+            event_queue: *event_queue,
+            vdx_program_signer: *vdx_program_signer,
+            vdx_mint: *vdx_mint,
+            vdx_vault: *vdx_vault,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(dex_program::instruction::LockVdx { amount, lock_tier })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_redeem_vdx_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    user_mint_acc: &Pubkey,
+    user_state: &Pubkey,
+    event_queue: &Pubkey,
+    vdx_mint: &Pubkey,
+    vdx_program_signer: &Pubkey,
+    vdx_vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(RedeemVdx {
+            dex: *dex,
+            user_mint_acc: *user_mint_acc,
+            user_state: *user_state,
+            event_queue: *event_queue,
+            vdx_program_signer: *vdx_program_signer,
+            vdx_mint: *vdx_mint,
+            vdx_vault: *vdx_vault,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(dex_program::instruction::RedeemVdx { amount })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_liquidity_fee_rate_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    index: u8,
+    add_fee_rate: u16,
+    remove_fee_rate: u16,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetLiquidityFeeRate {
+            dex: *dex,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::SetLiquidityFeeRate {
+            index,
+            add_fee_rate,
+            remove_fee_rate,
+        })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_flash_loan_begin_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    mint: &Pubkey,
+    vault: &Pubkey,
+    program_signer: &Pubkey,
+    user_mint_acc: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(FlashLoanBegin {
+            dex: *dex,
+            mint: *mint,
+            vault: *vault,
+            program_signer: *program_signer,
+            user_mint_acc: *user_mint_acc,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+            instructions: anchor_client::solana_sdk::sysvar::instructions::id(),
+        })
+        .args(dex_program::instruction::FlashLoanBegin { amount })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_flash_loan_end_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    mint: &Pubkey,
+    vault: &Pubkey,
+    user_mint_acc: &Pubkey,
+) -> Instruction {
+    program
+        .request()
+        .accounts(FlashLoanEnd {
+            dex: *dex,
+            mint: *mint,
+            vault: *vault,
+            user_mint_acc: *user_mint_acc,
+            authority: payer.pubkey(),
+            token_program: spl_token::id(),
+        })
+        .args(dex_program::instruction::FlashLoanEnd {})
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_init_timelock_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    timelock: &Pubkey,
+    delay: i64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(InitTimelock {
+            dex: *dex,
+            timelock: *timelock,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::InitTimelock { delay })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_update_market_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    oracle: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    market: u8,
+    minimum_collateral: u64,
+    charge_borrow_fee_interval: u64,
+    open_fee_rate: u16,
+    close_fee_rate: u16,
+    liquidate_fee_rate: u16,
+    liquidate_threshold: u16,
+    max_leverage: u32,
+    oracle_source: u8,
+) -> Instruction {
+    program
+        .request()
+        .accounts(UpdateMarket {
+            dex: *dex,
+            oracle: *oracle,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::UpdateMarket {
+            market,
+            minimum_collateral,
+            charge_borrow_fee_interval,
+            open_fee_rate,
+            close_fee_rate,
+            liquidate_fee_rate,
+            liquidate_threshold,
+            max_leverage,
+            oracle_source,
+        })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_execute_timelock_ix(
+    program: &Program,
+    dex: &Pubkey,
+    timelock: &Pubkey,
+    oracle: &Pubkey,
+    event_queue: &Pubkey,
+    id: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(ExecuteTimelock {
+            dex: *dex,
+            timelock: *timelock,
+            oracle: *oracle,
+            event_queue: *event_queue,
+        })
+        .args(dex_program::instruction::ExecuteTimelock { id })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_cancel_timelock_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    timelock: &Pubkey,
+    event_queue: &Pubkey,
+    id: u64,
+) -> Instruction {
+    program
+        .request()
+        .accounts(CancelTimelock {
+            dex: *dex,
+            timelock: *timelock,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .args(dex_program::instruction::CancelTimelock { id })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_protocol_fee_percentage_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    percentage: u16,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetProtocolFeePercentage {
+            dex: *dex,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::SetProtocolFeePercentage { percentage })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_append_order_pool_page_ixs(
+    context: &mut ProgramTestContext,
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    order_pool_entry_page: &Pubkey,
+    new_page: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    market: u8,
+) -> Vec<Instruction> {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let account_size = 128 * 1024; //128k
+    let account_rent = rent.minimum_balance(account_size);
+
+    program
+        .request()
+        .instruction(system_instruction::create_account(
+            &payer.pubkey(),
+            new_page,
+            account_rent,
+            account_size as u64,
+            &program.id(),
+        ))
+        .accounts(AppendOrderPoolPage {
+            dex: *dex,
+            order_pool_entry_page: *order_pool_entry_page,
+            new_page: *new_page,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::AppendOrderPoolPage { market })
+        .instructions()
+        .unwrap()
+}
+
+pub async fn compose_set_fee_tiers_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    staked: [u64; FEE_TIER_COUNT],
+    discounts: [u16; FEE_TIER_COUNT],
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetFeeTiers {
+            dex: *dex,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::SetFeeTiers { staked, discounts })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_delegate_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    delegate: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetDelegate {
+            dex: *dex,
+            delegate: *delegate,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::SetDelegate {})
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_referral_rates_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    event_queue: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    discount: u16,
+    rebate: u16,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetReferralRates {
+            dex: *dex,
+            event_queue: *event_queue,
+            authority: payer.pubkey(),
+        })
+        .accounts(remaining_accounts)
+        .args(dex_program::instruction::SetReferralRates { discount, rebate })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_create_referral_code_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    referral_code: &Pubkey,
+    user_state: &Pubkey,
+    code: [u8; 16],
+) -> Instruction {
+    program
+        .request()
+        .accounts(CreateReferralCode {
+            referral_code: *referral_code,
+            dex: *dex,
+            user_state: *user_state,
+            authority: payer.pubkey(),
+            system_program: system_program::id(),
+        })
+        .args(dex_program::instruction::CreateReferralCode { code })
+        .instructions()
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+pub async fn compose_set_referral_ix(
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    referral_code: &Pubkey,
+    referrer: &Pubkey,
+    user_state: &Pubkey,
+) -> Instruction {
+    program
+        .request()
+        .accounts(SetReferral {
+            dex: *dex,
+            referral_code: *referral_code,
+            referrer: *referrer,
+            user_state: *user_state,
+            authority: payer.pubkey(),
         })
+        .args(dex_program::instruction::SetReferral {})
         .instructions()
         .unwrap()
         .pop()
@@ -18,10 +18,12 @@ pub mod set_di_update_option;
 pub mod set_di_withdraw_settled;
 pub mod set_feed_mock_oracle;
 pub mod set_fill;
+pub mod set_flash_loan;
 pub mod set_market_swap;
 pub mod set_mock_oracle;
 pub mod set_open;
 pub mod set_redeem_vdx;
+pub mod set_refresh_aum;
 pub mod set_remove_liquidity;
 pub mod set_stake_vdx;
 pub mod set_update_price;
@@ -49,10 +51,12 @@ pub use set_di_update_option::*;
 pub use set_di_withdraw_settled::*;
 pub use set_feed_mock_oracle::*;
 pub use set_fill::*;
+pub use set_flash_loan::*;
 pub use set_market_swap::*;
 pub use set_mock_oracle::*;
 pub use set_open::*;
 pub use set_redeem_vdx::*;
+pub use set_refresh_aum::*;
 pub use set_remove_liquidity::*;
 pub use set_stake_vdx::*;
 pub use set_update_price::*;
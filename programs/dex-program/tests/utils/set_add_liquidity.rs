@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::prelude::Pubkey;
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
 
@@ -19,12 +19,12 @@ pub async fn setup(
     user: &Keypair,
     dex: &Pubkey,
     mint: &Pubkey,
+    oracle: &Pubkey,
     vault: &Pubkey,
     event_queue: &Pubkey,
     user_state: &Pubkey,
     price_feed: &Pubkey,
     amount: u64,
-    remaining_accounts: Vec<AccountMeta>,
 ) -> Result<(), TransportError> {
     let user_wsol_acc = Keypair::new();
 
@@ -42,13 +42,13 @@ pub async fn setup(
         user,
         dex,
         mint,
+        oracle,
         vault,
         &user_mint_acc,
         event_queue,
         &user_state,
         &price_feed,
         amount,
-        remaining_accounts,
     )
     .await;
 
@@ -27,6 +27,8 @@ pub async fn setup(
     long: bool,
     price: u64,
     size: u64,
+    out_asset: u8,
+    minimum_out: u64,
 ) -> Result<(), TransportError> {
     let ask_ix = compose_ask_ix(
         program,
@@ -42,6 +44,8 @@ pub async fn setup(
         long,
         price,
         size,
+        out_asset,
+        minimum_out,
     )
     .await;
 
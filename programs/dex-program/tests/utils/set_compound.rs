@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::prelude::Pubkey;
 use solana_program_test::ProgramTestContext;
 
 use super::compose_compound_ix;
@@ -17,23 +17,19 @@ pub async fn setup(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    price_feed: &Pubkey,
     user_state: &Pubkey,
     vdx_mint: &Pubkey,
     vdx_program_signer: &Pubkey,
     vdx_vault: &Pubkey,
-    remaining_accounts: Vec<AccountMeta>,
 ) -> Result<(), TransportError> {
     let compound_ix = compose_compound_ix(
         program,
         payer,
         dex,
-        price_feed,
         user_state,
         vdx_mint,
         vdx_program_signer,
         vdx_vault,
-        remaining_accounts,
     )
     .await;
 
@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::Pubkey;
+use anchor_lang::prelude::{AccountMeta, Pubkey};
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
 
@@ -33,6 +33,7 @@ pub async fn setup(
     match_queue: &Pubkey,
     event_queue: &Pubkey,
     price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
     create_user_mint_acc: bool,
 ) -> Result<(), TransportError> {
     let user_wsol_acc = Keypair::new();
@@ -82,6 +83,7 @@ pub async fn setup(
         match_queue,
         event_queue,
         price_feed,
+        remaining_accounts,
     )
     .await;
 
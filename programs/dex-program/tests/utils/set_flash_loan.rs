@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+use anchor_client::{
+    solana_sdk::{
+        instruction::Instruction, signature::Keypair, signer::Signer, transaction::Transaction,
+        transport::TransportError,
+    },
+    Program,
+};
+use anchor_lang::prelude::Pubkey;
+use solana_program_test::ProgramTestContext;
+use spl_associated_token_account::get_associated_token_address;
+
+use super::{compose_flash_loan_begin_ix, compose_flash_loan_end_ix};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn setup(
+    context: &mut ProgramTestContext,
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    mint: &Pubkey,
+    vault: &Pubkey,
+    program_signer: &Pubkey,
+    amount: u64,
+    inner_ixs: Vec<Instruction>,
+    repay: bool,
+) -> Result<(), TransportError> {
+    let user_mint_acc = get_associated_token_address(&payer.pubkey(), mint);
+
+    let begin_ix = compose_flash_loan_begin_ix(
+        program,
+        payer,
+        dex,
+        mint,
+        vault,
+        program_signer,
+        &user_mint_acc,
+        amount,
+    )
+    .await;
+
+    let mut instructions: Vec<Instruction> = vec![begin_ix];
+    instructions.extend(inner_ixs);
+
+    if repay {
+        let end_ix =
+            compose_flash_loan_end_ix(program, payer, dex, mint, vault, &user_mint_acc).await;
+        instructions.push(end_ix);
+    }
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        context.banks_client.get_latest_blockhash().await.unwrap(),
+    );
+
+    context
+        .banks_client
+        .process_transaction_with_preflight(transaction)
+        .await
+        .map_err(|e| e.into())
+}
@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::Pubkey;
+use anchor_lang::prelude::{AccountMeta, Pubkey};
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
 
@@ -28,10 +28,12 @@ pub async fn setup(
     user_state: &Pubkey,
     event_queue: &Pubkey,
     price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
     market: u8,
     long: bool,
     amount: u64,
     leverage: u32,
+    stable_collateral: bool,
 ) -> Result<(), TransportError> {
     let user_wsol_acc = Keypair::new();
 
@@ -66,10 +68,12 @@ pub async fn setup(
         user_state,
         event_queue,
         price_feed,
+        remaining_accounts,
         market,
         long,
         amount,
         leverage,
+        stable_collateral,
     )
     .await;
 
@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+use anchor_client::{
+    solana_sdk::{
+        signature::Keypair, signer::Signer, transaction::Transaction, transport::TransportError,
+    },
+    Program,
+};
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use solana_program_test::ProgramTestContext;
+
+use super::compose_refresh_aum_ix;
+
+pub async fn setup(
+    context: &mut ProgramTestContext,
+    program: &Program,
+    payer: &Keypair,
+    dex: &Pubkey,
+    price_feed: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Result<(), TransportError> {
+    let refresh_aum_ix = compose_refresh_aum_ix(program, dex, price_feed, remaining_accounts).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[refresh_aum_ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        context.banks_client.get_latest_blockhash().await.unwrap(),
+    );
+
+    context
+        .banks_client
+        .process_transaction_with_preflight(transaction)
+        .await
+        .map_err(|e| e.into())
+}
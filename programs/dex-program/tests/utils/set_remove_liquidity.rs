@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::prelude::Pubkey;
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
 
@@ -19,13 +19,13 @@ pub async fn setup(
     user: &Keypair,
     dex: &Pubkey,
     mint: &Pubkey,
+    oracle: &Pubkey,
     vault: &Pubkey,
     program_signer: &Pubkey,
     event_queue: &Pubkey,
     user_state: &Pubkey,
     price_feed: &Pubkey,
     vlp_amount: u64,
-    remaining_accounts: Vec<AccountMeta>,
 ) -> Result<(), TransportError> {
     let user_wsol_acc = Keypair::new();
 
@@ -43,6 +43,7 @@ pub async fn setup(
         user,
         dex,
         mint,
+        oracle,
         vault,
         program_signer,
         &user_mint_acc,
@@ -50,7 +51,6 @@ pub async fn setup(
         &user_state,
         &price_feed,
         vlp_amount,
-        remaining_accounts,
     )
     .await;
 
@@ -6,7 +6,7 @@ use anchor_client::{
     },
     Program,
 };
-use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::prelude::Pubkey;
 use solana_program_test::ProgramTestContext;
 use spl_associated_token_account::get_associated_token_address;
 
@@ -18,13 +18,11 @@ pub async fn setup(
     program: &Program,
     payer: &Keypair,
     dex: &Pubkey,
-    price_feed: &Pubkey,
     user_state: &Pubkey,
     event_queue: &Pubkey,
     vdx_mint: &Pubkey,
     vdx_program_signer: &Pubkey,
     vdx_vault: &Pubkey,
-    remaining_accounts: Vec<AccountMeta>,
     amount: u64,
 ) -> Result<(), TransportError> {
     let user_mint_acc = get_associated_token_address(&payer.pubkey(), vdx_mint);
@@ -34,13 +32,11 @@ pub async fn setup(
         payer,
         dex,
         &user_mint_acc,
-        price_feed,
         user_state,
         event_queue,
         vdx_mint,
         vdx_program_signer,
         vdx_vault,
-        remaining_accounts,
         amount,
     )
     .await;